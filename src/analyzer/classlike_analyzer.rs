@@ -83,7 +83,12 @@ impl<'a> ClassLikeAnalyzer<'a> {
         let mut class_context = BlockContext::new(function_context);
 
         let mut analysis_data = FunctionAnalysisData::new(
-            DataFlowGraph::new(statements_analyzer.get_config().graph_kind),
+            DataFlowGraph::new(statements_analyzer.get_config().graph_kind).with_traversal_counts(
+                analysis_result
+                    .program_dataflow_graph
+                    .traversal_counts
+                    .clone(),
+            ),
             &statements_analyzer.file_analyzer.file_source,
             &statements_analyzer.comments,
             &statements_analyzer.get_config().all_custom_issues,
@@ -3,6 +3,8 @@ use std::{error::Error, fs::File, io::BufReader, path::Path};
 use rustc_hash::FxHashMap;
 use serde::Deserialize;
 
+use super::DeprecatedUsageLevel;
+
 #[derive(Deserialize, Debug)]
 pub struct JsonConfig {
     #[serde(default)]
@@ -17,6 +19,18 @@ pub struct JsonConfig {
     pub allowed_issues: Vec<String>,
     #[serde(default)]
     pub test_files: Vec<String>,
+    #[serde(default)]
+    pub generated_files: Vec<String>,
+    #[serde(default)]
+    pub deprecated_usage: Option<DeprecatedUsageLevel>,
+    #[serde(default)]
+    pub symbol_time_budget_ms: Option<u64>,
+    #[serde(default)]
+    pub symbol_statement_budget: Option<usize>,
+    #[serde(default)]
+    pub max_type_alias_atomics: Option<usize>,
+    #[serde(default)]
+    pub trim_property_reads: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Default)]
@@ -1,12 +1,14 @@
-use std::{error::Error, path::Path};
+use std::{error::Error, path::Path, time::Duration};
 
 use hakana_code_info::{
     data_flow::{graph::GraphKind, tainted_node::TaintedNode},
-    issue::{Issue, IssueKind},
+    issue::{Issue, IssueKind, IssueSeverity},
     taint::{SinkType, SourceType},
+    EFFECT_PURE, EFFECT_READ_GLOBALS, EFFECT_READ_PROPS,
 };
 use hakana_str::{Interner, StrId};
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
 
 use crate::custom_hook::CustomHook;
 
@@ -24,6 +26,9 @@ pub struct Config {
     pub graph_kind: GraphKind,
     pub ignore_files: Vec<String>,
     pub test_files: Vec<glob::Pattern>,
+    /// Files (e.g. generated code) in which type aliases are always fully expanded
+    /// during analysis, so checks see the concrete types
+    pub generated_files: Vec<glob::Pattern>,
     pub ignore_issue_patterns: FxHashMap<IssueKind, Vec<glob::Pattern>>,
     pub ignore_all_issues_in_patterns: Vec<glob::Pattern>,
     pub banned_builtin_functions: FxHashMap<StrId, StrId>,
@@ -38,6 +43,41 @@ pub struct Config {
     pub ast_diff: bool,
     pub max_changes_allowed: usize,
     pub collect_goto_definition_locations: bool,
+    /// Record issues dropped by fixme and ignore comments in the analysis result, so
+    /// they can be audited
+    pub collect_suppressed_issues: bool,
+    /// Number of threads to use when analyzing files, if different from the number
+    /// used for scanning
+    pub analysis_threads: Option<u8>,
+    /// Effects an unused assignment's value can have while still allowing the whole
+    /// statement to be removed when fixing it
+    pub trimmable_assignment_effects: Vec<u8>,
+    /// Report issue columns in Unicode scalar values rather than bytes, as editors
+    /// expect
+    pub char_columns: bool,
+    /// How references to `<<__Deprecated>>` functions and classes are reported
+    pub deprecated_usage: DeprecatedUsageLevel,
+    /// When diffing, re-analyze up to this many files whose issues were carried over
+    /// from the previous run and log any that don't match a fresh analysis
+    pub verify_cached_issues: Option<usize>,
+    /// Analysis of a single function or method is abandoned once it has taken longer
+    /// than this, so one pathological symbol can't hold up the whole run
+    pub symbol_time_budget: Option<Duration>,
+    /// Like `symbol_time_budget`, but counts analyzed statements rather than time,
+    /// so the cutoff is the same on every run
+    pub symbol_statement_budget: Option<usize>,
+    /// Type aliases whose expansion visits more than this many atomics are replaced by
+    /// their `as` constraint, and reported where they're defined
+    pub max_type_alias_atomics: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeprecatedUsageLevel {
+    Error,
+    /// Reported, but doesn't make the run fail
+    Warning,
+    Ignore,
 }
 
 #[derive(Clone, Debug)]
@@ -75,6 +115,7 @@ impl Config {
             graph_kind: GraphKind::FunctionBody,
             ignore_files: Vec::new(),
             test_files: Vec::new(),
+            generated_files: Vec::new(),
             ignore_issue_patterns: FxHashMap::default(),
             ignore_all_issues_in_patterns: vec![],
             security_config: SecurityConfig::new(),
@@ -90,6 +131,15 @@ impl Config {
             banned_builtin_functions: FxHashMap::default(),
             max_changes_allowed: 5000,
             collect_goto_definition_locations: false,
+            collect_suppressed_issues: false,
+            analysis_threads: None,
+            trimmable_assignment_effects: vec![EFFECT_PURE, EFFECT_READ_GLOBALS, EFFECT_READ_PROPS],
+            char_columns: false,
+            deprecated_usage: DeprecatedUsageLevel::Warning,
+            verify_cached_issues: None,
+            symbol_time_budget: None,
+            symbol_statement_budget: None,
+            max_type_alias_atomics: None,
         }
     }
 
@@ -113,6 +163,12 @@ impl Config {
             .map(|v| glob::Pattern::new(&format!("{}/{}", cwd, v)).unwrap())
             .collect();
 
+        self.generated_files = json_config
+            .generated_files
+            .into_iter()
+            .map(|v| glob::Pattern::new(&format!("{}/{}", cwd, v)).unwrap())
+            .collect();
+
         self.ignore_issue_patterns = json_config
             .ignore_issue_files
             .iter()
@@ -154,6 +210,26 @@ impl Config {
             .map(|(k, v)| (interner.intern(k), interner.intern(v)))
             .collect();
 
+        if let Some(deprecated_usage) = json_config.deprecated_usage {
+            self.deprecated_usage = deprecated_usage;
+        }
+
+        if let Some(symbol_time_budget_ms) = json_config.symbol_time_budget_ms {
+            self.symbol_time_budget = Some(Duration::from_millis(symbol_time_budget_ms));
+        }
+
+        if let Some(symbol_statement_budget) = json_config.symbol_statement_budget {
+            self.symbol_statement_budget = Some(symbol_statement_budget);
+        }
+
+        if let Some(max_type_alias_atomics) = json_config.max_type_alias_atomics {
+            self.max_type_alias_atomics = Some(max_type_alias_atomics);
+        }
+
+        if let Some(trim_property_reads) = json_config.trim_property_reads {
+            self.set_trim_property_reads(trim_property_reads);
+        }
+
         self.security_config.ignore_patterns = json_config
             .security_analysis
             .ignore_files
@@ -178,6 +254,16 @@ impl Config {
         Ok(())
     }
 
+    /// Whether an unused assignment whose value reads properties can still be removed
+    /// in its entirety when fixing it
+    pub fn set_trim_property_reads(&mut self, trim_property_reads: bool) {
+        self.trimmable_assignment_effects = if trim_property_reads {
+            vec![EFFECT_PURE, EFFECT_READ_GLOBALS, EFFECT_READ_PROPS]
+        } else {
+            vec![EFFECT_PURE, EFFECT_READ_GLOBALS]
+        };
+    }
+
     pub fn can_add_issue(&self, issue: &Issue) -> bool {
         if let Some(issue_filter) = &self.allowed_issues {
             if !issue_filter.contains(&issue.kind) {
@@ -188,6 +274,18 @@ impl Config {
         true
     }
 
+    /// Every output format presents issues with this severity, so they agree on
+    /// whether the run failed
+    pub fn get_issue_severity(&self, issue_kind: &IssueKind) -> IssueSeverity {
+        if *issue_kind == IssueKind::DeprecatedUsage
+            && self.deprecated_usage == DeprecatedUsageLevel::Warning
+        {
+            IssueSeverity::Warning
+        } else {
+            IssueSeverity::Error
+        }
+    }
+
     pub fn allow_issues_in_file(&self, file: &str) -> bool {
         for ignore_pattern in &self.ignore_all_issues_in_patterns {
             if ignore_pattern.matches(file) {
@@ -13,7 +13,7 @@ use std::rc::Rc;
 use std::time::Instant;
 
 use crate::config::Config;
-use hakana_code_info::data_flow::graph::DataFlowGraph;
+use hakana_code_info::data_flow::graph::{DataFlowGraph, MAX_TRAVERSAL_DEPTH};
 use hakana_code_info::data_flow::path::ArrayDataKind;
 use hakana_code_info::data_flow::path::PathKind;
 use hakana_code_info::data_flow::tainted_node::TaintedNode;
@@ -29,16 +29,57 @@ pub fn find_tainted_data(
 ) -> Vec<Issue> {
     let mut new_issues = vec![];
 
+    let sink_ids = graph.sinks.keys().cloned().collect::<FxHashSet<_>>();
+
+    // Sources that can't reach any sink don't need tracing. The check only ignores
+    // aggregate paths, which the traversal below never follows either, so it can't
+    // drop a real flow unless it's searching less deeply than the traversal would.
+    let can_skip_unreachable_sources =
+        config.security_config.max_depth as usize <= MAX_TRAVERSAL_DEPTH;
+
     let sources = graph
         .sources
         .values()
+        .filter(|source| {
+            !can_skip_unreachable_sources
+                || graph.reaches_any(&source.id, &sink_ids, |path, _| {
+                    matches!(path.kind, PathKind::Aggregate)
+                })
+        })
         .map(|v| Rc::new(TaintedNode::from(v)))
         .collect::<Vec<_>>();
 
     logger.log_sync("Security analysis: detecting paths");
-    logger.log_sync(&format!(" - initial sources count: {}", sources.len()));
+    logger.log_sync(&format!(
+        " - initial sources count: {} of {}",
+        sources.len(),
+        graph.sources.len()
+    ));
     logger.log_sync(&format!(" - initial sinks count:   {}", graph.sinks.len()));
 
+    if matches!(
+        logger.get_verbosity(),
+        Verbosity::Debugging | Verbosity::DebuggingByLine
+    ) {
+        let sink_types = graph
+            .sinks
+            .values()
+            .flat_map(|sink| match &sink.kind {
+                DataFlowNodeKind::TaintSink { types, .. } => types.clone(),
+                _ => vec![],
+            })
+            .unique()
+            .sorted_by_key(|sink_type| sink_type.to_string());
+
+        for sink_type in sink_types {
+            logger.log_debug_sync(&format!(
+                " - sources that can taint {} sinks: {}",
+                sink_type,
+                graph.sources_for_sink_type(&sink_type).len()
+            ));
+        }
+    }
+
     // println!("{:#?}", graph);
 
     // for (sink_id, _) in &graph.sinks {
@@ -480,7 +521,7 @@ fn has_recent_assignment(generated_path_types: &[PathKind]) -> bool {
     let filtered_paths = generated_path_types
         .iter()
         .rev()
-        .filter(|t| !matches!(t, PathKind::Default));
+        .filter(|t| !matches!(t, PathKind::Default | PathKind::InoutWriteback));
 
     let mut nesting = 0;
 
@@ -516,7 +557,7 @@ fn has_unmatched_property_assignment(symbol: &StrId, generated_path_types: &[Pat
     let filtered_paths = generated_path_types
         .iter()
         .rev()
-        .filter(|t| !matches!(t, PathKind::Default));
+        .filter(|t| !matches!(t, PathKind::Default | PathKind::InoutWriteback));
 
     let mut nesting = 0;
 
@@ -614,7 +655,7 @@ pub(crate) fn should_ignore_array_fetch(
             if let Some(PathKind::ArrayAssignment(ArrayDataKind::ArrayValue, assigned_name)) =
                 previous_path_types
                     .iter()
-                    .filter(|t| !matches!(t, PathKind::Default))
+                    .filter(|t| !matches!(t, PathKind::Default | PathKind::InoutWriteback))
                     .last()
             {
                 if assigned_name == key_name {
@@ -3,10 +3,9 @@ use hakana_code_info::code_location::HPos;
 use hakana_code_info::data_flow::node::DataFlowNodeId;
 use hakana_code_info::data_flow::node::DataFlowNodeKind;
 use hakana_code_info::data_flow::node::VariableSourceKind;
+use hakana_code_info::data_flow::path::DataFlowPath;
 use hakana_code_info::data_flow::path::PathKind;
-use hakana_code_info::EFFECT_PURE;
-use hakana_code_info::EFFECT_READ_GLOBALS;
-use hakana_code_info::EFFECT_READ_PROPS;
+use hakana_str::StrId;
 use oxidized::{
     aast,
     aast_visitor::{visit, AstParams, Node, Visitor},
@@ -14,7 +13,6 @@ use oxidized::{
 use rustc_hash::FxHashMap;
 use rustc_hash::FxHashSet;
 use std::collections::BTreeMap;
-use std::rc::Rc;
 
 use crate::dataflow::program_analyzer::{should_ignore_array_fetch, should_ignore_property_fetch};
 use crate::function_analysis_data::FunctionAnalysisData;
@@ -31,7 +29,10 @@ enum VariableUsage {
     Used,
 }
 
-pub fn check_variables_used(graph: &DataFlowGraph) -> (Vec<DataFlowNode>, Vec<DataFlowNode>) {
+pub fn check_variables_used(
+    graph: &DataFlowGraph,
+    unreachable_loop_bodies: &[(u32, u32)],
+) -> (Vec<DataFlowNode>, Vec<DataFlowNode>) {
     let vars = graph
         .sources
         .iter()
@@ -60,11 +61,40 @@ pub fn check_variables_used(graph: &DataFlowGraph) -> (Vec<DataFlowNode>, Vec<Da
     //     }
     // }
 
+    // a variable is used once its value reaches a sink, or is written back to the
+    // caller through an inout param
+    let mut used_node_ids = graph.sinks.keys().cloned().collect::<FxHashSet<_>>();
+
+    for forward_edges in graph.forward_edges.values() {
+        for (to_id, path) in forward_edges {
+            if matches!(path.kind, PathKind::InoutWriteback) {
+                used_node_ids.insert(to_id.clone());
+            }
+        }
+    }
+
     let mut unused_nodes = Vec::new();
     let mut unused_but_referenced_nodes = Vec::new();
 
-    for (_, source_node) in vars {
-        match is_variable_used(graph, source_node) {
+    for ((start_offset, end_offset), source_node) in vars {
+        // assignments in a loop body that never runs are dead whatever reads them
+        let in_unreachable_loop_body = matches!(
+            source_node.kind,
+            DataFlowNodeKind::VariableUseSource {
+                kind: VariableSourceKind::Default,
+                ..
+            }
+        ) && unreachable_loop_bodies
+            .iter()
+            .any(|(body_start, body_end)| start_offset >= *body_start && end_offset <= *body_end);
+
+        let usage = if in_unreachable_loop_body {
+            VariableUsage::NeverReferenced
+        } else {
+            is_variable_used(graph, source_node, &used_node_ids)
+        };
+
+        match usage {
             VariableUsage::NeverReferenced => {
                 if let DataFlowNode {
                     kind:
@@ -420,104 +450,44 @@ fn get_all_variable_uses(
     }
 }
 
-fn is_variable_used(graph: &DataFlowGraph, source_node: &DataFlowNode) -> VariableUsage {
-    let mut visited_source_ids = FxHashSet::default();
-
-    let mut sources = FxHashMap::default();
-
-    let source_node = VariableUseNode::from(source_node);
-    sources.insert(source_node.0.clone(), source_node.1);
-
-    let mut i = 0;
-
-    while i < 200 {
-        if sources.is_empty() {
-            break;
-        }
-
-        let mut new_child_nodes = FxHashMap::default();
-
-        for (id, source) in &sources {
-            visited_source_ids.insert(id.clone());
-
-            let child_nodes = get_variable_child_nodes(graph, id, source, &visited_source_ids);
-
-            if let Some(child_nodes) = child_nodes {
-                new_child_nodes.extend(child_nodes);
-            } else {
-                return VariableUsage::Used;
-            }
-        }
-
-        sources = new_child_nodes;
-
-        i += 1;
+fn is_variable_used(
+    graph: &DataFlowGraph,
+    source_node: &DataFlowNode,
+    used_node_ids: &FxHashSet<DataFlowNodeId>,
+) -> VariableUsage {
+    if graph.reaches_any(&source_node.id, used_node_ids, should_ignore_path) {
+        return VariableUsage::Used;
     }
 
-    if i == 1 {
-        VariableUsage::NeverReferenced
-    } else {
+    let is_referenced = graph
+        .forward_edges
+        .get(&source_node.id)
+        .is_some_and(|forward_edges| {
+            forward_edges
+                .iter()
+                .any(|(to_id, path)| to_id != &source_node.id && !should_ignore_path(path, &[]))
+        });
+
+    if is_referenced {
         VariableUsage::ReferencedButNotUsed
+    } else {
+        VariableUsage::NeverReferenced
     }
 }
 
-fn get_variable_child_nodes(
-    graph: &DataFlowGraph,
-    generated_source_id: &DataFlowNodeId,
-    generated_source: &VariableUseNode,
-    visited_source_ids: &FxHashSet<DataFlowNodeId>,
-) -> Option<FxHashMap<DataFlowNodeId, VariableUseNode>> {
-    let mut new_child_nodes = FxHashMap::default();
-
-    if let Some(forward_edges) = graph.forward_edges.get(generated_source_id) {
-        for (to_id, path) in forward_edges {
-            if graph.sinks.contains_key(to_id) {
-                return None;
-            }
-
-            if visited_source_ids.contains(to_id) {
-                continue;
-            }
-
-            if should_ignore_array_fetch(
-                &path.kind,
-                &ArrayDataKind::ArrayKey,
-                &generated_source.path_types,
-            ) {
-                continue;
-            }
-
-            if should_ignore_array_fetch(
-                &path.kind,
-                &ArrayDataKind::ArrayValue,
-                &generated_source.path_types,
-            ) {
-                continue;
-            }
-
-            if should_ignore_property_fetch(&path.kind, &generated_source.path_types) {
-                continue;
-            }
-
-            let mut new_destination = VariableUseNode {
-                path_types: generated_source.clone().path_types,
-                kind: generated_source.kind.clone(),
-                pos: generated_source.pos.clone(),
-            };
-
-            new_destination.path_types.push(path.kind.clone());
-
-            new_child_nodes.insert(to_id.clone(), new_destination);
-        }
-    }
-
-    Some(new_child_nodes)
+// array and property fetches only count when they read back a value that was
+// assigned earlier on the same path
+fn should_ignore_path(path: &DataFlowPath, previous_path_types: &[PathKind]) -> bool {
+    should_ignore_array_fetch(&path.kind, &ArrayDataKind::ArrayKey, previous_path_types)
+        || should_ignore_array_fetch(&path.kind, &ArrayDataKind::ArrayValue, previous_path_types)
+        || should_ignore_property_fetch(&path.kind, previous_path_types)
 }
 
 struct Scanner<'a> {
     pub unused_variable_nodes: &'a Vec<DataFlowNode>,
     pub comments: &'a Vec<(Pos, Comment)>,
     pub in_single_block: bool,
+    pub trimmable_effects: &'a [u8],
 }
 
 impl<'ast> Visitor<'ast> for Scanner<'_> {
@@ -532,6 +502,62 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
         analysis_data: &mut FunctionAnalysisData,
         expr: &aast::Expr<(), ()>,
     ) -> Result<(), ()> {
+        if let aast::Expr_::Assign(boxed) = &expr.2 {
+            if let aast::Expr_::List(exprs) = &boxed.0 .2 {
+                if exprs
+                    .iter()
+                    .any(|list_expr| self.is_unused_variable(list_expr))
+                {
+                    let used_exprs = exprs
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, list_expr)| {
+                            !self.is_unused_variable(list_expr) && !is_list_placeholder(list_expr)
+                        })
+                        .collect::<Vec<_>>();
+
+                    // when none of the list's items are used, `list($a, $b) = foo()` becomes
+                    // just `foo()`, and when only one is, `list($a, $b) = foo()` becomes
+                    // `$b = foo()[1]`. The replacement claims the whole list so the
+                    // per-item rewrites below are skipped
+                    match used_exprs[..] {
+                        [] => {
+                            analysis_data.add_replacement(
+                                (expr.1.start_offset() as u32, expr.1.end_offset() as u32),
+                                Replacement::ReplaceRange(
+                                    (
+                                        expr.1.start_offset() as u32,
+                                        boxed.2 .1.start_offset() as u32,
+                                    ),
+                                    "".to_string(),
+                                ),
+                            );
+                        }
+                        [(offset, aast::Expr(_, _, aast::Expr_::Lvar(lid)))] => {
+                            if analysis_data.add_replacement(
+                                (
+                                    boxed.0 .1.start_offset() as u32,
+                                    boxed.0 .1.end_offset() as u32,
+                                ),
+                                Replacement::Substitute(lid.name().to_string()),
+                            ) {
+                                let rhs_start = boxed.2 .1.start_offset() as u32;
+                                let rhs_end = boxed.2 .1.end_offset() as u32;
+
+                                if can_index_without_parens(&boxed.2) {
+                                    analysis_data.insert_at(rhs_end, format!("[{}]", offset));
+                                } else {
+                                    analysis_data.insert_at(rhs_start, "(".to_string());
+                                    analysis_data.insert_at(rhs_end, format!(")[{}]", offset));
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
         if let aast::Expr_::List(exprs) = &expr.2 {
             for list_expr in exprs {
                 let has_matching_node = self.unused_variable_nodes.iter().any(|n| match &n.kind {
@@ -595,16 +621,25 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                         ))
                         .unwrap_or(&0);
 
-                    if let EFFECT_PURE | EFFECT_READ_GLOBALS | EFFECT_READ_PROPS =
-                        *expression_effects
-                    {
+                    if self.trimmable_effects.contains(expression_effects) {
                         if !self.in_single_block {
-                            let span = stmt.0.to_raw_span();
+                            let beg_of_line = stmt.0.to_raw_span().start.beg_of_line() as u32;
+
+                            // a comment after the statement on the same line describes it, so
+                            // it goes too rather than being left dangling
                             analysis_data.add_replacement(
                                 (stmt.0.start_offset() as u32, stmt.0.end_offset() as u32),
-                                Replacement::TrimPrecedingWhitespace(
-                                    span.start.beg_of_line() as u32
-                                ),
+                                if let Some(comment_pos) = self.get_trailing_comment(stmt) {
+                                    Replacement::TrimPrecedingWhitespaceAndTrailingComment(
+                                        beg_of_line,
+                                        (
+                                            comment_pos.start_offset() as u32,
+                                            comment_pos.end_offset() as u32,
+                                        ),
+                                    )
+                                } else {
+                                    Replacement::TrimPrecedingWhitespace(beg_of_line)
+                                },
                             );
 
                             self.remove_fixme_comments(stmt, analysis_data, stmt.0.start_offset());
@@ -629,9 +664,7 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                                     ))
                                     .unwrap_or(&0);
 
-                                if let EFFECT_PURE | EFFECT_READ_GLOBALS | EFFECT_READ_PROPS =
-                                    *array_offset_effects
-                                {
+                                if self.trimmable_effects.contains(array_offset_effects) {
                                     analysis_data.add_replacement(
                                         (
                                             array_offset_expr.pos().start_offset() as u32 - 1,
@@ -652,6 +685,28 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
 }
 
 impl<'a> Scanner<'a> {
+    fn is_unused_variable(&self, expr: &aast::Expr<(), ()>) -> bool {
+        self.unused_variable_nodes.iter().any(|n| match &n.kind {
+            DataFlowNodeKind::VariableUseSource { pos, .. } => {
+                pos.start_offset == expr.1.start_offset() as u32
+            }
+            _ => false,
+        })
+    }
+
+    fn get_trailing_comment(&self, stmt: &aast::Stmt<(), ()>) -> Option<&'a Pos> {
+        let stmt_end_line = stmt.0.to_raw_span().end.line() as usize;
+
+        self.comments
+            .iter()
+            .filter(|(comment_pos, _)| {
+                comment_pos.line() == stmt_end_line
+                    && comment_pos.start_offset() >= stmt.0.end_offset()
+            })
+            .map(|(comment_pos, _)| comment_pos)
+            .min_by_key(|comment_pos| comment_pos.start_offset())
+    }
+
     fn remove_fixme_comments(
         &mut self,
         stmt: &aast::Stmt<(), ()>,
@@ -695,16 +750,38 @@ impl<'a> Scanner<'a> {
     }
 }
 
+/// Removes unused assignments. Statements whose assigned value has one of the
+/// `trimmable_effects` are removed entirely, otherwise just the assignment is removed.
 pub(crate) fn add_unused_expression_replacements(
     stmts: &Vec<aast::Stmt<(), ()>>,
     analysis_data: &mut FunctionAnalysisData,
     unused_source_nodes: &Vec<DataFlowNode>,
     statements_analyzer: &StatementsAnalyzer,
+    trimmable_effects: &[u8],
+) {
+    let replacements = get_unused_expression_replacements(
+        stmts,
+        analysis_data,
+        unused_source_nodes,
+        statements_analyzer,
+        trimmable_effects,
+    );
+
+    analysis_data.replacements.extend(replacements);
+}
+
+fn scan_unused_expressions(
+    stmts: &Vec<aast::Stmt<(), ()>>,
+    analysis_data: &mut FunctionAnalysisData,
+    unused_source_nodes: &Vec<DataFlowNode>,
+    statements_analyzer: &StatementsAnalyzer,
+    trimmable_effects: &[u8],
 ) {
     let mut scanner = Scanner {
         unused_variable_nodes: unused_source_nodes,
         comments: statements_analyzer.file_analyzer.file_source.comments,
         in_single_block: false,
+        trimmable_effects,
     };
 
     for stmt in stmts {
@@ -712,37 +789,267 @@ pub(crate) fn add_unused_expression_replacements(
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct VariableUseNode {
-    pub pos: Rc<HPos>,
-    pub path_types: Vec<PathKind>,
-    pub kind: VariableSourceKind,
+fn can_index_without_parens(expr: &aast::Expr<(), ()>) -> bool {
+    matches!(
+        expr.2,
+        aast::Expr_::Lvar(_)
+            | aast::Expr_::Call(_)
+            | aast::Expr_::ArrayGet(_)
+            | aast::Expr_::ObjGet(_)
+            | aast::Expr_::ClassGet(_)
+            | aast::Expr_::ClassConst(_)
+    )
 }
 
-impl VariableUseNode {
-    pub fn from(node: &DataFlowNode) -> (DataFlowNodeId, Self) {
-        (
-            node.id.clone(),
-            match &node.kind {
-                DataFlowNodeKind::Vertex { pos, .. } => Self {
-                    pos: Rc::new((*pos).unwrap()),
-                    path_types: Vec::new(),
-                    kind: VariableSourceKind::Default,
-                },
-                DataFlowNodeKind::VariableUseSource { kind, pos, .. } => Self {
-                    pos: Rc::new(*pos),
-                    path_types: Vec::new(),
-                    kind: kind.clone(),
-                },
-                DataFlowNodeKind::VariableUseSink { pos } => Self {
-                    pos: Rc::new(*pos),
-                    path_types: Vec::new(),
+fn is_list_placeholder(expr: &aast::Expr<(), ()>) -> bool {
+    match &expr.2 {
+        aast::Expr_::Omitted => true,
+        aast::Expr_::Lvar(lid) => lid.name() == "$_",
+        _ => false,
+    }
+}
+
+/// Returns the start offsets of unused assignments whose value is overwritten on every
+/// path before the variable is next read: the variable is read again later on, every
+/// read sees a later assignment, and the value isn't still held when the function exits.
+pub(crate) fn get_dead_store_offsets(
+    graph: &DataFlowGraph,
+    unused_source_nodes: &(Vec<DataFlowNode>, Vec<DataFlowNode>),
+    node_ids_at_exit: &FxHashSet<DataFlowNodeId>,
+    closure_spans: &[(u32, u32)],
+) -> FxHashSet<u32> {
+    let unused_node_ids = unused_source_nodes
+        .0
+        .iter()
+        .chain(unused_source_nodes.1.iter())
+        .map(|node| &node.id)
+        .collect::<FxHashSet<_>>();
+
+    // the offsets of assignments to each variable whose values are read
+    let mut used_assignment_offsets = FxHashMap::default();
+
+    for (node_id, node) in &graph.sources {
+        if let (
+            DataFlowNodeId::Var(var_id, ..),
+            DataFlowNodeKind::VariableUseSource {
+                pos,
+                kind: VariableSourceKind::Default | VariableSourceKind::InoutArg,
+                ..
+            },
+        ) = (node_id, &node.kind)
+        {
+            if !unused_node_ids.contains(node_id) {
+                used_assignment_offsets
+                    .entry(var_id)
+                    .or_insert_with(Vec::new)
+                    .push(pos.start_offset);
+            }
+        }
+    }
+
+    // an assignment inside a closure can't overwrite one outside it, and vice versa
+    let get_closure_span = |offset: u32| {
+        closure_spans
+            .iter()
+            .filter(|(start, end)| offset > *start && offset < *end)
+            .min_by_key(|(start, end)| end - start)
+    };
+
+    unused_source_nodes
+        .0
+        .iter()
+        .chain(unused_source_nodes.1.iter())
+        .filter_map(|node| {
+            if let (
+                DataFlowNodeId::Var(var_id, ..),
+                DataFlowNodeKind::VariableUseSource {
+                    pos,
                     kind: VariableSourceKind::Default,
+                    ..
                 },
-                _ => {
-                    panic!();
+            ) = (&node.id, &node.kind)
+            {
+                // each pipe has its own $$, so one can't overwrite another
+                if var_id.0 == StrId::DOLLAR_DOLLAR || node_ids_at_exit.contains(&node.id) {
+                    return None;
                 }
-            },
-        )
+
+                let closure_span = get_closure_span(pos.start_offset);
+
+                used_assignment_offsets
+                    .get(var_id)?
+                    .iter()
+                    .any(|offset| {
+                        *offset > pos.start_offset && get_closure_span(*offset) == closure_span
+                    })
+                    .then_some(pos.start_offset)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Finds variables assigned in every branch of an if/else (including else-if chains)
+/// whose assignments are all unused, so that the variable is only reported once. Maps
+/// the offset of each of those assignments to the offset of the first one.
+pub(crate) fn get_all_branch_unused_assignments<'a>(
+    stmts: &[aast::Stmt<(), ()>],
+    unused_source_nodes: impl Iterator<Item = &'a DataFlowNode>,
+    dead_store_offsets: &FxHashSet<u32>,
+) -> FxHashMap<u32, u32> {
+    let unused_offsets = unused_source_nodes
+        .filter_map(|node| match &node.kind {
+            DataFlowNodeKind::VariableUseSource {
+                pos,
+                kind: VariableSourceKind::Default,
+                pure: false,
+                has_awaitable: false,
+                ..
+            } if !dead_store_offsets.contains(&pos.start_offset) => Some(pos.start_offset),
+            _ => None,
+        })
+        .collect::<FxHashSet<_>>();
+
+    let mut finder = BranchAssignmentFinder {
+        unused_offsets: &unused_offsets,
+        first_offsets: FxHashMap::default(),
+    };
+
+    for stmt in stmts {
+        visit(&mut finder, &mut (), stmt).unwrap();
+    }
+
+    finder.first_offsets
+}
+
+struct BranchAssignmentFinder<'a> {
+    unused_offsets: &'a FxHashSet<u32>,
+    first_offsets: FxHashMap<u32, u32>,
+}
+
+impl BranchAssignmentFinder<'_> {
+    fn check_if(
+        &mut self,
+        if_stmt: &(aast::Expr<(), ()>, aast::Block<(), ()>, aast::Block<(), ()>),
+    ) {
+        let mut branches = vec![];
+
+        if !collect_if_branches(if_stmt, &mut branches) {
+            return;
+        }
+
+        // the last unused assignment to each variable in every branch
+        let branch_assignments = branches
+            .iter()
+            .map(|branch| {
+                let mut assignments = FxHashMap::default();
+
+                for stmt in *branch {
+                    if let Some(var_name) = get_assigned_var(stmt) {
+                        let offset = stmt.0.start_offset() as u32;
+
+                        if self.unused_offsets.contains(&offset) {
+                            assignments.insert(var_name, offset);
+                        }
+                    }
+                }
+
+                assignments
+            })
+            .collect::<Vec<_>>();
+
+        for (var_name, first_offset) in &branch_assignments[0] {
+            let offsets = branch_assignments
+                .iter()
+                .filter_map(|assignments| assignments.get(var_name))
+                .collect::<Vec<_>>();
+
+            if offsets.len() != branch_assignments.len() {
+                continue;
+            }
+
+            for offset in offsets {
+                // an enclosing if/else that already grouped this assignment takes precedence
+                self.first_offsets.entry(*offset).or_insert(*first_offset);
+            }
+        }
+    }
+}
+
+/// Adds the statements of each branch to `branches`, following else-if chains. Returns
+/// false if there's no final else, since then not every path goes through a branch.
+/// (A missing else can also be parsed as a lone no-op, which has no assignments either.)
+fn collect_if_branches<'a>(
+    if_stmt: &'a (aast::Expr<(), ()>, aast::Block<(), ()>, aast::Block<(), ()>),
+    branches: &mut Vec<&'a [aast::Stmt<(), ()>]>,
+) -> bool {
+    branches.push(&if_stmt.1 .0);
+
+    match if_stmt.2 .0.as_slice() {
+        [] => false,
+        [aast::Stmt(_, aast::Stmt_::If(else_if))] => collect_if_branches(else_if, branches),
+        else_stmts => {
+            branches.push(else_stmts);
+            true
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for BranchAssignmentFinder<'_> {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_stmt(&mut self, c: &mut (), stmt: &aast::Stmt<(), ()>) -> Result<(), ()> {
+        if let aast::Stmt_::If(boxed) = &stmt.1 {
+            self.check_if(boxed);
+        }
+
+        stmt.recurse(c, self)
+    }
+}
+
+fn get_assigned_var(stmt: &aast::Stmt<(), ()>) -> Option<&String> {
+    if let aast::Stmt_::Expr(boxed) = &stmt.1 {
+        if let aast::Expr_::Assign(boxed) = &boxed.2 {
+            if let (aast::Expr_::Lvar(lid), None) = (&boxed.0 .2, &boxed.1) {
+                return Some(lid.name());
+            }
+        }
     }
+
+    None
+}
+
+/// Dry-run version of [`add_unused_expression_replacements`], returning the replacements
+/// that would be made without leaving them in `analysis_data`.
+pub(crate) fn get_unused_expression_replacements(
+    stmts: &Vec<aast::Stmt<(), ()>>,
+    analysis_data: &mut FunctionAnalysisData,
+    unused_source_nodes: &Vec<DataFlowNode>,
+    statements_analyzer: &StatementsAnalyzer,
+    trimmable_effects: &[u8],
+) -> Vec<((u32, u32), Replacement)> {
+    // existing replacements are kept in place so overlapping fixes are rejected
+    // exactly as they would be when applying them for real
+    let existing_replacements = analysis_data.replacements.clone();
+
+    scan_unused_expressions(
+        stmts,
+        analysis_data,
+        unused_source_nodes,
+        statements_analyzer,
+        trimmable_effects,
+    );
+
+    let all_replacements =
+        std::mem::replace(&mut analysis_data.replacements, existing_replacements);
+
+    all_replacements
+        .into_iter()
+        .filter(|(offsets, _)| !analysis_data.replacements.contains_key(offsets))
+        .collect()
 }
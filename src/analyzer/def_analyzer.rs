@@ -10,8 +10,10 @@ use crate::statements_analyzer::StatementsAnalyzer;
 use crate::stmt_analyzer::AnalysisError;
 use crate::{expression_analyzer, stmt_analyzer};
 use hakana_code_info::analysis_result::AnalysisResult;
+use hakana_code_info::data_flow::graph::{DataFlowGraph, GraphKind};
 use hakana_code_info::function_context::FunctionContext;
 use hakana_code_info::issue::{Issue, IssueKind};
+use hakana_code_info::ttype::type_expander::{self, TypeExpansionOptions};
 use oxidized::aast;
 
 pub(crate) fn analyze(
@@ -55,7 +57,19 @@ pub(crate) fn analyze(
                 return Err(InternalError(error, pos));
             }
         }
-        aast::Def::Typedef(_) | aast::Def::NamespaceUse(_) => {
+        aast::Def::Typedef(boxed) => {
+            if let Some(max_type_alias_atomics) =
+                statements_analyzer.get_config().max_type_alias_atomics
+            {
+                analyze_type_alias_size(
+                    statements_analyzer,
+                    boxed,
+                    max_type_alias_atomics,
+                    analysis_data,
+                );
+            }
+        }
+        aast::Def::NamespaceUse(_) => {
             // already handled
         }
         aast::Def::Stmt(boxed) => {
@@ -147,3 +161,65 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+/// Aliases whose expansion visits more atomics than the configured budget are replaced
+/// by their `as` constraint wherever they're used, so flag them where they're defined
+fn analyze_type_alias_size(
+    statements_analyzer: &StatementsAnalyzer,
+    typedef: &aast::Typedef<(), ()>,
+    max_type_alias_atomics: usize,
+    analysis_data: &mut FunctionAnalysisData,
+) {
+    let type_name = if let Some(type_name) = statements_analyzer
+        .file_analyzer
+        .resolved_names
+        .get(&(typedef.name.0.start_offset() as u32))
+    {
+        *type_name
+    } else {
+        return;
+    };
+
+    let type_definition = if let Some(type_definition) = statements_analyzer
+        .codebase
+        .type_definitions
+        .get(&type_name)
+    {
+        type_definition
+    } else {
+        return;
+    };
+
+    let mut actual_type = type_definition.actual_type.clone();
+    let mut cost = 0;
+
+    type_expander::expand_union(
+        statements_analyzer.codebase,
+        &Some(statements_analyzer.interner),
+        statements_analyzer.get_file_path(),
+        &mut actual_type,
+        &TypeExpansionOptions {
+            max_type_alias_atomics: None,
+            ..statements_analyzer.get_type_expansion_options()
+        },
+        &mut DataFlowGraph::new(GraphKind::FunctionBody),
+        &mut cost,
+    );
+
+    if cost as usize > max_type_alias_atomics {
+        analysis_data.maybe_add_issue(
+            Issue::new(
+                IssueKind::LargeTypeExpansion,
+                format!(
+                    "Expanding type alias {} visits {} types, so its as constraint is used instead",
+                    statements_analyzer.interner.lookup(&type_name),
+                    cost,
+                ),
+                statements_analyzer.get_hpos(&typedef.name.0),
+                &None,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+}
@@ -175,7 +175,7 @@ pub(crate) fn analyze<'expr>(
                 &mut hint_type,
                 &TypeExpansionOptions {
                     self_class: context.function_context.calling_class,
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut DataFlowGraph::new(GraphKind::FunctionBody),
                 &mut 0,
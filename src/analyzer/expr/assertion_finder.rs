@@ -242,7 +242,7 @@ fn get_is_assertions(
             &TypeExpansionOptions {
                 self_class: assertion_context.this_class_name,
                 expand_hakana_types: false,
-                ..Default::default()
+                ..assertion_context.type_expansion_options.clone()
             },
             &mut DataFlowGraph::new(GraphKind::FunctionBody),
             &mut 0,
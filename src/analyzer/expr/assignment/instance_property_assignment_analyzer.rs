@@ -590,9 +590,9 @@ pub(crate) fn analyze_atomic_assignment(
                 &mut class_property_type,
                 &TypeExpansionOptions {
                     self_class: Some(declaring_classlike_storage.name),
-                    static_class_type: StaticClassType::Name(declaring_classlike_storage.name),
+                    static_class_type: StaticClassType::Object(lhs_type_part),
                     parent_class: declaring_classlike_storage.direct_parent_class,
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut analysis_data.data_flow_graph,
                 &mut 0,
@@ -206,7 +206,7 @@ pub(crate) fn analyze(
                         self_class: Some(declaring_class_storage.name),
                         static_class_type: StaticClassType::Name(declaring_class_storage.name),
                         parent_class: declaring_class_storage.direct_parent_class,
-                        ..Default::default()
+                        ..statements_analyzer.get_type_expansion_options()
                     },
                     &mut analysis_data.data_flow_graph,
                     &mut 0,
@@ -3,7 +3,10 @@ use crate::function_analysis_data::FunctionAnalysisData;
 use crate::scope::BlockContext;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::stmt_analyzer::AnalysisError;
+use hakana_code_info::analysis_result::Replacement;
+use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::t_atomic::TAtomic;
+use hakana_code_info::t_union::TUnion;
 use hakana_code_info::ttype::{extend_dataflow_uniquely, get_mixed_any};
 use hakana_code_info::EFFECT_IMPURE;
 use oxidized::aast;
@@ -32,6 +35,17 @@ pub(crate) fn analyze(
         .cloned()
         .unwrap_or(get_mixed_any());
 
+    if is_never_awaitable(&awaited_stmt_type) {
+        report_redundant_await(
+            statements_analyzer,
+            expr,
+            boxed,
+            &awaited_stmt_type,
+            analysis_data,
+            context,
+        );
+    }
+
     let awaited_types = awaited_stmt_type.types.drain(..).collect::<Vec<_>>();
 
     let mut new_types = vec![];
@@ -68,3 +82,61 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+// Only flag values whose every member is known not to be awaitable, so unions like
+// ?Awaitable<T> and opaque types (objects, generics, mixed) are left alone
+fn is_never_awaitable(awaited_type: &TUnion) -> bool {
+    !awaited_type.types.is_empty()
+        && awaited_type.types.iter().all(|atomic| match atomic {
+            TAtomic::TAwaitable { .. }
+            | TAtomic::TNamedObject { .. }
+            | TAtomic::TObject
+            | TAtomic::TGenericParam { .. }
+            | TAtomic::TTypeAlias { .. }
+            | TAtomic::TClassTypeConstant { .. }
+            | TAtomic::TTypeVariable { .. }
+            | TAtomic::TPlaceholder
+            | TAtomic::TNothing => false,
+            _ => !atomic.is_mixed(),
+        })
+}
+
+fn report_redundant_await(
+    statements_analyzer: &StatementsAnalyzer,
+    expr: &aast::Expr<(), ()>,
+    boxed: &aast::Expr<(), ()>,
+    awaited_type: &TUnion,
+    analysis_data: &mut FunctionAnalysisData,
+    context: &BlockContext,
+) {
+    let config = statements_analyzer.get_config();
+
+    if config.issues_to_fix.contains(&IssueKind::RedundantAwait) && !config.add_fixmes {
+        analysis_data.add_replacement(
+            (
+                expr.pos().start_offset() as u32,
+                boxed.pos().start_offset() as u32,
+            ),
+            Replacement::Remove,
+        );
+
+        return;
+    }
+
+    analysis_data.maybe_add_issue(
+        Issue::new(
+            IssueKind::RedundantAwait,
+            format!(
+                "Value of type {} is not awaitable, so this await is redundant",
+                awaited_type.get_id(Some(statements_analyzer.interner))
+            ),
+            statements_analyzer.get_hpos(expr.pos()),
+            &context.function_context.calling_functionlike_id,
+        ),
+        config,
+        &statements_analyzer
+            .file_analyzer
+            .file_source
+            .file_path_actual,
+    );
+}
@@ -538,6 +538,7 @@ fn analyze_assignment_to_variable(
     let can_taint = has_parent_nodes
         && match analysis_data.data_flow_graph.kind {
             GraphKind::FunctionBody => inout_node.is_none(),
+            GraphKind::Disabled => false,
             GraphKind::WholeProgram(kind) => {
                 context.allow_taints
                     && (kind != WholeProgramKind::Taint || assign_value_type.has_taintable_value())
@@ -3,6 +3,7 @@ use std::rc::Rc;
 use crate::scope::BlockContext;
 use crate::statements_analyzer::StatementsAnalyzer;
 
+use crate::expr::binop_analyzer::check_unnecessary_null_check;
 use crate::expression_analyzer;
 use crate::function_analysis_data::FunctionAnalysisData;
 use crate::stmt_analyzer::AnalysisError;
@@ -56,6 +57,19 @@ pub(crate) fn analyze<'expr>(
         }
     }
 
+    if let aast::Expr_::Lvar(lid) = &left.2 {
+        if let Some(var_type) = context.locals.get(lid.1 .1.as_str()).cloned() {
+            check_unnecessary_null_check(
+                statements_analyzer,
+                lid,
+                &var_type,
+                left.pos(),
+                analysis_data,
+                context,
+            );
+        }
+    }
+
     let mut replacement_left = None;
 
     if has_arrayget_key {
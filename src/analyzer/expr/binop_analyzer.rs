@@ -5,11 +5,17 @@ use crate::scope_analyzer::ScopeAnalyzer;
 use crate::statements_analyzer::StatementsAnalyzer;
 use crate::stmt_analyzer::AnalysisError;
 
+use hakana_code_info::data_flow::graph::GraphKind;
+use hakana_code_info::data_flow::node::{
+    DataFlowNode, DataFlowNodeId, DataFlowNodeKind, VariableSourceKind,
+};
 use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::t_atomic::TAtomic;
+use hakana_code_info::t_union::TUnion;
 use hakana_code_info::ttype::comparison::union_type_comparator;
 use hakana_code_info::ttype::{get_bool, get_int};
 use oxidized::pos::Pos;
+use oxidized::tast::Lid;
 use oxidized::{aast, ast};
 
 pub(crate) fn analyze(
@@ -164,6 +170,34 @@ pub(crate) fn analyze(
                 }
             }
 
+            if matches!(
+                expr.0,
+                oxidized::ast_defs::Bop::Eqeq
+                    | oxidized::ast_defs::Bop::Eqeqeq
+                    | oxidized::ast_defs::Bop::Diff
+                    | oxidized::ast_defs::Bop::Diff2
+            ) {
+                let var_expr = match (&expr.1 .2, &expr.2 .2) {
+                    (aast::Expr_::Lvar(_), aast::Expr_::Null) => Some(expr.1),
+                    (aast::Expr_::Null, aast::Expr_::Lvar(_)) => Some(expr.2),
+                    _ => None,
+                };
+
+                if let Some(var_expr @ aast::Expr(_, _, aast::Expr_::Lvar(lid))) = var_expr {
+                    if let Some(var_type) = analysis_data.get_rc_expr_type(var_expr.pos()).cloned()
+                    {
+                        check_unnecessary_null_check(
+                            statements_analyzer,
+                            lid,
+                            &var_type,
+                            pos,
+                            analysis_data,
+                            context,
+                        );
+                    }
+                }
+            }
+
             add_decision_dataflow(
                 statements_analyzer,
                 analysis_data,
@@ -237,6 +271,78 @@ pub(crate) fn analyze(
     }
 }
 
+// Flags a null check (`$x === null`, `$x !== null` or `$x ?? ...`) on a local
+// variable when every value that can flow into that variable is known to be
+// non-null, and only when all of the variable's origins are local assignments
+// rather than params or inout args. The reconciler skips its own null and isset
+// issues for any check reported here.
+pub(crate) fn check_unnecessary_null_check(
+    statements_analyzer: &StatementsAnalyzer,
+    lid: &Lid,
+    var_type: &TUnion,
+    pos: &Pos,
+    analysis_data: &mut FunctionAnalysisData,
+    context: &BlockContext,
+) {
+    if analysis_data.data_flow_graph.kind != GraphKind::FunctionBody {
+        return;
+    }
+
+    if var_type.parent_nodes.is_empty()
+        || var_type.possibly_undefined_from_try
+        || var_type.is_nullable()
+        || var_type.has_template_types()
+        || var_type.types.iter().any(|t| {
+            t.is_mixed()
+                || matches!(
+                    t,
+                    TAtomic::TNull | TAtomic::TVoid | TAtomic::TNothing | TAtomic::TPlaceholder
+                )
+        })
+    {
+        return;
+    }
+
+    let mut origin_node_ids = vec![];
+
+    for parent_node in &var_type.parent_nodes {
+        origin_node_ids.extend(analysis_data.data_flow_graph.get_origin_node_ids(
+            &parent_node.id,
+            &[],
+            false,
+        ));
+    }
+
+    if origin_node_ids.is_empty()
+        || !origin_node_ids.iter().all(|id| {
+            matches!(id, DataFlowNodeId::Var(..))
+                && matches!(
+                    analysis_data.data_flow_graph.get_node(id),
+                    Some(DataFlowNode {
+                        kind: DataFlowNodeKind::VariableUseSource {
+                            kind: VariableSourceKind::Default,
+                            ..
+                        },
+                        ..
+                    })
+                )
+        })
+    {
+        return;
+    }
+
+    analysis_data.maybe_add_issue(
+        Issue::new(
+            IssueKind::UnnecessaryNullCheck,
+            format!("{} is never null", lid.name()),
+            statements_analyzer.get_hpos(pos),
+            &context.function_context.calling_functionlike_id,
+        ),
+        statements_analyzer.get_config(),
+        statements_analyzer.get_file_path_actual(),
+    );
+}
+
 fn is_resolvable(expr: &aast::Expr<(), ()>) -> bool {
     matches!(expr.2, aast::Expr_::Lvar(_) | aast::Expr_::ObjGet(_))
 }
@@ -673,10 +673,10 @@ fn add_dataflow(
     // maybe todo prevent numeric types from being tainted
     // ALTHOUGH numbers may still contain PII
 
-    let removed_taints = if data_flow_graph.kind == GraphKind::FunctionBody {
-        vec![]
-    } else {
+    let removed_taints = if let GraphKind::WholeProgram(_) = data_flow_graph.kind {
         get_removed_taints_in_comments(statements_analyzer, input_expr.pos())
+    } else {
+        vec![]
     };
     // TODO add plugin hooks for adding/removing taints
 
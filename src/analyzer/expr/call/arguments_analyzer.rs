@@ -113,7 +113,7 @@ pub(crate) fn check_arguments_match(
                         false
                     },
                     expand_type_aliases: false,
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut analysis_data.data_flow_graph,
                 &mut 0,
@@ -844,7 +844,7 @@ fn get_param_type(
                     } else {
                         false
                     },
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut analysis_data.data_flow_graph,
                 &mut 0,
@@ -1211,7 +1211,7 @@ fn handle_possibly_matching_inout_param(
             } else {
                 false
             },
-            ..Default::default()
+            ..statements_analyzer.get_type_expansion_options()
         },
         &mut analysis_data.data_flow_graph,
         &mut 0,
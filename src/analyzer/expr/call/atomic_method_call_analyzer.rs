@@ -75,6 +75,7 @@ pub(crate) fn analyze(
                 expr,
                 lhs_type_part,
                 context,
+                &[],
             )?;
         }
         TAtomic::TReference {
@@ -181,6 +182,7 @@ pub(crate) fn handle_method_call_on_named_object(
     ),
     lhs_type_part: &TAtomic,
     context: &mut BlockContext,
+    static_classes: &[TAtomic],
 ) -> Result<(), AnalysisError> {
     let codebase = statements_analyzer.codebase;
 
@@ -270,6 +272,7 @@ pub(crate) fn handle_method_call_on_named_object(
             analysis_data,
             context,
             lhs_var_id.as_ref(),
+            static_classes,
         )?;
 
         result.return_type = Some(hakana_code_info::ttype::add_optional_union_type(
@@ -74,6 +74,7 @@ pub(crate) fn analyze(
                     ),
                     lhs_type_part,
                     context,
+                    &[],
                 );
             }
         }
@@ -228,6 +229,7 @@ pub(crate) fn analyze(
         analysis_data,
         context,
         None,
+        &[],
     )?);
 
     Ok(())
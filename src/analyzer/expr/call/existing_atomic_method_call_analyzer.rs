@@ -57,6 +57,7 @@ pub(crate) fn analyze(
     analysis_data: &mut FunctionAnalysisData,
     context: &mut BlockContext,
     lhs_var_id: Option<&String>,
+    static_classes: &[TAtomic],
 ) -> Result<TUnion, AnalysisError> {
     analysis_data.symbol_references.add_reference_to_symbol(
         &context.function_context,
@@ -76,13 +77,13 @@ pub(crate) fn analyze(
 
     let classlike_storage = codebase.classlike_infos.get(&classlike_name).unwrap();
 
-    analysis_data
-        .symbol_references
-        .add_reference_to_class_member(
-            &context.function_context,
-            (declaring_method_id.0, declaring_method_id.1),
-            false,
-        );
+    add_method_references(
+        statements_analyzer,
+        analysis_data,
+        context,
+        &method_id,
+        &declaring_method_id,
+    );
 
     // Track member definition location for go-to-definition support
     if statements_analyzer
@@ -100,59 +101,6 @@ pub(crate) fn analyze(
         }
     }
 
-    if method_id != declaring_method_id
-        && codebase.class_or_trait_extends(&method_id.0, &declaring_method_id.0)
-    {
-        let mut child_storage = classlike_storage;
-        let mut child_class_name = classlike_name;
-        loop {
-            analysis_data
-                .symbol_references
-                .add_reference_to_class_member(
-                    &context.function_context,
-                    (child_class_name, method_id.1),
-                    false,
-                );
-
-            for used_trait in &child_storage.used_traits {
-                analysis_data
-                    .symbol_references
-                    .add_reference_to_class_member(
-                        &context.function_context,
-                        (*used_trait, method_id.1),
-                        false,
-                    );
-            }
-
-            if let Some(parent_class) = child_storage.direct_parent_class {
-                if parent_class == declaring_method_id.0 {
-                    break;
-                }
-
-                if let Some(parent_class_storage) = codebase.classlike_infos.get(&parent_class) {
-                    child_storage = parent_class_storage;
-                    child_class_name = parent_class;
-                } else {
-                    break;
-                }
-            }
-        }
-    }
-
-    if let Some(overridden_classlikes) = classlike_storage
-        .overridden_method_ids
-        .get(&declaring_method_id.1)
-    {
-        for overridden_classlike in overridden_classlikes {
-            analysis_data
-                .symbol_references
-                .add_reference_to_overridden_class_member(
-                    &context.function_context,
-                    (*overridden_classlike, declaring_method_id.1),
-                );
-        }
-    }
-
     let class_template_params =
         if classlike_name != StrId::VECTOR || *method_name != StrId::FROM_ITEMS {
             let declaring_classlike_storage =
@@ -308,6 +256,7 @@ pub(crate) fn analyze(
         classlike_storage,
         &template_result,
         pos,
+        static_classes,
     );
 
     // todo check method visibility
@@ -323,6 +272,85 @@ pub(crate) fn analyze(
     Ok(return_type_candidate)
 }
 
+/// Registers the references a call to `method_id` makes: the declaring method, the
+/// classes between the receiver and the declaring class, and any overridden methods
+pub(crate) fn add_method_references(
+    statements_analyzer: &StatementsAnalyzer,
+    analysis_data: &mut FunctionAnalysisData,
+    context: &BlockContext,
+    method_id: &MethodIdentifier,
+    declaring_method_id: &MethodIdentifier,
+) {
+    let codebase = statements_analyzer.codebase;
+
+    let classlike_storage = if let Some(s) = codebase.classlike_infos.get(&method_id.0) {
+        s
+    } else {
+        return;
+    };
+
+    analysis_data
+        .symbol_references
+        .add_reference_to_class_member(
+            &context.function_context,
+            (declaring_method_id.0, declaring_method_id.1),
+            false,
+        );
+
+    if method_id != declaring_method_id
+        && codebase.class_or_trait_extends(&method_id.0, &declaring_method_id.0)
+    {
+        let mut child_storage = classlike_storage;
+        let mut child_class_name = method_id.0;
+        loop {
+            analysis_data
+                .symbol_references
+                .add_reference_to_class_member(
+                    &context.function_context,
+                    (child_class_name, method_id.1),
+                    false,
+                );
+
+            for used_trait in &child_storage.used_traits {
+                analysis_data
+                    .symbol_references
+                    .add_reference_to_class_member(
+                        &context.function_context,
+                        (*used_trait, method_id.1),
+                        false,
+                    );
+            }
+
+            if let Some(parent_class) = child_storage.direct_parent_class {
+                if parent_class == declaring_method_id.0 {
+                    break;
+                }
+
+                if let Some(parent_class_storage) = codebase.classlike_infos.get(&parent_class) {
+                    child_storage = parent_class_storage;
+                    child_class_name = parent_class;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    if let Some(overridden_classlikes) = classlike_storage
+        .overridden_method_ids
+        .get(&declaring_method_id.1)
+    {
+        for overridden_classlike in overridden_classlikes {
+            analysis_data
+                .symbol_references
+                .add_reference_to_overridden_class_member(
+                    &context.function_context,
+                    (*overridden_classlike, declaring_method_id.1),
+                );
+        }
+    }
+}
+
 fn handle_shapes_static_method(
     method_id: &MethodIdentifier,
     call_expr: (
@@ -14,8 +14,8 @@ use hakana_code_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_code_info::functionlike_info::{FnEffect, FunctionLikeInfo, MetaStart};
 use hakana_code_info::functionlike_parameter::FunctionLikeParameter;
 use hakana_code_info::t_atomic::TAtomic;
-use hakana_code_info::ttype::get_mixed_any;
 use hakana_code_info::ttype::template::TemplateResult;
+use hakana_code_info::ttype::{get_mixed_any, get_nothing};
 use hakana_code_info::{VarId, EFFECT_CAN_THROW};
 use hakana_str::StrId;
 use indexmap::IndexMap;
@@ -117,12 +117,16 @@ pub(crate) fn analyze(
                 &expr.args,
             );
 
+            // no argument can be passed to a `nothing` param, so the call never completes
+            let return_type = if closure.is_uncallable() {
+                Some(get_nothing())
+            } else {
+                closure.return_type.clone()
+            };
+
             stmt_type = Some(hakana_code_info::ttype::combine_optional_union_types(
                 stmt_type.as_ref(),
-                match &closure.return_type {
-                    Some(t) => Some(t),
-                    None => None,
-                },
+                return_type.as_ref(),
                 codebase,
             ));
         }
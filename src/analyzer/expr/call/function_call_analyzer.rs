@@ -10,6 +10,7 @@ use hakana_str::StrId;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::rc::Rc;
 
+use crate::config::DeprecatedUsageLevel;
 use crate::expr::call::arguments_analyzer;
 use crate::expr::call_analyzer::{apply_effects, check_template_result};
 use crate::expr::{echo_analyzer, exit_analyzer, expression_identifier, isset_analyzer};
@@ -225,6 +226,32 @@ pub(crate) fn analyze(
         );
     }
 
+    if function_storage.deprecated
+        && statements_analyzer.get_config().deprecated_usage != DeprecatedUsageLevel::Ignore
+    {
+        analysis_data.maybe_add_issue(
+            Issue::new(
+                IssueKind::DeprecatedUsage,
+                format!(
+                    "Function {} is deprecated{}",
+                    statements_analyzer.interner.lookup(&name),
+                    if let Some(deprecation_message) = function_storage.deprecation_message {
+                        format!(
+                            ": {}",
+                            statements_analyzer.interner.lookup(&deprecation_message)
+                        )
+                    } else {
+                        "".to_string()
+                    }
+                ),
+                statements_analyzer.get_hpos(pos),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+
     if !function_storage.is_production_code
         && function_storage.user_defined
         && context.function_context.is_production(codebase)
@@ -273,6 +300,16 @@ pub(crate) fn analyze(
     }
 
     match name {
+        StrId::CONSTANT | StrId::DEFINED => {
+            if let Some(first_arg) = &expr.2.first() {
+                add_constant_lookup_reference(
+                    first_arg.to_expr_ref(),
+                    context,
+                    statements_analyzer,
+                    analysis_data,
+                );
+            }
+        }
         StrId::INVARIANT => {
             if let Some(first_arg) = &expr.2.first() {
                 process_invariant(
@@ -688,6 +725,35 @@ fn process_invariant(
     }
 }
 
+/// `constant('FOO')` and `defined('FOO')` look a constant up by name, so they count as a
+/// reference to it. When the name isn't a literal any constant could be looked up, which
+/// is recorded as a reference to the lookup function so unused constants aren't reported.
+fn add_constant_lookup_reference(
+    first_arg: &aast::Expr<(), ()>,
+    context: &BlockContext,
+    statements_analyzer: &StatementsAnalyzer,
+    analysis_data: &mut FunctionAnalysisData,
+) {
+    let referenced_symbol = if let aast::Expr_::String(constant_name) = &first_arg.2 {
+        if let Some(constant_name) = statements_analyzer
+            .interner
+            .get(constant_name.to_string().trim_start_matches('\\'))
+        {
+            constant_name
+        } else {
+            return;
+        }
+    } else {
+        StrId::CONSTANT
+    };
+
+    analysis_data.symbol_references.add_reference_to_symbol(
+        &context.function_context,
+        referenced_symbol,
+        false,
+    );
+}
+
 fn check_array_key_or_value_type(
     codebase: &CodebaseInfo,
     statements_analyzer: &StatementsAnalyzer,
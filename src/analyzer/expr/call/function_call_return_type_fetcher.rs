@@ -108,7 +108,7 @@ pub(crate) fn fetch(
                 &mut function_return_type,
                 &TypeExpansionOptions {
                     expand_templates: false,
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut analysis_data.data_flow_graph,
                 &mut 0,
@@ -128,8 +128,7 @@ pub(crate) fn fetch(
             &mut function_return_type,
             &TypeExpansionOptions {
                 expand_templates: false,
-                expand_generic: true,
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
@@ -871,12 +870,17 @@ fn add_dataflow(
 
     let function_call_node = DataFlowNode::get_for_method_return(
         functionlike_id,
-        if data_flow_graph.kind == GraphKind::FunctionBody {
-            Some(statements_analyzer.get_hpos(pos))
-        } else if let Some(return_pos) = &functionlike_storage.return_type_location {
-            Some(*return_pos)
-        } else {
-            functionlike_storage.name_location
+        match data_flow_graph.kind {
+            GraphKind::FunctionBody | GraphKind::Disabled => {
+                Some(statements_analyzer.get_hpos(pos))
+            }
+            GraphKind::WholeProgram(_) => {
+                if let Some(return_pos) = &functionlike_storage.return_type_location {
+                    Some(*return_pos)
+                } else {
+                    functionlike_storage.name_location
+                }
+            }
         },
         if functionlike_storage.specialize_call {
             Some(statements_analyzer.get_hpos(pos))
@@ -12,13 +12,18 @@ use hakana_code_info::data_flow::graph::GraphKind;
 use hakana_code_info::data_flow::node::DataFlowNode;
 use hakana_code_info::data_flow::path::PathKind;
 use hakana_code_info::issue::{Issue, IssueKind};
+use hakana_code_info::method_identifier::MethodIdentifier;
 use hakana_code_info::t_atomic::TAtomic;
 use hakana_code_info::ttype::{add_union_type, get_mixed_any, get_null};
 use hakana_code_info::EFFECT_WRITE_PROPS;
+use hakana_str::StrId;
 use oxidized::aast;
 use oxidized::pos::Pos;
 
-use super::atomic_method_call_analyzer::{self, AtomicMethodCallAnalysisResult};
+use super::atomic_method_call_analyzer::{
+    self, handle_method_call_on_named_object, AtomicMethodCallAnalysisResult,
+};
+use super::existing_atomic_method_call_analyzer::add_method_references;
 
 pub(crate) fn analyze(
     statements_analyzer: &StatementsAnalyzer,
@@ -84,6 +89,55 @@ pub(crate) fn analyze(
 
         let mut class_types = class_type.types.iter().collect::<Vec<_>>();
 
+        // receivers like `A|B` that share a method declaration are analyzed once, with
+        // `this` in the method's return type resolved against each of them
+        if let Some(method_name) =
+            get_shared_declaration_method_name(statements_analyzer, &class_type.types, expr.1)
+        {
+            for (i, lhs_atomic_type) in class_type.types.iter().enumerate() {
+                if let TAtomic::TNamedObject {
+                    name, extra_types, ..
+                } = lhs_atomic_type
+                {
+                    if i == 0 {
+                        handle_method_call_on_named_object(
+                            &mut analysis_result,
+                            name,
+                            extra_types,
+                            &lhs_var_id,
+                            analysis_data,
+                            statements_analyzer,
+                            pos,
+                            expr,
+                            lhs_atomic_type,
+                            context,
+                            &class_type.types,
+                        )?;
+                    } else {
+                        analysis_data.symbol_references.add_reference_to_symbol(
+                            &context.function_context,
+                            *name,
+                            false,
+                        );
+
+                        let method_id = MethodIdentifier(*name, method_name);
+
+                        add_method_references(
+                            statements_analyzer,
+                            analysis_data,
+                            context,
+                            &method_id,
+                            &statements_analyzer
+                                .codebase
+                                .get_declaring_method_id(&method_id),
+                        );
+                    }
+                }
+            }
+
+            class_types.clear();
+        }
+
         let type_variable_bounds = analysis_data.type_variable_bounds.clone();
 
         while let Some(lhs_atomic_type) = class_types.pop() {
@@ -190,3 +244,58 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+/// Returns the called method's name when every receiver is a plain object whose method
+/// comes from one shared declaration
+fn get_shared_declaration_method_name(
+    statements_analyzer: &StatementsAnalyzer,
+    lhs_types: &[TAtomic],
+    method_expr: &aast::Expr<(), ()>,
+) -> Option<StrId> {
+    if lhs_types.len() < 2 {
+        return None;
+    }
+
+    let aast::Expr_::Id(boxed) = &method_expr.2 else {
+        return None;
+    };
+
+    let method_name = statements_analyzer.interner.get(&boxed.1)?;
+
+    let codebase = statements_analyzer.codebase;
+
+    let mut shared_declaring_method_id = None;
+
+    for lhs_type_part in lhs_types {
+        let TAtomic::TNamedObject {
+            name,
+            type_params: None,
+            extra_types: None,
+            is_this: false,
+            ..
+        } = lhs_type_part
+        else {
+            return None;
+        };
+
+        if !codebase.method_exists(name, &method_name)
+            || !codebase
+                .classlike_infos
+                .get(name)
+                .is_some_and(|classlike_info| classlike_info.template_types.is_empty())
+        {
+            return None;
+        }
+
+        let declaring_method_id =
+            codebase.get_declaring_method_id(&MethodIdentifier(*name, method_name));
+
+        if shared_declaring_method_id.is_some_and(|shared_id| shared_id != declaring_method_id) {
+            return None;
+        }
+
+        shared_declaring_method_id = Some(declaring_method_id);
+    }
+
+    Some(method_name)
+}
@@ -50,6 +50,7 @@ pub(crate) fn fetch(
     classlike_storage: &ClassLikeInfo,
     template_result: &TemplateResult,
     call_pos: &Pos,
+    static_classes: &[TAtomic],
 ) -> TUnion {
     let codebase = statements_analyzer.codebase;
 
@@ -93,8 +94,7 @@ pub(crate) fn fetch(
                 self_class: Some(method_id.0),
                 parent_class: classlike_storage.direct_parent_class,
                 function_is_final: method_storage.is_final,
-                expand_generic: true,
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
@@ -107,30 +107,45 @@ pub(crate) fn fetch(
         );
     }
 
-    type_expander::expand_union(
-        codebase,
-        &Some(statements_analyzer.interner),
-        &statements_analyzer.file_analyzer.file_source.file_path,
-        &mut return_type_candidate,
-        &TypeExpansionOptions {
-            self_class: Some(method_id.0),
-            static_class_type: match lhs_type_part {
-                TAtomic::TNamedObject { .. } | TAtomic::TGenericParam { .. } => {
-                    type_expander::StaticClassType::Object(lhs_type_part)
-                }
-                TAtomic::TGenericClassname { as_type, .. } | TAtomic::TClassname { as_type } => {
-                    type_expander::StaticClassType::Object(as_type)
-                }
-                _ => type_expander::StaticClassType::None,
-            },
-            parent_class: classlike_storage.direct_parent_class,
-            function_is_final: method_storage.is_final,
-            expand_generic: true,
-            ..Default::default()
+    let expansion_options = TypeExpansionOptions {
+        self_class: Some(method_id.0),
+        static_class_type: match lhs_type_part {
+            TAtomic::TNamedObject { .. } | TAtomic::TGenericParam { .. } => {
+                type_expander::StaticClassType::Object(lhs_type_part)
+            }
+            TAtomic::TGenericClassname { as_type, .. } | TAtomic::TClassname { as_type } => {
+                type_expander::StaticClassType::Object(as_type)
+            }
+            _ => type_expander::StaticClassType::None,
         },
-        &mut analysis_data.data_flow_graph,
-        &mut 0,
-    );
+        parent_class: classlike_storage.direct_parent_class,
+        function_is_final: method_storage.is_final,
+        ..statements_analyzer.get_type_expansion_options()
+    };
+
+    if static_classes.len() > 1 {
+        // the call stands in for several receivers, so `this` resolves to each of them
+        return_type_candidate = type_expander::expand_union_for_static_classes(
+            codebase,
+            &Some(statements_analyzer.interner),
+            &statements_analyzer.file_analyzer.file_source.file_path,
+            &return_type_candidate,
+            static_classes,
+            &expansion_options,
+            &mut analysis_data.data_flow_graph,
+            &mut 0,
+        );
+    } else {
+        type_expander::expand_union(
+            codebase,
+            &Some(statements_analyzer.interner),
+            &statements_analyzer.file_analyzer.file_source.file_path,
+            &mut return_type_candidate,
+            &expansion_options,
+            &mut analysis_data.data_flow_graph,
+            &mut 0,
+        );
+    }
 
     if return_type_candidate.is_nothing() && context.function_context.ignore_noreturn_calls {
         return_type_candidate = get_mixed();
@@ -11,6 +11,7 @@ use hakana_code_info::ttype::template::standin_type_replacer::get_most_specific_
 use hakana_str::StrId;
 use rustc_hash::FxHashMap;
 
+use crate::config::DeprecatedUsageLevel;
 use crate::expr::call_analyzer::{check_method_args, get_generic_param_for_offset};
 use crate::expression_analyzer;
 use crate::function_analysis_data::FunctionAnalysisData;
@@ -314,13 +315,34 @@ fn analyze_named_constructor(
     }
 
     if storage.is_deprecated
+        && statements_analyzer.get_config().deprecated_usage != DeprecatedUsageLevel::Ignore
         && if let Some(calling_class) = &context.function_context.calling_class {
             calling_class != &classlike_name
         } else {
             true
         }
     {
-        // todo complain about deprecated class
+        analysis_data.maybe_add_issue(
+            Issue::new(
+                IssueKind::DeprecatedUsage,
+                format!(
+                    "Class {} is deprecated{}",
+                    statements_analyzer.interner.lookup(&classlike_name),
+                    if let Some(deprecation_message) = storage.deprecation_message {
+                        format!(
+                            ": {}",
+                            statements_analyzer.interner.lookup(&deprecation_message)
+                        )
+                    } else {
+                        "".to_string()
+                    }
+                ),
+                statements_analyzer.get_hpos(pos),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
     }
 
     let mut generic_type_params = None;
@@ -509,7 +531,7 @@ fn analyze_named_constructor(
                         &mut param_type,
                         &TypeExpansionOptions {
                             parent_class: None,
-                            ..Default::default()
+                            ..statements_analyzer.get_type_expansion_options()
                         },
                         &mut analysis_data.data_flow_graph,
                         &mut 0,
@@ -623,7 +645,7 @@ fn analyze_named_constructor(
                             &mut param_type,
                             &TypeExpansionOptions {
                                 parent_class: None,
-                                ..Default::default()
+                                ..statements_analyzer.get_type_expansion_options()
                             },
                             &mut analysis_data.data_flow_graph,
                             &mut 0,
@@ -11,10 +11,11 @@ use hakana_code_info::data_flow::node::DataFlowNode;
 use hakana_code_info::data_flow::path::PathKind;
 use hakana_code_info::function_context::FunctionLikeIdentifier;
 use hakana_code_info::functionlike_parameter::FnParameter;
+use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::symbol_references::SymbolReferences;
 use hakana_code_info::t_atomic::{TAtomic, TClosure};
 use hakana_code_info::ttype::type_expander;
-use hakana_code_info::ttype::type_expander::TypeExpansionOptions;
+use hakana_code_info::ttype::type_expander::{StaticClassType, TypeExpansionOptions};
 use hakana_code_info::ttype::wrap_atomic;
 use oxidized::aast;
 
@@ -28,6 +29,8 @@ pub(crate) fn analyze(
     let mut function_analyzer = FunctionLikeAnalyzer::new(statements_analyzer.file_analyzer);
     let mut analysis_result =
         AnalysisResult::new(analysis_data.data_flow_graph.kind, SymbolReferences::new());
+    analysis_result.program_dataflow_graph.traversal_counts =
+        analysis_data.data_flow_graph.traversal_counts.clone();
     let mut lambda_storage = if let Ok(lambda_storage) = function_analyzer.analyze_lambda(
         fun,
         context.clone(),
@@ -40,6 +43,21 @@ pub(crate) fn analyze(
         return Err(AnalysisError::UserError);
     };
 
+    // closures are scanned without a class, so `self` and `this` in their signatures
+    // only get resolved against the enclosing method's class here
+    let calling_class = context.function_context.calling_class;
+
+    let type_expansion_options = TypeExpansionOptions {
+        evaluate_conditional_types: true,
+        self_class: calling_class,
+        static_class_type: if let Some(calling_class) = calling_class {
+            StaticClassType::Name(calling_class)
+        } else {
+            StaticClassType::None
+        },
+        ..statements_analyzer.get_type_expansion_options()
+    };
+
     for param in lambda_storage.params.iter_mut() {
         if let Some(ref mut param_type) = param.signature_type {
             type_expander::expand_union(
@@ -47,17 +65,25 @@ pub(crate) fn analyze(
                 &Some(statements_analyzer.interner),
                 statements_analyzer.get_file_path(),
                 param_type,
-                &TypeExpansionOptions {
-                    evaluate_conditional_types: true,
-                    expand_generic: true,
-                    ..Default::default()
-                },
+                &type_expansion_options,
                 &mut analysis_data.data_flow_graph,
                 &mut 0,
             )
         }
     }
 
+    if let Some(ref mut return_type) = lambda_storage.return_type {
+        type_expander::expand_union(
+            statements_analyzer.codebase,
+            &Some(statements_analyzer.interner),
+            statements_analyzer.get_file_path(),
+            return_type,
+            &type_expansion_options,
+            &mut analysis_data.data_flow_graph,
+            &mut 0,
+        )
+    }
+
     let issues = analysis_result.emitted_issues.into_iter().next();
 
     if let Some(issues) = issues {
@@ -135,3 +161,33 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+/// A `use`-captured variable that shares its name with one of the closure's parameters
+/// is never read, since the parameter always wins inside the closure body.
+pub(crate) fn check_shadowed_captures(
+    statements_analyzer: &StatementsAnalyzer,
+    context: &BlockContext,
+    analysis_data: &mut FunctionAnalysisData,
+    fun: &aast::Fun_<(), ()>,
+    captures: &[aast::CaptureLid<()>],
+) {
+    for capture in captures {
+        let lid = &capture.1;
+
+        if fun.params.iter().any(|param| param.name == lid.1 .1) {
+            analysis_data.maybe_add_issue(
+                Issue::new(
+                    IssueKind::ShadowedClosureUse,
+                    format!(
+                        "Captured variable {} is shadowed by a closure parameter of the same name",
+                        lid.1 .1
+                    ),
+                    statements_analyzer.get_hpos(&lid.0),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+    }
+}
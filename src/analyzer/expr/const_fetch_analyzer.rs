@@ -42,6 +42,12 @@ pub(crate) fn analyze(
     };
 
     let mut stmt_type = if let Some(constant_storage) = codebase.constant_infos.get(name) {
+        analysis_data.symbol_references.add_reference_to_symbol(
+            &context.function_context,
+            *name,
+            false,
+        );
+
         if *name == StrId::FILE_CONST {
             get_literal_string(statements_analyzer.get_file_path_actual().to_string())
         } else if *name == StrId::DIR_CONST {
@@ -83,7 +89,7 @@ pub(crate) fn analyze(
         statements_analyzer.get_file_path(),
         &mut stmt_type,
         &TypeExpansionOptions {
-            ..Default::default()
+            ..statements_analyzer.get_type_expansion_options()
         },
         &mut analysis_data.data_flow_graph,
         &mut 0,
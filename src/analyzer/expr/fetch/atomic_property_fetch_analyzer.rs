@@ -246,7 +246,7 @@ fn get_class_property_type(
                 self_class: Some(declaring_class_storage.name),
                 static_class_type: StaticClassType::Object(&lhs_type_part),
                 parent_class: parent_class,
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
@@ -287,7 +287,7 @@ fn get_class_property_type(
                             } else {
                                 Some(&functionlike_storage.where_constraints)
                             },
-                            ..Default::default()
+                            ..statements_analyzer.get_type_expansion_options()
                         },
                         &mut analysis_data.data_flow_graph,
                         &mut 0,
@@ -368,7 +368,7 @@ fn update_template_types(
                                 &mut lhs_param_type,
                                 &TypeExpansionOptions {
                                     parent_class: None,
-                                    ..Default::default()
+                                    ..statements_analyzer.get_type_expansion_options()
                                 },
                                 &mut analysis_data.data_flow_graph,
                                 &mut 0,
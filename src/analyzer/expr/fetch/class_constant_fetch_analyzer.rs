@@ -305,11 +305,10 @@ fn analyse_known_class_constant(
             class_constant_type,
             &TypeExpansionOptions {
                 evaluate_conditional_types: true,
-                expand_generic: true,
                 self_class: Some(*classlike_name),
                 static_class_type: StaticClassType::Object(&this_class),
                 parent_class: None,
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
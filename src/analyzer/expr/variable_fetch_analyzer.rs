@@ -136,7 +136,10 @@ fn add_dataflow_to_variable(
 
     let data_flow_graph = &mut analysis_data.data_flow_graph;
 
+    // unsetting a variable doesn't read it, so an assignment that's only ever unset is
+    // still unused. isset checks do count as uses, since they often guard real logic.
     if data_flow_graph.kind == GraphKind::FunctionBody
+        && !context.inside_unset
         && (context.inside_general_use || context.inside_throw || context.inside_isset)
     {
         let pos = statements_analyzer.get_hpos(pos);
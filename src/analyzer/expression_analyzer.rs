@@ -297,6 +297,14 @@ pub(crate) fn analyze(
             closure_analyzer::analyze(statements_analyzer, context, analysis_data, &boxed.0, expr)?;
         }
         aast::Expr_::Efun(boxed) => {
+            closure_analyzer::check_shadowed_captures(
+                statements_analyzer,
+                context,
+                analysis_data,
+                &boxed.fun,
+                &boxed.use_,
+            );
+
             closure_analyzer::analyze(
                 statements_analyzer,
                 context,
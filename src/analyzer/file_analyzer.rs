@@ -5,15 +5,18 @@ use crate::functionlike_analyzer::update_analysis_result_with_tast;
 use crate::scope_analyzer::ScopeAnalyzer;
 use crate::scope::BlockContext;
 use crate::statements_analyzer::StatementsAnalyzer;
-use hakana_code_info::analysis_result::AnalysisResult;
+use hakana_code_info::analysis_result::{AnalysisResult, Replacement};
 use hakana_code_info::code_location::HPos;
 use hakana_code_info::codebase_info::CodebaseInfo;
 use hakana_code_info::data_flow::graph::DataFlowGraph;
 use hakana_code_info::function_context::FunctionContext;
+use hakana_code_info::issue::IssueKind;
+use hakana_code_info::ttype::type_expander::TypeExpansionOptions;
 use hakana_code_info::type_resolution::TypeResolutionContext;
 use hakana_code_info::FileSource;
 use hakana_str::{Interner, StrId};
 use oxidized::aast;
+use oxidized::aast::NsKind;
 use rustc_hash::FxHashMap;
 
 pub struct InternalError(pub String, pub HPos);
@@ -26,6 +29,7 @@ pub struct FileAnalyzer<'a> {
     pub codebase: &'a CodebaseInfo,
     pub interner: &'a Interner,
     pub analysis_config: &'a Config,
+    is_generated_file: bool,
 }
 
 impl<'a> FileAnalyzer<'a> {
@@ -36,6 +40,11 @@ impl<'a> FileAnalyzer<'a> {
         interner: &'a Interner,
         analysis_config: &'a Config,
     ) -> Self {
+        let is_generated_file = analysis_config
+            .generated_files
+            .iter()
+            .any(|pattern| pattern.matches(&file_source.file_path_actual));
+
         Self {
             file_source,
             namespace_name: None,
@@ -43,6 +52,17 @@ impl<'a> FileAnalyzer<'a> {
             codebase,
             interner,
             analysis_config,
+            is_generated_file,
+        }
+    }
+
+    /// The base options for expanding types in this file, which callers extend with
+    /// their own settings
+    pub fn get_type_expansion_options(&self) -> TypeExpansionOptions<'static> {
+        TypeExpansionOptions {
+            force_alias_expansion: self.is_generated_file,
+            max_type_alias_atomics: self.analysis_config.max_type_alias_atomics,
+            ..Default::default()
         }
     }
 
@@ -52,7 +72,12 @@ impl<'a> FileAnalyzer<'a> {
         analysis_result: &mut AnalysisResult,
     ) -> Result<(), InternalError> {
         let mut analysis_data = FunctionAnalysisData::new(
-            DataFlowGraph::new(self.analysis_config.graph_kind),
+            DataFlowGraph::new(self.analysis_config.graph_kind).with_traversal_counts(
+                analysis_result
+                    .program_dataflow_graph
+                    .traversal_counts
+                    .clone(),
+            ),
             &self.file_source,
             &Vec::from_iter(self.file_source.comments.iter()),
             &self.get_config().all_custom_issues,
@@ -118,9 +143,133 @@ impl<'a> FileAnalyzer<'a> {
             false,
         );
 
+        let config = self.get_config();
+
+        if (config.issues_to_fix.contains(&IssueKind::UnusedAssignment)
+            || config
+                .issues_to_fix
+                .contains(&IssueKind::UnusedAssignmentStatement))
+            && !config.add_fixmes
+        {
+            self.remove_orphaned_imports(program, analysis_result);
+        }
+
         Ok(())
     }
 
+    /// Once unused assignments have been removed, a `use` statement whose imported name
+    /// was only referenced inside the removed code is dead too, so it gets removed as well
+    fn remove_orphaned_imports(
+        &self,
+        program: &aast::Program<(), ()>,
+        analysis_result: &mut AnalysisResult,
+    ) {
+        let file_path = self.file_source.file_path;
+
+        let removed_ranges =
+            if let Some(replacements) = analysis_result.replacements.get(&file_path) {
+                replacements
+                    .iter()
+                    .filter(|(_, replacement)| {
+                        !matches!(
+                            replacement,
+                            Replacement::Substitute(_) | Replacement::ReplaceRange(..)
+                        )
+                    })
+                    .map(|(range, _)| *range)
+                    .collect::<Vec<_>>()
+            } else {
+                return;
+            };
+
+        if removed_ranges.is_empty() {
+            return;
+        }
+
+        let mut import_removals = vec![];
+
+        for declaration in program {
+            let namespace_use_defs = if let aast::Def::Namespace(namespace) = declaration {
+                namespace.1.iter().collect::<Vec<_>>()
+            } else {
+                vec![declaration]
+            };
+
+            for def in namespace_use_defs {
+                let uses = if let aast::Def::NamespaceUse(uses) = def {
+                    uses
+                } else {
+                    continue;
+                };
+
+                // grouped imports would need to be rewritten rather than removed
+                let (ns_kind, name, alias_name) = if let [single_use] = &uses[..] {
+                    single_use
+                } else {
+                    continue;
+                };
+
+                if !matches!(ns_kind, NsKind::NSClass | NsKind::NSFun) {
+                    continue;
+                }
+
+                let imported_name = if let Some(imported_name) = self
+                    .interner
+                    .get(name.1.strip_prefix('\\').unwrap_or(&name.1))
+                {
+                    imported_name
+                } else {
+                    continue;
+                };
+
+                let use_start = name.0.to_raw_span().start.beg_of_line() as u32;
+                let mut use_end = alias_name.0.end_offset() as u32 + 1;
+
+                let mut references = self
+                    .resolved_names
+                    .iter()
+                    .filter(|(offset, resolved_name)| {
+                        **resolved_name == imported_name
+                            && (**offset < use_start || **offset >= use_end)
+                    })
+                    .map(|(offset, _)| *offset)
+                    .peekable();
+
+                // an import that was never referenced isn't something this fix made dead
+                if references.peek().is_none() {
+                    continue;
+                }
+
+                if references.all(|offset| {
+                    removed_ranges
+                        .iter()
+                        .any(|(start, end)| offset >= *start && offset < *end)
+                }) {
+                    if let Some(newline_offset) = self
+                        .file_source
+                        .file_contents
+                        .get(use_end as usize..)
+                        .and_then(|rest| rest.find('\n'))
+                    {
+                        use_end += newline_offset as u32 + 1;
+                    }
+
+                    import_removals.push((use_start, use_end));
+                }
+            }
+        }
+
+        if import_removals.is_empty() {
+            return;
+        }
+
+        let file_replacements = analysis_result.replacements.entry(file_path).or_default();
+
+        for import_removal in import_removals {
+            file_replacements.insert(import_removal, Replacement::Remove);
+        }
+    }
+
     pub fn get_file_source(&self) -> &FileSource {
         &self.file_source
     }
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use hakana_algebra::clause::ClauseKey;
 use hakana_algebra::Clause;
 use hakana_code_info::symbol_references::ReferenceSource;
+use hakana_code_info::ttype::type_expander::TypeExpansionOptions;
 use hakana_code_info::var_name::VarName;
 use hakana_code_info::FileSource;
 use hakana_code_info::{
@@ -27,6 +28,7 @@ pub(crate) struct AssertionContext<'a> {
     pub type_resolution_context: &'a TypeResolutionContext,
     pub config: &'a Config,
     pub reference_source: ReferenceSource,
+    pub type_expansion_options: TypeExpansionOptions<'static>,
 }
 
 pub(crate) fn get_formula(
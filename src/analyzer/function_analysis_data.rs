@@ -1,11 +1,17 @@
-use crate::{config::Config, scope::CaseScope};
+use crate::{
+    config::Config,
+    scope::{loop_scope::LoopScope, BlockContext, CaseScope},
+};
 use hakana_code_info::analysis_result::Replacement;
 use hakana_code_info::code_location::StmtStart;
 use hakana_code_info::ttype::template::TemplateBound;
 use hakana_code_info::FileSource;
 use hakana_code_info::{
     assertion::Assertion,
-    data_flow::graph::{DataFlowGraph, GraphKind, WholeProgramKind},
+    data_flow::{
+        graph::{DataFlowGraph, GraphKind, WholeProgramKind},
+        node::DataFlowNodeId,
+    },
     functionlike_info::FunctionLikeInfo,
     issue::{get_issue_from_comment, Issue, IssueKind},
     symbol_references::SymbolReferences,
@@ -23,6 +29,9 @@ pub struct FunctionAnalysisData {
     pub data_flow_graph: DataFlowGraph,
     pub case_scopes: Vec<CaseScope>,
     pub issues_to_emit: Vec<Issue>,
+    /// Issues dropped by a fixme or ignore comment, only populated when the config
+    /// asks for them
+    pub suppressed_issues: Vec<Issue>,
     pub inferred_return_types: Vec<TUnion>,
     pub inferred_yield_type: Option<TUnion>,
     pub fully_matched_switch_offsets: FxHashSet<usize>,
@@ -52,6 +61,13 @@ pub struct FunctionAnalysisData {
     pub await_calls_count: usize,
     pub if_block_boundaries: Vec<(u32, u32)>,
     pub loop_boundaries: Vec<(u32, u32)>,
+    pub loop_scopes: FxHashMap<(u32, u32), LoopScope>,
+    /// Bodies of loops whose condition is always falsy, so nothing assigned in them
+    /// is ever read
+    pub unreachable_loop_bodies: Vec<(u32, u32)>,
+    /// Assignments that local variables may still hold where the function returns or
+    /// throws, so they were not overwritten on every path
+    pub node_ids_at_exit: FxHashSet<DataFlowNodeId>,
     pub for_loop_init_boundaries: Vec<(u32, u32)>,
     pub concurrent_block_boundaries: Vec<(u32, u32)>,
     pub definition_locations: FxHashMap<(u32, u32), (StrId, StrId)>,
@@ -74,6 +90,7 @@ impl FunctionAnalysisData {
             data_flow_graph,
             case_scopes: Vec::new(),
             issues_to_emit: Vec::new(),
+            suppressed_issues: Vec::new(),
             inferred_return_types: Vec::new(),
             inferred_yield_type: None,
             fully_matched_switch_offsets: FxHashSet::default(),
@@ -106,12 +123,28 @@ impl FunctionAnalysisData {
             actual_service_calls: FxHashSet::default(),
             if_block_boundaries: Vec::new(),
             loop_boundaries: Vec::new(),
+            loop_scopes: FxHashMap::default(),
+            unreachable_loop_bodies: Vec::new(),
+            node_ids_at_exit: FxHashSet::default(),
             for_loop_init_boundaries: Vec::new(),
             concurrent_block_boundaries: Vec::new(),
             definition_locations: FxHashMap::default(),
         }
     }
 
+    /// Records the assignments each local variable holds at a point where the function
+    /// exits, for dead store detection
+    pub(crate) fn record_locals_at_exit(&mut self, context: &BlockContext, config: &Config) {
+        if !config.find_unused_expressions || self.data_flow_graph.kind != GraphKind::FunctionBody {
+            return;
+        }
+
+        for local_type in context.locals.values() {
+            self.node_ids_at_exit
+                .extend(local_type.parent_nodes.iter().map(|node| node.id.clone()));
+        }
+    }
+
     pub fn add_issue(&mut self, issue: Issue) {
         if !self.issues_to_emit.contains(&issue) {
             self.issues_to_emit.push(issue);
@@ -140,7 +173,7 @@ impl FunctionAnalysisData {
 
         issue.can_fix = config.add_fixmes && config.issues_to_fix.contains(&issue.kind);
 
-        if !self.can_add_issue(&issue) {
+        if !self.can_add_issue(&issue, config) {
             return;
         }
 
@@ -164,6 +197,7 @@ impl FunctionAnalysisData {
                         | IssueKind::UnusedInoutAssignment
                         | IssueKind::UnusedAssignmentInClosure
                         | IssueKind::UnusedAssignmentStatement
+                        | IssueKind::DeadStore
                         | IssueKind::UnusedStatement
                         | IssueKind::OnlyUsedInTests
                         | IssueKind::UnusedFunction
@@ -189,7 +223,7 @@ impl FunctionAnalysisData {
         }
     }
 
-    pub fn can_add_issue(&mut self, issue: &Issue) -> bool {
+    pub fn can_add_issue(&mut self, issue: &Issue, config: &Config) -> bool {
         if matches!(
             &self.data_flow_graph.kind,
             GraphKind::WholeProgram(WholeProgramKind::Taint)
@@ -208,6 +242,7 @@ impl FunctionAnalysisData {
             issue.pos.start_offset,
             issue.pos.end_offset,
         ) {
+            self.record_suppressed_issue(issue, config);
             return false;
         }
 
@@ -218,6 +253,7 @@ impl FunctionAnalysisData {
                 *self.issue_counts.entry(issue.kind.clone()).or_insert(0) += 1;
             }
 
+            self.record_suppressed_issue(issue, config);
             return false;
         }
 
@@ -229,6 +265,12 @@ impl FunctionAnalysisData {
         self.can_output_issue(issue)
     }
 
+    fn record_suppressed_issue(&mut self, issue: &Issue, config: &Config) {
+        if config.collect_suppressed_issues && self.recorded_issues.is_empty() {
+            self.suppressed_issues.push(issue.clone());
+        }
+    }
+
     fn can_output_issue(&mut self, issue: &Issue) -> bool {
         *self.issue_counts.entry(issue.kind.clone()).or_insert(0) += 1;
 
@@ -250,7 +292,8 @@ impl FunctionAnalysisData {
                     if line_issue.0 == issue.kind
                         || (line_issue.0 == IssueKind::UnusedAssignment
                             && (issue.kind == IssueKind::UnusedAssignmentStatement
-                                || issue.kind == IssueKind::UnusedInoutAssignment))
+                                || issue.kind == IssueKind::UnusedInoutAssignment
+                                || issue.kind == IssueKind::DeadStore))
                     {
                         return Some((line_issue.1 .0, line_issue.1 .1));
                     }
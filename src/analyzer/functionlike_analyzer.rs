@@ -2,6 +2,7 @@ use crate::config::Config;
 use crate::custom_hook::FunctionLikeParamData;
 use crate::dataflow::unused_variable_analyzer::{
     add_unused_expression_replacements, check_variables_scoped_incorrectly, check_variables_used,
+    get_all_branch_unused_assignments, get_dead_store_offsets,
 };
 use crate::expr::call_analyzer::reconcile_lower_bounds_with_upper_bounds;
 use crate::expr::fetch::atomic_property_fetch_analyzer;
@@ -9,7 +10,7 @@ use crate::expression_analyzer;
 use crate::file_analyzer::InternalError;
 use crate::scope::BlockContext;
 use crate::scope_analyzer::ScopeAnalyzer;
-use crate::statements_analyzer::StatementsAnalyzer;
+use crate::statements_analyzer::{AnalysisBudget, StatementsAnalyzer};
 use crate::stmt::return_analyzer::handle_inout_at_return;
 use crate::stmt_analyzer::AnalysisError;
 use crate::{file_analyzer::FileAnalyzer, function_analysis_data::FunctionAnalysisData};
@@ -40,6 +41,7 @@ use oxidized::ast_defs::Pos;
 use oxidized::{aast, tast};
 use rustc_hash::FxHashSet;
 
+use std::cell::RefCell;
 use std::rc::Rc;
 
 pub(crate) struct FunctionLikeAnalyzer<'a> {
@@ -407,9 +409,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                         } else {
                             false
                         },
-                        expand_generic: true,
-
-                        ..Default::default()
+                        ..self.file_analyzer.get_type_expansion_options()
                     },
                     &mut analysis_data.data_flow_graph,
                     cost,
@@ -456,7 +456,12 @@ impl<'a> FunctionLikeAnalyzer<'a> {
             };
 
         let mut analysis_data = FunctionAnalysisData::new(
-            DataFlowGraph::new(statements_analyzer.get_config().graph_kind),
+            DataFlowGraph::new(statements_analyzer.get_config().graph_kind).with_traversal_counts(
+                analysis_result
+                    .program_dataflow_graph
+                    .traversal_counts
+                    .clone(),
+            ),
             &statements_analyzer.file_analyzer.file_source,
             &statements_analyzer.comments,
             &self.get_config().all_custom_issues,
@@ -551,12 +556,54 @@ impl<'a> FunctionLikeAnalyzer<'a> {
 
         //let start_t = std::time::Instant::now();
 
+        if let Some(time_budget) = statements_analyzer.get_config().symbol_time_budget {
+            statements_analyzer.set_time_budget(time_budget);
+        }
+
+        if let Some(statement_budget) = statements_analyzer.get_config().symbol_statement_budget {
+            statements_analyzer.set_statement_budget(statement_budget);
+        }
+
         if let Err(AnalysisError::InternalError(error, pos)) =
             statements_analyzer.analyze(fb_ast, &mut analysis_data, &mut context, &mut None)
         {
             return Err(AnalysisError::InternalError(error, pos));
         }
 
+        let exceeded_budget = statements_analyzer.get_exceeded_budget();
+        let completed_analysis = exceeded_budget.is_none();
+
+        if let Some(exceeded_budget) = exceeded_budget {
+            analysis_data.maybe_add_issue(
+                Issue::new(
+                    IssueKind::AnalysisTimeout,
+                    match exceeded_budget {
+                        AnalysisBudget::Statements => format!(
+                            "Stopped analyzing this function after it exceeded {} statements",
+                            statements_analyzer
+                                .get_config()
+                                .symbol_statement_budget
+                                .unwrap_or_default()
+                        ),
+                        AnalysisBudget::Time => format!(
+                            "Stopped analyzing this function after it took longer than {}ms",
+                            statements_analyzer
+                                .get_config()
+                                .symbol_time_budget
+                                .unwrap_or_default()
+                                .as_millis()
+                        ),
+                    },
+                    functionlike_storage
+                        .name_location
+                        .unwrap_or(functionlike_storage.def_location),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+
         // let end_t = start_t.elapsed();
 
         // if let Some(functionlike_id) = &context.function_context.calling_functionlike_id {
@@ -577,6 +624,8 @@ impl<'a> FunctionLikeAnalyzer<'a> {
         // }
 
         if !context.has_returned {
+            analysis_data.record_locals_at_exit(&context, statements_analyzer.get_config());
+
             handle_inout_at_return(
                 functionlike_storage,
                 &mut context,
@@ -622,6 +671,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
         let config = statements_analyzer.get_config();
 
         if config.find_unused_expressions
+            && completed_analysis
             && parent_analysis_data.is_none()
             && analysis_data
                 .issue_counts
@@ -761,7 +811,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                         false
                     },
 
-                    ..Default::default()
+                    ..statements_analyzer.get_type_expansion_options()
                 },
                 &mut analysis_data.data_flow_graph,
                 &mut cost,
@@ -788,7 +838,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                 hook.after_functionlike_analysis(
                     &mut context,
                     functionlike_storage,
-                    true,
+                    completed_analysis,
                     &mut analysis_data,
                     &mut inferred_return_type,
                     codebase,
@@ -861,7 +911,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                 hook.after_functionlike_analysis(
                     &mut context,
                     functionlike_storage,
-                    true,
+                    completed_analysis,
                     &mut analysis_data,
                     &mut inferred_return_type,
                     codebase,
@@ -912,6 +962,10 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                     .extend(analysis_data.insertions);
             }
 
+            parent_analysis_data
+                .suppressed_issues
+                .extend(analysis_data.suppressed_issues);
+
             for issue in analysis_data.issues_to_emit {
                 parent_analysis_data.maybe_add_issue(
                     issue,
@@ -932,6 +986,14 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                 .closure_spans
                 .extend(analysis_data.closure_spans);
 
+            parent_analysis_data
+                .unreachable_loop_bodies
+                .extend(analysis_data.unreachable_loop_bodies);
+
+            parent_analysis_data
+                .node_ids_at_exit
+                .extend(analysis_data.node_ids_at_exit);
+
             parent_analysis_data
                 .matched_ignore_positions
                 .extend(analysis_data.matched_ignore_positions);
@@ -1051,6 +1113,7 @@ impl<'a> FunctionLikeAnalyzer<'a> {
 
                     let mut param_type = param_type.clone();
                     let calling_class = context.function_context.calling_class;
+                    let consulted_symbols = RefCell::new(FxHashSet::default());
 
                     type_expander::expand_union(
                         self.file_analyzer.codebase,
@@ -1073,7 +1136,6 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                             } else {
                                 false
                             },
-                            expand_generic: true,
                             expand_templates: true,
                             where_constraints: if functionlike_storage.where_constraints.is_empty()
                             {
@@ -1081,13 +1143,24 @@ impl<'a> FunctionLikeAnalyzer<'a> {
                             } else {
                                 Some(&functionlike_storage.where_constraints)
                             },
+                            consulted_symbols: Some(&consulted_symbols),
 
-                            ..Default::default()
+                            ..statements_analyzer.get_type_expansion_options()
                         },
                         &mut analysis_data.data_flow_graph,
                         cost,
                     );
 
+                    // the aliases a param type expands through are part of the signature,
+                    // so a change to any of them should re-analyze this function
+                    for symbol in consulted_symbols.into_inner() {
+                        analysis_data.symbol_references.add_reference_to_symbol(
+                            &context.function_context,
+                            symbol,
+                            true,
+                        );
+                    }
+
                     for type_node in param_type.get_all_child_nodes() {
                         if let hakana_code_info::t_union::TypeNode::Atomic(TAtomic::TReference {
                             name,
@@ -1365,7 +1438,10 @@ fn report_unused_expressions(
     calling_functionlike_id: &Option<FunctionLikeIdentifier>,
     functionlike_storage: &FunctionLikeInfo,
 ) {
-    let unused_source_nodes = check_variables_used(&analysis_data.data_flow_graph);
+    let unused_source_nodes = check_variables_used(
+        &analysis_data.data_flow_graph,
+        &analysis_data.unreachable_loop_bodies,
+    );
 
     // Check for variables defined outside if blocks but only used inside
     let (incorrectly_scoped_nodes, async_incorrectly_scoped_nodes) =
@@ -1424,6 +1500,37 @@ fn report_unused_expressions(
 
     let interner = statements_analyzer.interner;
 
+    let dead_store_offsets = get_dead_store_offsets(
+        &analysis_data.data_flow_graph,
+        &unused_source_nodes,
+        &analysis_data.node_ids_at_exit,
+        &analysis_data.closure_spans,
+    );
+
+    let all_branch_offsets = get_all_branch_unused_assignments(
+        fb_ast,
+        unused_source_nodes
+            .0
+            .iter()
+            .chain(unused_source_nodes.1.iter()),
+        &dead_store_offsets,
+    );
+
+    // variables a loop assigns whose values are never read once the loop has finished
+    let loop_only_vars = analysis_data
+        .loop_scopes
+        .iter()
+        .flat_map(|(loop_bounds, loop_scope)| {
+            let live_vars = loop_scope.get_live_vars(*loop_bounds, &analysis_data.data_flow_graph);
+
+            loop_scope
+                .redefined_loop_vars
+                .keys()
+                .filter(move |var_id| !live_vars.contains(*var_id))
+                .map(move |var_id| (*loop_bounds, var_id.clone()))
+        })
+        .collect::<Vec<_>>();
+
     for node in &unused_source_nodes.0 {
         match &node.kind {
             DataFlowNodeKind::VariableUseSource {
@@ -1453,6 +1560,9 @@ fn report_unused_expressions(
                         pure,
                         has_awaitable,
                         false,
+                        dead_store_offsets.contains(&pos.start_offset),
+                        all_branch_offsets.get(&pos.start_offset).copied(),
+                        false,
                     );
                 }
             }
@@ -1544,6 +1654,9 @@ fn report_unused_expressions(
                             &false,
                             has_awaitable,
                             false,
+                            dead_store_offsets.contains(&pos.start_offset),
+                            all_branch_offsets.get(&pos.start_offset).copied(),
+                            is_only_read_in_loop(&node.id, pos, &loop_only_vars, interner),
                         );
                     }
                     VariableSourceKind::InoutArg => {
@@ -1558,6 +1671,9 @@ fn report_unused_expressions(
                             &false,
                             has_awaitable,
                             true,
+                            false,
+                            None,
+                            false,
                         );
                     }
                     VariableSourceKind::InoutParam => {
@@ -1577,6 +1693,9 @@ fn report_unused_expressions(
             analysis_data,
             &unused_variable_nodes,
             statements_analyzer,
+            &statements_analyzer
+                .get_config()
+                .trimmable_assignment_effects,
         )
     }
 }
@@ -1605,6 +1724,9 @@ fn handle_unused_assignment(
     pure: &bool,
     has_awaitable: &bool,
     from_inout: bool,
+    is_dead_store: bool,
+    all_branches_first_offset: Option<u32>,
+    only_read_in_loop: bool,
 ) {
     if config.allow_issue_kind_in_file(
         &IssueKind::UnusedAssignment,
@@ -1620,13 +1742,20 @@ fn handle_unused_assignment(
 
         if (config.issues_to_fix.contains(&IssueKind::UnusedAssignment)
             || (*pure
-                && config
+                && (config
                     .issues_to_fix
-                    .contains(&IssueKind::UnusedAssignmentStatement)))
+                    .contains(&IssueKind::UnusedAssignmentStatement)
+                    || (is_dead_store && config.issues_to_fix.contains(&IssueKind::DeadStore)))))
             && !config.add_fixmes
         {
             unused_variable_nodes.push(node.clone());
         } else {
+            // a variable assigned in every branch of an if/else is only reported once, at
+            // its assignment in the first branch
+            if all_branches_first_offset.is_some_and(|offset| offset != pos.start_offset) {
+                return;
+            }
+
             let interner = statements_analyzer.interner;
             analysis_data.maybe_add_issue(
                 if node.id.to_label(interner) == "$$" {
@@ -1676,6 +1805,26 @@ fn handle_unused_assignment(
                         *pos,
                         calling_functionlike_id,
                     )
+                } else if only_read_in_loop {
+                    Issue::new(
+                        IssueKind::UnusedAssignment,
+                        format!(
+                            "Assignment to {} is only read inside the loop",
+                            node.id.to_label(interner),
+                        ),
+                        *pos,
+                        calling_functionlike_id,
+                    )
+                } else if all_branches_first_offset.is_some() {
+                    Issue::new(
+                        IssueKind::UnusedAssignment,
+                        format!(
+                            "Assignment to {} is unused in every branch",
+                            node.id.to_label(interner),
+                        ),
+                        *pos,
+                        calling_functionlike_id,
+                    )
                 } else {
                     Issue::new(
                         IssueKind::UnusedAssignment,
@@ -1687,10 +1836,49 @@ fn handle_unused_assignment(
                 statements_analyzer.get_config(),
                 statements_analyzer.get_file_path_actual(),
             );
+
+            if is_dead_store && !*has_awaitable {
+                analysis_data.maybe_add_issue(
+                    Issue::new(
+                        IssueKind::DeadStore,
+                        format!(
+                            "Assignment to {} is overwritten before it is read",
+                            node.id.to_label(interner),
+                        ),
+                        *pos,
+                        calling_functionlike_id,
+                    ),
+                    statements_analyzer.get_config(),
+                    statements_analyzer.get_file_path_actual(),
+                );
+            }
         }
     }
 }
 
+/// Whether an assignment sits inside a loop whose assigned value is only read by the loop
+/// itself, and never after it
+fn is_only_read_in_loop(
+    node_id: &DataFlowNodeId,
+    pos: &HPos,
+    loop_only_vars: &[((u32, u32), VarName)],
+    interner: &Interner,
+) -> bool {
+    if let DataFlowNodeId::Var(var_id, ..) = node_id {
+        let var_name = interner.lookup(&var_id.0);
+
+        loop_only_vars
+            .iter()
+            .any(|((loop_start, loop_end), loop_var_id)| {
+                loop_var_id.as_str() == var_name
+                    && pos.start_offset > *loop_start
+                    && pos.start_offset < *loop_end
+            })
+    } else {
+        false
+    }
+}
+
 pub(crate) fn update_analysis_result_with_tast(
     analysis_data: FunctionAnalysisData,
     analysis_result: &mut AnalysisResult,
@@ -1726,6 +1914,19 @@ pub(crate) fn update_analysis_result_with_tast(
         .or_default()
         .extend(issues_to_emit.into_iter().unique().collect::<Vec<_>>());
 
+    if !analysis_data.suppressed_issues.is_empty() {
+        let mut suppressed_issues = analysis_data.suppressed_issues;
+
+        suppressed_issues
+            .sort_by(|a, b| a.pos.start_offset.partial_cmp(&b.pos.start_offset).unwrap());
+
+        analysis_result
+            .suppressed_issues
+            .entry(*file_path)
+            .or_default()
+            .extend(suppressed_issues.into_iter().unique());
+    }
+
     if let GraphKind::WholeProgram(_) = &analysis_data.data_flow_graph.kind {
         if !ignore_taint_path {
             analysis_result
@@ -275,10 +275,7 @@ pub fn reconcile(
             &Some(statements_analyzer.interner),
             statements_analyzer.get_file_path(),
             &mut refined_type,
-            &TypeExpansionOptions {
-                expand_generic: true,
-                ..Default::default()
-            },
+            &TypeExpansionOptions::default(),
             &mut analysis_data.data_flow_graph,
             &mut 0,
         );
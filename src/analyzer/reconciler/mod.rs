@@ -10,9 +10,13 @@ use crate::{
     scope_analyzer::ScopeAnalyzer,
     statements_analyzer::StatementsAnalyzer,
 };
+use hakana_code_info::ttype::{
+    add_union_type, get_mixed_any, get_null, get_value_param,
+    type_expander::{self, StaticClassType, TypeExpansionOptions},
+    wrap_atomic,
+};
 use hakana_code_info::{
     assertion::Assertion,
-    codebase_info::CodebaseInfo,
     data_flow::{graph::GraphKind, node::DataFlowNode, path::PathKind},
     functionlike_identifier::FunctionLikeIdentifier,
     issue::{Issue, IssueKind},
@@ -21,15 +25,7 @@ use hakana_code_info::{
     var_name::VarName,
     VarId,
 };
-use hakana_code_info::{
-    code_location::FilePath,
-    ttype::{
-        add_union_type, get_mixed_any, get_null, get_value_param,
-        type_expander::{self, StaticClassType, TypeExpansionOptions},
-        wrap_atomic,
-    },
-};
-use hakana_str::{Interner, StrId};
+use hakana_str::StrId;
 use lazy_static::lazy_static;
 use oxidized::ast_defs::Pos;
 use regex::Regex;
@@ -154,9 +150,7 @@ pub(crate) fn reconcile_keyed_types(
             Some((**existing_type).clone())
         } else {
             get_value_for_key(
-                codebase,
-                statements_analyzer.interner,
-                statements_analyzer.get_file_path(),
+                statements_analyzer,
                 key.clone(),
                 context,
                 &mut added_var_ids,
@@ -695,9 +689,7 @@ fn break_up_path_into_parts(path: &str) -> Vec<String> {
 }
 
 fn get_value_for_key(
-    codebase: &CodebaseInfo,
-    interner: &Interner,
-    file_path: &FilePath,
+    statements_analyzer: &StatementsAnalyzer,
     key: VarName,
     context: &mut BlockContext,
     added_var_ids: &mut FxHashSet<VarName>,
@@ -708,6 +700,9 @@ fn get_value_for_key(
     possibly_undefined: &mut bool,
     analysis_data: &mut FunctionAnalysisData,
 ) -> Option<TUnion> {
+    let codebase = statements_analyzer.codebase;
+    let interner = statements_analyzer.interner;
+
     lazy_static! {
         static ref INTEGER_REGEX: Regex = Regex::new("^[0-9]+$").unwrap();
     }
@@ -998,9 +993,7 @@ fn get_value_for_key(
                             panic!();
                         } else {
                             let maybe_class_property_type = get_property_type(
-                                codebase,
-                                interner,
-                                file_path,
+                                statements_analyzer,
                                 &fq_class_name,
                                 &interner.get(&property_name)?,
                                 analysis_data,
@@ -1044,13 +1037,13 @@ fn get_value_for_key(
 }
 
 fn get_property_type(
-    codebase: &CodebaseInfo,
-    interner: &Interner,
-    file_path: &FilePath,
+    statements_analyzer: &StatementsAnalyzer,
     classlike_name: &StrId,
     property_name: &StrId,
     analysis_data: &mut FunctionAnalysisData,
 ) -> Option<TUnion> {
+    let codebase = statements_analyzer.codebase;
+
     if !codebase.property_exists(classlike_name, property_name) {
         return None;
     }
@@ -1065,13 +1058,13 @@ fn get_property_type(
     if let Some(mut class_property_type) = class_property_type {
         type_expander::expand_union(
             codebase,
-            &Some(interner),
-            file_path,
+            &Some(statements_analyzer.interner),
+            statements_analyzer.get_file_path(),
             &mut class_property_type,
             &TypeExpansionOptions {
                 self_class: Some(declaring_property_class),
                 static_class_type: StaticClassType::Name(declaring_property_class),
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
@@ -1094,6 +1087,20 @@ pub(crate) fn trigger_issue_for_impossible(
     calling_functionlike_id: &Option<FunctionLikeIdentifier>,
     _suppressed_issues: &FxHashMap<String, usize>,
 ) {
+    if is_null_check_assertion(assertion) {
+        let hpos = statements_analyzer.get_hpos(pos);
+
+        // already reported more precisely by binop_analyzer::check_unnecessary_null_check
+        if analysis_data.issues_to_emit.iter().any(|issue| {
+            issue.kind == IssueKind::UnnecessaryNullCheck
+                && issue.pos.file_path == hpos.file_path
+                && issue.pos.start_offset >= hpos.start_offset
+                && issue.pos.end_offset <= hpos.end_offset
+        }) {
+            return;
+        }
+    }
+
     let mut assertion_string = assertion.to_string(Some(statements_analyzer.interner));
     let mut not_operator = assertion_string.starts_with('!');
 
@@ -1173,6 +1180,16 @@ pub(crate) fn trigger_issue_for_impossible(
     }
 }
 
+fn is_null_check_assertion(assertion: &Assertion) -> bool {
+    matches!(
+        assertion,
+        Assertion::IsType(TAtomic::TNull)
+            | Assertion::IsNotType(TAtomic::TNull)
+            | Assertion::IsIsset
+            | Assertion::IsNotIsset
+    )
+}
+
 fn get_impossible_issue(
     assertion: &Assertion,
     assertion_string: &String,
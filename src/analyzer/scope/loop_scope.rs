@@ -1,6 +1,13 @@
 use std::{collections::BTreeMap, rc::Rc};
 
-use hakana_code_info::{t_union::TUnion, var_name::VarName};
+use hakana_code_info::{
+    data_flow::{
+        graph::DataFlowGraph,
+        node::{DataFlowNodeId, DataFlowNodeKind},
+    },
+    t_union::TUnion,
+    var_name::VarName,
+};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use super::control_action::ControlAction;
@@ -34,4 +41,70 @@ impl LoopScope {
             final_actions: FxHashSet::default(),
         }
     }
+
+    /// Returns the variables the loop (re)defines whose values at the loop's exit are read
+    /// outside of the loop's bounds. Reads inside the loop (e.g. `$i = $i + 1`) don't make
+    /// a variable live on their own, but values they pass on are followed, so a variable
+    /// copied into another one that is read after the loop is live. Reads after the loop
+    /// only show up once the rest of the function body has been analyzed, so callers should
+    /// wait until then.
+    pub fn get_live_vars(
+        &self,
+        loop_bounds: (u32, u32),
+        data_flow_graph: &DataFlowGraph,
+    ) -> FxHashSet<VarName> {
+        self.redefined_loop_vars
+            .iter()
+            .chain(self.possibly_defined_loop_parent_vars.iter())
+            .filter(|(_, var_type)| {
+                is_read_outside_loop(
+                    var_type.parent_nodes.iter().map(|node| node.id.clone()),
+                    loop_bounds,
+                    data_flow_graph,
+                )
+            })
+            .map(|(var_id, _)| var_id.clone())
+            .collect()
+    }
+}
+
+fn is_read_outside_loop(
+    node_ids: impl Iterator<Item = DataFlowNodeId>,
+    loop_bounds: (u32, u32),
+    data_flow_graph: &DataFlowGraph,
+) -> bool {
+    let mut visited_node_ids = FxHashSet::default();
+    let mut node_ids = node_ids.collect::<Vec<_>>();
+
+    while let Some(node_id) = node_ids.pop() {
+        if !visited_node_ids.insert(node_id.clone()) {
+            continue;
+        }
+
+        let Some(edges) = data_flow_graph.forward_edges.get(&node_id) else {
+            continue;
+        };
+
+        for to_id in edges.keys() {
+            // nodes without a position can't be placed, so count them as reads after
+            // the loop
+            let to_pos = data_flow_graph
+                .get_node(to_id)
+                .and_then(|to_node| match &to_node.kind {
+                    DataFlowNodeKind::VariableUseSink { pos } => Some(*pos),
+                    _ => to_node.get_pos(),
+                });
+
+            match to_pos {
+                Some(pos)
+                    if pos.start_offset >= loop_bounds.0 && pos.start_offset <= loop_bounds.1 =>
+                {
+                    node_ids.push(to_id.clone());
+                }
+                _ => return true,
+            }
+        }
+    }
+
+    false
 }
@@ -12,11 +12,21 @@ use hakana_code_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_code_info::functionlike_info::FunctionLikeInfo;
 use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::symbol_references::ReferenceSource;
+use hakana_code_info::ttype::type_expander::TypeExpansionOptions;
 use hakana_code_info::type_resolution::TypeResolutionContext;
 use hakana_str::{Interner, StrId};
 use oxidized::aast;
 use oxidized::ast_defs::Pos;
 use oxidized::prim_defs::Comment;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// The budget a function's analysis was abandoned for running over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AnalysisBudget {
+    Time,
+    Statements,
+}
 
 pub struct StatementsAnalyzer<'a> {
     pub file_analyzer: &'a FileAnalyzer<'a>,
@@ -26,6 +36,10 @@ pub struct StatementsAnalyzer<'a> {
     pub in_migratable_function: bool,
     pub interner: &'a Interner,
     pub codebase: &'a CodebaseInfo,
+    time_budget_deadline: Option<Instant>,
+    statement_budget: Option<usize>,
+    statements_analyzed: Cell<usize>,
+    exceeded_budget: Cell<Option<AnalysisBudget>>,
 }
 
 impl<'a> StatementsAnalyzer<'a> {
@@ -42,6 +56,10 @@ impl<'a> StatementsAnalyzer<'a> {
             in_migratable_function: false,
             interner: file_analyzer.interner,
             codebase: &file_analyzer.codebase,
+            time_budget_deadline: None,
+            statement_budget: None,
+            statements_analyzed: Cell::new(0),
+            exceeded_budget: Cell::new(None),
         }
     }
 
@@ -52,7 +70,17 @@ impl<'a> StatementsAnalyzer<'a> {
         context: &mut BlockContext,
         loop_scope: &mut Option<LoopScope>,
     ) -> Result<(), AnalysisError> {
-        for stmt in stmts {
+        for (i, stmt) in stmts.iter().enumerate() {
+            // the budget is only checked between statements, so a single slow statement
+            // can still overrun it
+            if self.exceeded_budget.get().is_some() || (i > 0 && self.is_past_time_budget()) {
+                return Err(AnalysisError::UserError);
+            }
+
+            if self.is_past_statement_budget() {
+                return Err(AnalysisError::UserError);
+            }
+
             if context.has_returned {
                 if self.get_config().find_unused_expressions {
                     let is_harmless = match &stmt.1 {
@@ -100,6 +128,43 @@ impl<'a> StatementsAnalyzer<'a> {
         self.function_info = Some(function_info);
     }
 
+    pub(crate) fn set_time_budget(&mut self, time_budget: Duration) {
+        self.time_budget_deadline = Some(Instant::now() + time_budget);
+    }
+
+    pub(crate) fn set_statement_budget(&mut self, statement_budget: usize) {
+        self.statement_budget = Some(statement_budget);
+    }
+
+    /// The budget that analysis ran over, if it was abandoned part-way through
+    pub(crate) fn get_exceeded_budget(&self) -> Option<AnalysisBudget> {
+        self.exceeded_budget.get()
+    }
+
+    fn is_past_time_budget(&self) -> bool {
+        if let Some(deadline) = self.time_budget_deadline {
+            if Instant::now() > deadline {
+                self.exceeded_budget.set(Some(AnalysisBudget::Time));
+            }
+        }
+
+        self.exceeded_budget.get().is_some()
+    }
+
+    // counts every statement, including those in nested blocks
+    fn is_past_statement_budget(&self) -> bool {
+        if let Some(statement_budget) = self.statement_budget {
+            let statements_analyzed = self.statements_analyzed.get() + 1;
+            self.statements_analyzed.set(statements_analyzed);
+
+            if statements_analyzed > statement_budget {
+                self.exceeded_budget.set(Some(AnalysisBudget::Statements));
+            }
+        }
+
+        self.exceeded_budget.get().is_some()
+    }
+
     #[inline]
     pub fn get_functionlike_info(&self) -> Option<&FunctionLikeInfo> {
         self.function_info
@@ -110,6 +175,11 @@ impl<'a> StatementsAnalyzer<'a> {
         self.type_resolution_context
     }
 
+    #[inline]
+    pub fn get_type_expansion_options(&self) -> TypeExpansionOptions<'static> {
+        self.file_analyzer.get_type_expansion_options()
+    }
+
     #[inline]
     pub fn get_hpos(&self, pos: &Pos) -> HPos {
         HPos::new(pos, self.file_analyzer.file_source.file_path)
@@ -140,6 +210,7 @@ impl<'a> StatementsAnalyzer<'a> {
                 None => ReferenceSource::Symbol(false, self.get_file_path().0),
             },
             config: self.file_analyzer.analysis_config,
+            type_expansion_options: self.get_type_expansion_options(),
         }
     }
 
@@ -6,8 +6,9 @@ use crate::{
 };
 use hakana_code_info::ttype::{combine_union_types, extend_dataflow_uniquely};
 use hakana_code_info::{
-    analysis_result::Replacement, issue::IssueKind, EFFECT_PURE, EFFECT_READ_GLOBALS,
-    EFFECT_READ_PROPS,
+    analysis_result::Replacement,
+    issue::{Issue, IssueKind},
+    EFFECT_PURE, EFFECT_READ_GLOBALS, EFFECT_READ_PROPS,
 };
 use oxidized::{aast, ast::Uop, ast_defs::Pos};
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -249,6 +250,10 @@ pub(crate) fn analyze(
         loop_scope,
     )?;
 
+    if statements_analyzer.get_config().find_unused_expressions {
+        report_unused_else_branch(statements_analyzer, stmt, &if_scope, analysis_data, context);
+    }
+
     let mut else_context = post_if_context.clone();
 
     else_analyzer::analyze(
@@ -400,3 +405,45 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+/// An else branch after an if branch that always returns or throws can be dedented
+/// into the surrounding block
+fn report_unused_else_branch(
+    statements_analyzer: &StatementsAnalyzer,
+    stmt: (
+        &aast::Expr<(), ()>,
+        &aast::Block<(), ()>,
+        &aast::Block<(), ()>,
+    ),
+    if_scope: &IfScope,
+    analysis_data: &mut FunctionAnalysisData,
+    context: &BlockContext,
+) {
+    let if_always_leaves = !if_scope.if_actions.is_empty()
+        && if_scope
+            .if_actions
+            .iter()
+            .all(|action| matches!(action, ControlAction::Return | ControlAction::End));
+
+    if !if_always_leaves {
+        return;
+    }
+
+    if let Some(first_else_stmt) = stmt
+        .2
+        .iter()
+        .find(|else_stmt| !matches!(else_stmt.1, aast::Stmt_::Noop))
+    {
+        analysis_data.maybe_add_issue(
+            Issue::new(
+                IssueKind::UnusedElseBranch,
+                "This else branch is unnecessary, because the if branch always returns or throws"
+                    .to_string(),
+                statements_analyzer.get_hpos(&first_else_stmt.0),
+                &context.function_context.calling_functionlike_id,
+            ),
+            statements_analyzer.get_config(),
+            statements_analyzer.get_file_path_actual(),
+        );
+    }
+}
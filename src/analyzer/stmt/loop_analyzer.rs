@@ -2,6 +2,8 @@ use std::{collections::BTreeMap, rc::Rc};
 
 use hakana_algebra::Clause;
 
+use hakana_code_info::data_flow::graph::GraphKind;
+use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::t_union::TUnion;
 use hakana_code_info::ttype::{combine_union_types, extend_dataflow_uniquely};
 use hakana_code_info::var_name::VarName;
@@ -13,6 +15,7 @@ use crate::{
     function_analysis_data::FunctionAnalysisData,
     reconciler,
     scope::{control_action::ControlAction, loop_scope::LoopScope, BlockContext},
+    scope_analyzer::ScopeAnalyzer,
     statements_analyzer::StatementsAnalyzer,
     stmt_analyzer::AnalysisError,
 };
@@ -622,6 +625,35 @@ pub(crate) fn analyze<'a>(
         }
     }
 
+    if !is_do {
+        // a do-while body runs once whatever its condition, but other loops with an
+        // always-falsy condition never run theirs
+        if let Some(falsy_pre_condition) = pre_conditions.iter().find(|pre_condition| {
+            analysis_data
+                .get_expr_type(pre_condition.pos())
+                .map_or(false, |condition_type| condition_type.is_always_falsy())
+        }) {
+            analysis_data.maybe_add_issue(
+                Issue::new(
+                    IssueKind::UnreachableLoopBody,
+                    "This loop's body is never executed, because its condition is always falsy"
+                        .to_string(),
+                    statements_analyzer.get_hpos(falsy_pre_condition.pos()),
+                    &loop_parent_context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+
+            if let (Some(first_stmt), Some(last_stmt)) = (stmts.first(), stmts.last()) {
+                analysis_data.unreachable_loop_bodies.push((
+                    first_stmt.0.start_offset() as u32,
+                    last_stmt.0.end_offset() as u32,
+                ));
+            }
+        }
+    }
+
     if always_enters_loop {
         let does_sometimes_continue = loop_scope
             .clone()
@@ -655,6 +687,28 @@ pub(crate) fn analyze<'a>(
         }
     }
 
+    if matches!(analysis_data.data_flow_graph.kind, GraphKind::FunctionBody)
+        && statements_analyzer.get_config().find_unused_expressions
+    {
+        // keep the post-loop types of everything the loop assigns, so that once the whole
+        // body has been analyzed we can tell which of them are read after the loop
+        for var_id in assignment_map.keys() {
+            let var_id = VarName::new(var_id);
+
+            if let Some(var_type) = loop_parent_context.locals.get(&var_id) {
+                loop_scope
+                    .redefined_loop_vars
+                    .insert(var_id, (**var_type).clone());
+            }
+        }
+
+        // nested loops are analyzed once per iteration of their parent loop, and the
+        // last analysis has the most complete picture
+        analysis_data
+            .loop_scopes
+            .insert(loop_context.loop_bounds, loop_scope.clone());
+    }
+
     if let Some(inner_do_context) = inner_do_context {
         return Ok(inner_do_context);
     }
@@ -96,6 +96,8 @@ pub(crate) fn analyze(
         }
     }
 
+    analysis_data.record_locals_at_exit(context, statements_analyzer.get_config());
+
     context.has_returned = true;
     context.control_actions.insert(ControlAction::Return);
 
@@ -129,7 +131,7 @@ pub(crate) fn analyze(
             } else {
                 false
             },
-            ..Default::default()
+            ..statements_analyzer.get_type_expansion_options()
         },
         &mut analysis_data.data_flow_graph,
         &mut 0,
@@ -193,7 +195,7 @@ pub(crate) fn analyze(
                 } else {
                     false
                 },
-                ..Default::default()
+                ..statements_analyzer.get_type_expansion_options()
             },
             &mut analysis_data.data_flow_graph,
             &mut 0,
@@ -548,7 +550,7 @@ pub(crate) fn handle_inout_at_return(
                     analysis_data.data_flow_graph.add_path(
                         &parent_node.id,
                         &new_parent_node.id,
-                        PathKind::Default,
+                        PathKind::InoutWriteback,
                         vec![],
                         vec![],
                     );
@@ -582,7 +584,7 @@ fn handle_dataflow(
         }
 
         return_node
-    } else {
+    } else if let GraphKind::WholeProgram(_) = data_flow_graph.kind {
         if !inferred_type.has_taintable_value() {
             return;
         }
@@ -664,6 +666,8 @@ fn handle_dataflow(
 
         data_flow_graph.add_node(method_node);
         return_node
+    } else {
+        return;
     };
 
     data_flow_graph.add_node(return_node);
@@ -2,7 +2,10 @@ use hakana_code_info::{
     assertion::Assertion,
     issue::{Issue, IssueKind},
     t_union::TUnion,
-    ttype::{combine_union_types, get_mixed_any},
+    ttype::{
+        combine_union_types, get_mixed_any,
+        type_expander::{self, TypeExpansionOptions},
+    },
     var_name::VarName,
 };
 
@@ -172,7 +175,7 @@ pub(crate) fn analyze(
             let assertion = Assertion::NotInArray(case_cond_type);
 
             if let Some(switch_type) = original_context.locals.get(&switch_var_id) {
-                let new_switch_type = negated_assertion_reconciler::reconcile(
+                let mut new_switch_type = negated_assertion_reconciler::reconcile(
                     &assertion,
                     switch_type,
                     false,
@@ -186,6 +189,21 @@ pub(crate) fn analyze(
                     &FxHashMap::default(),
                 );
 
+                // an enum that no case narrowed is still whole, so it's split into its
+                // cases for each of them to be reported as uncovered
+                type_expander::expand_union(
+                    codebase,
+                    &Some(statements_analyzer.interner),
+                    statements_analyzer.get_file_path(),
+                    &mut new_switch_type,
+                    &TypeExpansionOptions {
+                        expand_enum_cases: true,
+                        ..statements_analyzer.get_type_expansion_options()
+                    },
+                    &mut analysis_data.data_flow_graph,
+                    &mut 0,
+                );
+
                 let literal_types = new_switch_type
                     .types
                     .into_iter()
@@ -10,10 +10,15 @@ use hakana_code_info::data_flow::node::{
     DataFlowNode, DataFlowNodeId, DataFlowNodeKind, VariableSourceKind,
 };
 use hakana_code_info::data_flow::path::PathKind;
+use hakana_code_info::issue::{Issue, IssueKind};
+use hakana_code_info::t_atomic::TAtomic;
 use hakana_code_info::ttype::{combine_union_types, get_named_object};
 use hakana_code_info::var_name::VarName;
 use hakana_code_info::VarId;
+use hakana_str::StrId;
 use oxidized::aast;
+use oxidized::aast_visitor::{visit, AstParams, Node, Visitor};
+use oxidized::ast_defs::Pos;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::cell::RefCell;
 use std::{collections::BTreeMap, rc::Rc};
@@ -105,6 +110,10 @@ pub(crate) fn analyze(
 
     let mut all_catches_leave = true;
 
+    let thrown_exception_names = get_thrown_exception_names(&stmt.0 .0, analysis_data);
+
+    let mut previous_catch_names = vec![];
+
     for catch in stmt.1 {
         let mut catch_context = original_context.clone();
         catch_context.has_returned = false;
@@ -137,6 +146,52 @@ pub(crate) fn analyze(
                 ));
             };
 
+        let covering_catch_name = previous_catch_names.iter().find(|previous_name| {
+            *previous_name == catch_classlike_name
+                || codebase.class_extends_or_implements(catch_classlike_name, previous_name)
+        });
+
+        // a thrown exception can reach the catch if it's an instance of the caught class,
+        // or if it's declared as a parent of the caught class and could be one at runtime
+        let catches_thrown_exception = thrown_exception_names.as_ref().map(|thrown_names| {
+            thrown_names.iter().any(|thrown_name| {
+                thrown_name == catch_classlike_name
+                    || codebase.class_extends_or_implements(thrown_name, catch_classlike_name)
+                    || codebase.class_extends_or_implements(catch_classlike_name, thrown_name)
+            })
+        });
+
+        if covering_catch_name.is_some() || catches_thrown_exception == Some(false) {
+            analysis_data.maybe_add_issue(
+                Issue::new(
+                    IssueKind::UnreachableCatch,
+                    if let Some(covering_catch_name) = covering_catch_name {
+                        format!(
+                            "This catch is never reached, because {} is already caught by an earlier catch of {}",
+                            statements_analyzer.interner.lookup(catch_classlike_name),
+                            statements_analyzer.interner.lookup(covering_catch_name)
+                        )
+                    } else {
+                        format!(
+                            "This catch is never reached, because the try block only throws {}",
+                            thrown_exception_names
+                                .iter()
+                                .flatten()
+                                .map(|name| statements_analyzer.interner.lookup(name))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    },
+                    statements_analyzer.get_hpos(&catch.0 .0),
+                    &context.function_context.calling_functionlike_id,
+                ),
+                statements_analyzer.get_config(),
+                statements_analyzer.get_file_path_actual(),
+            );
+        }
+
+        previous_catch_names.push(*catch_classlike_name);
+
         // discard all clauses because crazy stuff may have happened in try block
         catch_context.clauses = vec![];
 
@@ -372,3 +427,118 @@ pub(crate) fn analyze(
 
     Ok(())
 }
+
+/// Returns the classes of the exceptions thrown by `throw` statements in a try block,
+/// or `None` if the block throws nothing explicitly or contains anything else that
+/// might throw (such as a call), in which case any catch could be reached.
+fn get_thrown_exception_names(
+    stmts: &Vec<aast::Stmt<(), ()>>,
+    analysis_data: &FunctionAnalysisData,
+) -> Option<Vec<StrId>> {
+    let mut finder = ThrownExceptionFinder {
+        thrown_exprs: vec![],
+        may_throw_implicitly: false,
+    };
+
+    visit(&mut finder, &mut (), stmts).unwrap();
+
+    if finder.may_throw_implicitly || finder.thrown_exprs.is_empty() {
+        return None;
+    }
+
+    let mut thrown_names = vec![];
+
+    for thrown_pos in finder.thrown_exprs {
+        for thrown_atomic in &analysis_data.get_expr_type(&thrown_pos)?.types {
+            if let TAtomic::TNamedObject { name, .. } = thrown_atomic {
+                if !thrown_names.contains(name) {
+                    thrown_names.push(*name);
+                }
+            } else {
+                return None;
+            }
+        }
+    }
+
+    Some(thrown_names)
+}
+
+struct ThrownExceptionFinder {
+    thrown_exprs: Vec<Pos>,
+    may_throw_implicitly: bool,
+}
+
+impl<'ast> Visitor<'ast> for ThrownExceptionFinder {
+    type Params = AstParams<(), ()>;
+
+    fn object(&mut self) -> &mut dyn Visitor<'ast, Params = Self::Params> {
+        self
+    }
+
+    fn visit_stmt(&mut self, c: &mut (), stmt: &aast::Stmt<(), ()>) -> Result<(), ()> {
+        match &stmt.1 {
+            aast::Stmt_::Throw(boxed) => {
+                self.thrown_exprs.push(boxed.pos().clone());
+
+                // the constructor of the thrown exception is assumed not to throw itself
+                if let aast::Expr_::New(new_boxed) = &boxed.2 {
+                    for arg in &new_boxed.2 {
+                        arg.recurse(c, self)?;
+                    }
+
+                    if let Some(unpacked_arg) = &new_boxed.3 {
+                        unpacked_arg.recurse(c, self)?;
+                    }
+
+                    Ok(())
+                } else {
+                    boxed.recurse(c, self)
+                }
+            }
+            aast::Stmt_::Expr(_)
+            | aast::Stmt_::If(_)
+            | aast::Stmt_::Block(_)
+            | aast::Stmt_::Return(_)
+            | aast::Stmt_::Noop => stmt.recurse(c, self),
+            _ => {
+                self.may_throw_implicitly = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, c: &mut (), expr: &aast::Expr<(), ()>) -> Result<(), ()> {
+        match &expr.2 {
+            aast::Expr_::Binop(boxed)
+                if matches!(
+                    boxed.bop,
+                    oxidized::ast_defs::Bop::Slash | oxidized::ast_defs::Bop::Percent
+                ) =>
+            {
+                self.may_throw_implicitly = true;
+                Ok(())
+            }
+            aast::Expr_::Lvar(_)
+            | aast::Expr_::Int(_)
+            | aast::Expr_::Float(_)
+            | aast::Expr_::String(_)
+            | aast::Expr_::String2(_)
+            | aast::Expr_::True
+            | aast::Expr_::False
+            | aast::Expr_::Null
+            | aast::Expr_::Assign(_)
+            | aast::Expr_::Binop(_)
+            | aast::Expr_::Unop(_)
+            | aast::Expr_::Eif(_)
+            | aast::Expr_::Is(_)
+            | aast::Expr_::Shape(_)
+            | aast::Expr_::Tuple(_)
+            | aast::Expr_::ValCollection(_)
+            | aast::Expr_::KeyValCollection(_) => expr.recurse(c, self),
+            _ => {
+                self.may_throw_implicitly = true;
+                Ok(())
+            }
+        }
+    }
+}
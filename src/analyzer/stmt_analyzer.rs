@@ -156,6 +156,8 @@ pub(crate) fn analyze(
 
             expression_analyzer::analyze(statements_analyzer, boxed, analysis_data, context, false)?;
 
+            analysis_data.record_locals_at_exit(context, statements_analyzer.get_config());
+
             context.control_actions.insert(ControlAction::End);
 
             context.inside_throw = false;
@@ -0,0 +1,377 @@
+use hakana_analyzer::config::Config;
+use hakana_code_info::analysis_result::{AnalysisResult, FullEntry};
+use hakana_code_info::code_location::FilePath;
+use hakana_logger::Logger;
+use hakana_orchestrator::file::get_file_contents_hash;
+use hakana_orchestrator::SuccessfulScanData;
+use hakana_str::Interner;
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Requests only ever contain a file path, so anything bigger than this is either a
+/// confused client or garbage on the socket.
+const MAX_REQUEST_LENGTH: usize = 64 * 1024;
+
+/// Keeps the results of the previous run in memory. The first request scans the whole
+/// codebase; after that a request only re-analyzes the requested file (and whatever
+/// depends on it) if its contents changed since the previous run.
+pub(crate) struct Daemon {
+    config: Arc<Config>,
+    interner: Interner,
+    threads: u8,
+    logger: Arc<Logger>,
+    header: String,
+    previous_scan_data: Option<SuccessfulScanData>,
+    previous_analysis_result: Option<AnalysisResult>,
+}
+
+impl Daemon {
+    pub(crate) fn new(
+        config: Config,
+        interner: Interner,
+        threads: u8,
+        logger: Logger,
+        header: &str,
+    ) -> Self {
+        Self {
+            config: Arc::new(config),
+            interner,
+            threads,
+            logger: Arc::new(logger),
+            header: header.to_string(),
+            previous_scan_data: None,
+            previous_analysis_result: None,
+        }
+    }
+
+    /// Accepts connections one at a time. Each message in either direction is a JSON
+    /// document preceded by its length as a big-endian u32. Requests look like
+    /// `{"file": "path/to/file.hack"}` and get back the list of issues in that file.
+    pub(crate) fn listen(&mut self, socket_path: &str) -> io::Result<()> {
+        if Path::new(socket_path).exists() {
+            std::fs::remove_file(socket_path)?;
+        }
+
+        let listener = UnixListener::bind(socket_path)?;
+
+        self.logger
+            .log_sync(&format!("Listening for requests on {}", socket_path));
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    if let Err(error) = self.handle_connection(&mut stream) {
+                        self.logger
+                            .log_sync(&format!("Connection closed with error: {}", error));
+                    }
+                }
+                Err(error) => {
+                    self.logger
+                        .log_sync(&format!("Could not accept connection: {}", error));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_connection(&mut self, stream: &mut UnixStream) -> io::Result<()> {
+        loop {
+            let mut length_bytes = [0; 4];
+
+            match stream.read_exact(&mut length_bytes) {
+                Ok(_) => {}
+                // the client hung up between requests
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(error) => return Err(error),
+            }
+
+            let request_length = u32::from_be_bytes(length_bytes) as usize;
+
+            if request_length > MAX_REQUEST_LENGTH {
+                // we can't skip past the body without reading it, so give up on the connection
+                write_message(
+                    stream,
+                    &serde_json::json!({
+                        "error": format!(
+                            "Request of {} bytes exceeds the maximum of {} bytes",
+                            request_length, MAX_REQUEST_LENGTH
+                        )
+                    }),
+                )?;
+
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("request length {} is too large", request_length),
+                ));
+            }
+
+            let mut request = vec![0; request_length];
+            stream.read_exact(&mut request)?;
+
+            let response = match serde_json::from_slice::<serde_json::Value>(&request) {
+                Ok(request) => {
+                    if let Some(file) = request.get("file").and_then(|f| f.as_str()) {
+                        self.analyze_file(file)
+                    } else {
+                        serde_json::json!({ "error": "Request is missing a file" })
+                    }
+                }
+                Err(error) => {
+                    serde_json::json!({ "error": format!("Invalid request: {}", error) })
+                }
+            };
+
+            write_message(stream, &response)?;
+        }
+    }
+
+    fn analyze_file(&mut self, file: &str) -> serde_json::Value {
+        let root_dir = self.config.root_dir.trim_end_matches('/');
+
+        let relative_path = get_relative_path(root_dir, file).to_string();
+
+        let absolute_path = if file.starts_with('/') {
+            file.to_string()
+        } else {
+            format!("{}/{}", root_dir, file)
+        };
+
+        let previous_scan_data = self.previous_scan_data.as_ref();
+
+        let is_tracked = previous_scan_data.is_some_and(|previous_scan_data| {
+            previous_scan_data
+                .interner
+                .get(&absolute_path)
+                .is_some_and(|file_id| {
+                    previous_scan_data
+                        .file_system
+                        .file_hashes_and_times
+                        .contains_key(&FilePath(file_id))
+                })
+        });
+
+        if !is_tracked && !Path::new(&absolute_path).exists() {
+            return serde_json::json!({ "error": format!("Could not read {}", file) });
+        }
+
+        // an edit to any file the requested one depends on can change its issues, so the
+        // previous results are only reused if none of the files we know about has changed
+        if is_tracked && !previous_scan_data.is_some_and(has_changed_files) {
+            return serde_json::json!({ "issues": self.get_file_issues(&relative_path) });
+        }
+
+        let result = hakana_orchestrator::scan_and_analyze(
+            Vec::new(),
+            None,
+            None,
+            self.config.clone(),
+            None,
+            self.threads,
+            self.logger.clone(),
+            &self.header,
+            self.interner.clone(),
+            self.previous_scan_data.take(),
+            self.previous_analysis_result.take(),
+            // let the scan find every file that changed, as a normal incremental run does
+            None,
+            || {},
+        );
+
+        match result {
+            Ok((analysis_result, successful_run_data)) => {
+                self.previous_scan_data = Some(successful_run_data);
+                self.previous_analysis_result = Some(analysis_result);
+
+                serde_json::json!({ "issues": self.get_file_issues(&relative_path) })
+            }
+            Err(error) => serde_json::json!({ "error": error.to_string() }),
+        }
+    }
+
+    fn get_file_issues(&self, relative_path: &str) -> Vec<FullEntry> {
+        let (Some(scan_data), Some(analysis_result)) =
+            (&self.previous_scan_data, &self.previous_analysis_result)
+        else {
+            return vec![];
+        };
+
+        analysis_result
+            .get_all_issues(&scan_data.interner, &self.config.root_dir, true)
+            .get(relative_path)
+            .map(|issues| {
+                issues
+                    .iter()
+                    .map(|issue| {
+                        FullEntry::from_issue(
+                            issue,
+                            relative_path,
+                            self.config.get_issue_severity(&issue.kind),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Paths outside the root dir (including ones that merely share its prefix, like
+/// `/repo2` for `/repo`) are returned unchanged
+fn get_relative_path<'a>(root_dir: &str, file: &'a str) -> &'a str {
+    match file.strip_prefix(root_dir.trim_end_matches('/')) {
+        Some(path) if path.starts_with('/') => path.trim_start_matches('/'),
+        _ => file,
+    }
+}
+
+/// Whether any file from the previous run has been edited or removed since
+fn has_changed_files(scan_data: &SuccessfulScanData) -> bool {
+    scan_data
+        .file_system
+        .file_hashes_and_times
+        .iter()
+        .any(|(file_path, (contents_hash, _))| {
+            let str_path = scan_data.interner.lookup(&file_path.0).to_string();
+
+            if str_path.starts_with("hhi_embedded_") || str_path.starts_with("hsl_embedded_") {
+                return false;
+            }
+
+            get_file_contents_hash(&str_path).ok() != Some(*contents_hash)
+        })
+}
+
+fn write_message(stream: &mut UnixStream, message: &serde_json::Value) -> io::Result<()> {
+    let message = serde_json::to_vec(message).unwrap();
+    stream.write_all(&(message.len() as u32).to_be_bytes())?;
+    stream.write_all(&message)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hash::FxHashSet;
+    use std::fs;
+    use std::time::Duration;
+
+    fn send_request(socket_path: &Path, file: &str) -> serde_json::Value {
+        let mut stream = UnixStream::connect(socket_path).unwrap();
+
+        let request = serde_json::to_vec(&serde_json::json!({ "file": file })).unwrap();
+        stream
+            .write_all(&(request.len() as u32).to_be_bytes())
+            .unwrap();
+        stream.write_all(&request).unwrap();
+
+        let mut length_bytes = [0; 4];
+        stream.read_exact(&mut length_bytes).unwrap();
+        let mut response = vec![0; u32::from_be_bytes(length_bytes) as usize];
+        stream.read_exact(&mut response).unwrap();
+
+        serde_json::from_slice(&response).unwrap()
+    }
+
+    fn issue_kinds(response: &serde_json::Value) -> Vec<&str> {
+        response["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|issue| issue["kind"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reanalyzes_after_a_dependency_changes() {
+        let root_dir = std::env::temp_dir().join(format!("hakana-daemon-{}", std::process::id()));
+        fs::create_dir_all(&root_dir).unwrap();
+
+        fs::write(
+            root_dir.join("foo.hack"),
+            "function foo(): int {\n    return bar();\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root_dir.join("bar.hack"),
+            "function bar(): string {\n    return \"a\";\n}\n",
+        )
+        .unwrap();
+
+        let mut config = Config::new(root_dir.to_str().unwrap().to_string(), FxHashSet::default());
+        config.ast_diff = true;
+
+        let mut daemon = Daemon::new(config, Interner::default(), 1, Logger::DevNull, "");
+
+        let socket_path = root_dir.join("daemon.sock");
+        let listen_path = socket_path.to_str().unwrap().to_string();
+
+        // the daemon never stops listening, so its thread is left behind when the test ends
+        std::thread::spawn(move || daemon.listen(&listen_path));
+
+        while !socket_path.exists() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(
+            issue_kinds(&send_request(&socket_path, "foo.hack")),
+            vec!["InvalidReturnStatement"]
+        );
+        // nothing changed, so the answer comes straight from the previous run
+        assert_eq!(
+            issue_kinds(&send_request(&socket_path, "foo.hack")),
+            vec!["InvalidReturnStatement"]
+        );
+
+        fs::write(
+            root_dir.join("bar.hack"),
+            "function bar(): int {\n    return 1;\n}\n",
+        )
+        .unwrap();
+
+        let response = send_request(&socket_path, "foo.hack");
+
+        fs::remove_dir_all(&root_dir).unwrap();
+
+        assert!(issue_kinds(&response).is_empty());
+    }
+
+    #[test]
+    fn strips_the_root_dir_only_at_a_path_boundary() {
+        assert_eq!(get_relative_path("/repo", "/repo/foo.hack"), "foo.hack");
+        assert_eq!(get_relative_path("/repo/", "/repo/foo.hack"), "foo.hack");
+        assert_eq!(get_relative_path("/repo", "foo.hack"), "foo.hack");
+        assert_eq!(
+            get_relative_path("/repo", "/repo2/foo.hack"),
+            "/repo2/foo.hack"
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_requests() {
+        let config = Config::new("/tmp".to_string(), FxHashSet::default());
+        let mut daemon = Daemon::new(config, Interner::default(), 1, Logger::DevNull, "");
+
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        client
+            .write_all(&((MAX_REQUEST_LENGTH + 1) as u32).to_be_bytes())
+            .unwrap();
+
+        let result = daemon.handle_connection(&mut server);
+        drop(server);
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut length_bytes = [0; 4];
+        client.read_exact(&mut length_bytes).unwrap();
+        let mut response = vec![0; u32::from_be_bytes(length_bytes) as usize];
+        client.read_exact(&mut response).unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&response).unwrap();
+
+        assert!(response["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Request of 65537 bytes"));
+    }
+}
@@ -1,11 +1,11 @@
 use clap::{arg, Command};
-use hakana_analyzer::config::{self};
+use hakana_analyzer::config;
 use hakana_analyzer::custom_hook::CustomHook;
 use hakana_code_info::analysis_result::{
     AnalysisResult, CheckPointEntry, CheckPointEntryLevel, FullEntry, HhClientEntry, Replacement,
 };
 use hakana_code_info::data_flow::graph::{GraphKind, WholeProgramKind};
-use hakana_code_info::issue::IssueKind;
+use hakana_code_info::issue::{IssueKind, IssueSeverity};
 use hakana_logger::{Logger, Verbosity};
 use hakana_str::Interner;
 use indexmap::IndexMap;
@@ -20,6 +20,8 @@ use std::process::exit;
 use std::sync::Arc;
 use test_runners::test_runner::TestRunner;
 
+#[cfg(unix)]
+mod daemon;
 pub mod test_runners;
 
 pub fn init(
@@ -79,6 +81,11 @@ pub fn init(
                             .required(false)
                             .help("How many threads to use"),
                     )
+                    .arg(
+                        arg!(--"analysis-threads" <PATH>)
+                            .required(false)
+                            .help("How many threads to use when analyzing files — defaults to --threads"),
+                    )
                     .arg(
                         arg!(--"find-unused-expressions")
                             .required(false)
@@ -89,6 +96,21 @@ pub fn init(
                             .required(false)
                             .help("Find unused definitions — classes, functions, methods etc."),
                     )
+                    .arg(
+                        arg!(--"type-check-only")
+                            .required(false)
+                            .help("Only report type errors, skipping all data-flow analysis"),
+                    )
+                    .arg(
+                        arg!(--"char-columns")
+                            .required(false)
+                            .help("Report issue columns in characters rather than bytes"),
+                    )
+                    .arg(
+                        arg!(--"verify-cached-issues" <COUNT>)
+                            .required(false)
+                            .help("With --diff, re-analyze up to this many files whose issues came from the cache and log any that differ"),
+                    )
                     .arg(
                         arg!(--"show-issue" <PATH>)
                             .required(false)
@@ -131,6 +153,11 @@ pub fn init(
                             .required(false)
                             .help("Whether perform AST-based diffing to speed up execution"),
                     )
+                    .arg(
+                        arg!(--"changed-files-only")
+                            .required(false)
+                            .help("Only report issues in files changed since the last run (requires --diff)"),
+                    )
                     .arg(
                         arg!(--"show-issue-stats")
                             .required(false)
@@ -441,6 +468,33 @@ pub fn init(
                     .arg(arg!(<TEST> "The test to run"))
                     .arg_required_else_help(true),
             )
+            .subcommand(
+                Command::new("daemon")
+                    .about("Keeps the codebase in memory and analyzes files on request")
+                    .arg(arg!(--"root" <PATH>).required(false).help(
+                        "The root directory that Hakana runs in. Defaults to the current directory",
+                    ))
+                    .arg(
+                        arg!(--"config" <PATH>)
+                            .required(false)
+                            .help("Hakana config path — defaults to ./hakana.json"),
+                    )
+                    .arg(
+                        arg!(--"socket" <PATH>)
+                            .required(true)
+                            .help("Unix socket to listen for requests on"),
+                    )
+                    .arg(
+                        arg!(--"threads" <PATH>)
+                            .required(false)
+                            .help("How many threads to use"),
+                    )
+                    .arg(
+                        arg!(--"debug")
+                            .required(false)
+                            .help("Add output for debugging"),
+                    ),
+            )
             .subcommand(
                 Command::new("find-executable")
                     .about("Finds all executable lines of code")
@@ -670,6 +724,20 @@ pub fn init(
         Some(("find-executable", sub_matches)) => {
             do_find_executable(sub_matches, &root_dir, &cwd, threads, logger);
         }
+        Some(("daemon", sub_matches)) => {
+            do_daemon(
+                sub_matches,
+                all_custom_issues,
+                &root_dir,
+                analysis_hooks,
+                config_path,
+                &cwd,
+                threads,
+                logger,
+                header,
+                &mut had_error,
+            );
+        }
         _ => unreachable!(), // If all subcommands are defined above, anything else is unreachable!()
     }
 
@@ -1336,12 +1404,13 @@ fn do_security_check(
     config.hooks = analysis_hooks;
 
     let root_dir = config.root_dir.clone();
+    let config = Arc::new(config);
 
     let result = hakana_orchestrator::scan_and_analyze(
         Vec::new(),
         None,
         None,
-        Arc::new(config),
+        config.clone(),
         None,
         threads,
         Arc::new(logger),
@@ -1374,6 +1443,7 @@ fn do_security_check(
                 cwd,
                 &analysis_result,
                 &successful_run_data.interner,
+                &config,
             );
         }
     }
@@ -1407,6 +1477,7 @@ fn do_analysis(
     let ignore_mixed_issues = sub_matches.is_present("ignore-mixed-issues");
     let show_issue_stats = sub_matches.is_present("show-issue-stats");
     let do_ast_diff = sub_matches.is_present("diff");
+    let changed_files_only = sub_matches.is_present("changed-files-only");
 
     let mut issue_kinds_filter = FxHashSet::default();
 
@@ -1438,6 +1509,21 @@ fn do_analysis(
     config.ignore_mixed_issues = ignore_mixed_issues;
     config.ast_diff = do_ast_diff;
 
+    if sub_matches.is_present("type-check-only") {
+        config.find_unused_expressions = false;
+        config.graph_kind = GraphKind::Disabled;
+    }
+
+    config.char_columns = sub_matches.is_present("char-columns");
+
+    config.analysis_threads = sub_matches
+        .value_of("analysis-threads")
+        .map(|val| val.parse::<u8>().unwrap());
+
+    config.verify_cached_issues = sub_matches
+        .value_of("verify-cached-issues")
+        .map(|val| val.parse::<usize>().unwrap());
+
     config.hooks = analysis_hooks;
 
     let config_path = config_path.unwrap();
@@ -1458,12 +1544,13 @@ fn do_analysis(
     }
 
     let root_dir = config.root_dir.clone();
+    let config = Arc::new(config);
 
     let result = hakana_orchestrator::scan_and_analyze(
         Vec::new(),
         filter,
         ignored,
-        Arc::new(config),
+        config.clone(),
         if sub_matches.is_present("no-cache") {
             None
         } else {
@@ -1480,12 +1567,27 @@ fn do_analysis(
     );
 
     if let Ok((analysis_result, successful_run_data)) = result {
-        for (file_path, issues) in
+        let issues_by_file = if changed_files_only {
+            analysis_result.get_issues_in_changed_files(
+                &successful_run_data.interner,
+                &root_dir,
+                true,
+            )
+        } else {
             analysis_result.get_all_issues(&successful_run_data.interner, &root_dir, true)
-        {
+        };
+
+        for (file_path, issues) in issues_by_file {
             for issue in issues {
-                *had_error = true;
-                println!("{}", issue.format(&file_path));
+                match config.get_issue_severity(&issue.kind) {
+                    IssueSeverity::Error => {
+                        *had_error = true;
+                        println!("{}", issue.format(&file_path));
+                    }
+                    IssueSeverity::Warning => {
+                        println!("{}", issue.format_as_warning(&file_path));
+                    }
+                }
             }
         }
 
@@ -1500,6 +1602,7 @@ fn do_analysis(
                 cwd,
                 &analysis_result,
                 &successful_run_data.interner,
+                &config,
             );
         }
 
@@ -1539,12 +1642,68 @@ fn do_analysis(
     }
 }
 
+#[cfg(unix)]
+fn do_daemon(
+    sub_matches: &clap::ArgMatches,
+    all_custom_issues: FxHashSet<String>,
+    root_dir: &str,
+    analysis_hooks: Vec<Box<dyn CustomHook>>,
+    config_path: Option<&Path>,
+    cwd: &String,
+    threads: u8,
+    logger: Logger,
+    header: &str,
+    had_error: &mut bool,
+) {
+    let socket_path = sub_matches.value_of("socket").unwrap().to_string();
+
+    let mut config = config::Config::new(root_dir.to_string(), all_custom_issues);
+    // after the first request, only re-analyze what changed since the previous one
+    config.ast_diff = true;
+    config.hooks = analysis_hooks;
+
+    let config_path = config_path.unwrap();
+
+    let mut interner = Interner::default();
+
+    if config_path.exists() {
+        config
+            .update_from_file(cwd, config_path, &mut interner)
+            .ok();
+    }
+
+    let mut daemon = daemon::Daemon::new(config, interner, threads, logger, header);
+
+    if let Err(error) = daemon.listen(&socket_path) {
+        println!("Daemon stopped: {}", error);
+        *had_error = true;
+    }
+}
+
+#[cfg(not(unix))]
+fn do_daemon(
+    _sub_matches: &clap::ArgMatches,
+    _all_custom_issues: FxHashSet<String>,
+    _root_dir: &str,
+    _analysis_hooks: Vec<Box<dyn CustomHook>>,
+    _config_path: Option<&Path>,
+    _cwd: &String,
+    _threads: u8,
+    _logger: Logger,
+    _header: &str,
+    had_error: &mut bool,
+) {
+    println!("The daemon listens on a Unix socket, so it's only available on Unix platforms");
+    *had_error = true;
+}
+
 fn write_analysis_output_files(
     output_file: String,
     output_format: Option<String>,
     cwd: &String,
     analysis_result: &AnalysisResult,
     interner: &Interner,
+    config: &config::Config,
 ) {
     let output_path = if output_file.starts_with('/') {
         output_file
@@ -1559,7 +1718,11 @@ fn write_analysis_output_files(
 
             for (file_path, issues) in analysis_result.get_all_issues(interner, cwd, true) {
                 for issue in issues {
-                    entries.push(FullEntry::from_issue(issue, &file_path));
+                    entries.push(FullEntry::from_issue(
+                        issue,
+                        &file_path,
+                        config.get_issue_severity(&issue.kind),
+                    ));
                 }
             }
 
@@ -1581,7 +1744,11 @@ fn write_analysis_output_files(
 
             for (file_path, issues) in analysis_result.get_all_issues(interner, cwd, true) {
                 for issue in issues {
-                    checkpoint_entries.push(CheckPointEntry::from_issue(issue, &file_path));
+                    checkpoint_entries.push(CheckPointEntry::from_issue(
+                        issue,
+                        &file_path,
+                        config.get_issue_severity(&issue.kind),
+                    ));
                 }
             }
 
@@ -1709,6 +1876,29 @@ fn replace_contents(
                     file_contents = file_contents[..start as usize].to_string()
                         + &*file_contents[end as usize..].to_string();
                 }
+                Replacement::TrimPrecedingWhitespaceAndTrailingComment(
+                    beg_of_line,
+                    (comment_start, comment_end),
+                ) => {
+                    let potential_whitespace =
+                        file_contents[(*beg_of_line as usize)..start as usize].to_string();
+                    if potential_whitespace.trim() == "" {
+                        start = *beg_of_line;
+
+                        if beg_of_line > &0
+                            && &file_contents[((*beg_of_line as usize) - 1)..start as usize] == "\n"
+                        {
+                            start -= 1;
+                        }
+                    }
+
+                    if file_contents[end as usize..*comment_start as usize].trim() == "" {
+                        end = *comment_end;
+                    }
+
+                    file_contents = file_contents[..start as usize].to_string()
+                        + &*file_contents[end as usize..].to_string();
+                }
                 Replacement::TrimTrailingWhitespace(end_of_line) => {
                     let potential_whitespace =
                         file_contents[end as usize..(*end_of_line as usize)].to_string();
@@ -1724,6 +1914,11 @@ fn replace_contents(
                         + string
                         + &*file_contents[end as usize..].to_string();
                 }
+                Replacement::ReplaceRange((range_start, range_end), string) => {
+                    file_contents = file_contents[..*range_start as usize].to_string()
+                        + string
+                        + &*file_contents[*range_end as usize..].to_string();
+                }
             }
         }
     }
@@ -1,10 +1,23 @@
 use std::{error::Error, fs::File, io::BufReader, path::Path};
 
+use hakana_analyzer::config::DeprecatedUsageLevel;
+use rustc_hash::FxHashMap;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug, Default)]
 pub struct TestConfig {
    pub max_changes_allowed: Option<usize>,
+   #[serde(default)]
+   pub generated_files: Vec<String>,
+   #[serde(default)]
+   pub char_columns: bool,
+   pub deprecated_usage: Option<DeprecatedUsageLevel>,
+   pub symbol_time_budget_ms: Option<u64>,
+   pub symbol_statement_budget: Option<usize>,
+   pub max_type_alias_atomics: Option<usize>,
+   pub trim_property_reads: Option<bool>,
+   #[serde(default)]
+   pub ignore_issue_files: FxHashMap<String, Vec<String>>,
 }
 
 pub(crate) fn read_from_file(path: &Path) -> Result<TestConfig, Box<dyn Error>> {
@@ -181,6 +181,9 @@ impl TestRunner {
             GraphKind::WholeProgram(WholeProgramKind::Taint)
         } else if dir.contains("/find-paths/") {
             GraphKind::WholeProgram(WholeProgramKind::Query)
+        } else if dir.contains("/type-check-only/") {
+            analysis_config.find_unused_expressions = false;
+            GraphKind::Disabled
         } else {
             GraphKind::FunctionBody
         };
@@ -227,6 +230,10 @@ impl TestRunner {
             analysis_config.collect_goto_definition_locations = true;
         }
 
+        if Path::new(&(dir.to_string() + "/suppressed.txt")).exists() {
+            analysis_config.collect_suppressed_issues = true;
+        }
+
         analysis_config
     }
 
@@ -479,6 +486,24 @@ impl TestRunner {
 
                     let test_output = output;
 
+                    let expected_suppressed_path = dir.clone() + "/suppressed.txt";
+
+                    if Path::new(&expected_suppressed_path).exists() {
+                        let expected_suppressed = fs::read_to_string(expected_suppressed_path)
+                            .unwrap()
+                            .trim()
+                            .to_string();
+
+                        let suppressed_output =
+                            format_suppressed_issues(&analysis_result, &run_data.interner, &dir);
+
+                        if expected_suppressed != suppressed_output.trim() {
+                            test_diagnostics
+                                .push((dir, format_diff(&expected_suppressed, &suppressed_output)));
+                            return ("F".to_string(), Some(run_data), Some(analysis_result));
+                        }
+                    }
+
                     let expected_output_path = dir.clone() + "/output.txt";
                     let expected_output = if Path::new(&expected_output_path).exists() {
                         let expected = fs::read_to_string(expected_output_path)
@@ -778,6 +803,48 @@ fn augment_with_local_config(dir: &String, analysis_config: &mut config::Config)
         if let Some(max_changes_allowed) = test_config.max_changes_allowed {
             analysis_config.max_changes_allowed = max_changes_allowed;
         }
+
+        analysis_config.generated_files = test_config
+            .generated_files
+            .into_iter()
+            .map(|v| glob::Pattern::new(&format!("{}/{}", dir, v)).unwrap())
+            .collect();
+
+        analysis_config.char_columns = test_config.char_columns;
+
+        if let Some(deprecated_usage) = test_config.deprecated_usage {
+            analysis_config.deprecated_usage = deprecated_usage;
+        }
+
+        if let Some(symbol_time_budget_ms) = test_config.symbol_time_budget_ms {
+            analysis_config.symbol_time_budget =
+                Some(Duration::from_millis(symbol_time_budget_ms));
+        }
+
+        if let Some(symbol_statement_budget) = test_config.symbol_statement_budget {
+            analysis_config.symbol_statement_budget = Some(symbol_statement_budget);
+        }
+
+        if let Some(max_type_alias_atomics) = test_config.max_type_alias_atomics {
+            analysis_config.max_type_alias_atomics = Some(max_type_alias_atomics);
+        }
+
+        if let Some(trim_property_reads) = test_config.trim_property_reads {
+            analysis_config.set_trim_property_reads(trim_property_reads);
+        }
+
+        analysis_config.ignore_issue_patterns = test_config
+            .ignore_issue_files
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    IssueKind::from_str_custom(&k, &FxHashSet::default()).unwrap(),
+                    v.into_iter()
+                        .map(|v| glob::Pattern::new(&format!("{}/{}", dir, v)).unwrap())
+                        .collect(),
+                )
+            })
+            .collect();
     }
 }
 
@@ -795,6 +862,25 @@ fn copy_recursively(source: impl AsRef<Path>, destination: impl AsRef<Path>) ->
     Ok(())
 }
 
+fn format_suppressed_issues(
+    analysis_result: &AnalysisResult,
+    interner: &Interner,
+    dir: &str,
+) -> String {
+    let mut suppressed_issues = analysis_result
+        .suppressed_issues
+        .iter()
+        .map(|(file_path, issues)| (file_path.get_relative_path(interner, dir), issues))
+        .collect::<Vec<_>>();
+
+    suppressed_issues.sort_by(|a, b| a.0.cmp(&b.0));
+
+    suppressed_issues
+        .into_iter()
+        .flat_map(|(file_path, issues)| issues.iter().map(move |issue| issue.format(&file_path)))
+        .collect()
+}
+
 fn generate_definition_locations_json(analysis_result: &AnalysisResult, interner: &Interner) -> String {
     use serde_json::json;
 
@@ -10,8 +10,9 @@ use crate::{
         graph::{DataFlowGraph, GraphKind},
         node::DataFlowNodeId,
     },
+    diff::{CodebaseDiff, IssueDiffUpdates},
     function_context::FunctionLikeIdentifier,
-    issue::{Issue, IssueKind},
+    issue::{Issue, IssueKind, IssueSeverity},
     symbol_references::SymbolReferences,
 };
 
@@ -20,14 +21,23 @@ pub enum Replacement {
     Remove,
     TrimPrecedingWhitespace(u32),
     TrimPrecedingWhitespaceAndTrailingComma(u32),
+    /// Like `TrimPrecedingWhitespace`, but also removes the comment at the given range
+    /// when only whitespace separates it from the removed code
+    TrimPrecedingWhitespaceAndTrailingComment(u32, (u32, u32)),
     TrimTrailingWhitespace(u32),
     Substitute(String),
+    /// Replaces the given range (which must be within the replacement's offsets) with
+    /// the given text, so a fix can claim a larger span than the part it rewrites
+    ReplaceRange((u32, u32), String),
 }
 
 #[derive(Clone, Debug)]
 pub struct AnalysisResult {
     pub emitted_issues: FxHashMap<FilePath, Vec<Issue>>,
     pub emitted_definition_issues: FxHashMap<FilePath, Vec<Issue>>,
+    /// Issues that fixme or ignore comments kept out of `emitted_issues`, only
+    /// populated when the config asks for them
+    pub suppressed_issues: FxHashMap<FilePath, Vec<Issue>>,
     pub replacements: FxHashMap<FilePath, BTreeMap<(u32, u32), Replacement>>,
     pub insertions: FxHashMap<FilePath, BTreeMap<u32, Vec<String>>>,
     pub codegen: Vec<(String, Result<String, String>)>,
@@ -40,6 +50,14 @@ pub struct AnalysisResult {
     pub has_invalid_hack_files: bool,
     pub changed_during_analysis_files: FxHashSet<FilePath>,
     pub definition_locations: FxHashMap<FilePath, FxHashMap<(u32, u32), (StrId, StrId)>>,
+    /// Files whose cached issues were shifted or dropped by the diff against the
+    /// previous run
+    pub issue_diff_updates: IssueDiffUpdates,
+    /// The per-file offset changes and deleted ranges since the previous run, only
+    /// populated when AST diffing is enabled
+    pub codebase_diff: CodebaseDiff,
+    /// Files analyzed in this run, as opposed to those whose issues came from the cache
+    pub analyzed_files: FxHashSet<FilePath>,
 }
 
 impl AnalysisResult {
@@ -50,6 +68,7 @@ impl AnalysisResult {
         Self {
             emitted_issues: FxHashMap::default(),
             emitted_definition_issues: FxHashMap::default(),
+            suppressed_issues: FxHashMap::default(),
             replacements: FxHashMap::default(),
             insertions: FxHashMap::default(),
             mixed_source_counts: FxHashMap::default(),
@@ -62,6 +81,9 @@ impl AnalysisResult {
             has_invalid_hack_files: false,
             changed_during_analysis_files: FxHashSet::default(),
             definition_locations: FxHashMap::default(),
+            issue_diff_updates: IssueDiffUpdates::default(),
+            codebase_diff: CodebaseDiff::default(),
+            analyzed_files: FxHashSet::default(),
         }
     }
 
@@ -72,6 +94,12 @@ impl AnalysisResult {
                 .or_default()
                 .extend(issues);
         }
+        for (file_path, issues) in other.suppressed_issues {
+            self.suppressed_issues
+                .entry(file_path)
+                .or_default()
+                .extend(issues);
+        }
         self.replacements.extend(other.replacements);
         self.insertions.extend(other.insertions);
         for (id, c) in other.mixed_source_counts {
@@ -143,11 +171,111 @@ impl AnalysisResult {
 
         issues
     }
+
+    /// Returns emitted issues grouped by file, with files ordered by their path relative
+    /// to `root_dir` and each file's issues sorted by offset.
+    ///
+    /// Definition issues aren't included; use `get_all_issues` for those.
+    pub fn issues_by_file(
+        &self,
+        interner: &Interner,
+        root_dir: &str,
+    ) -> Vec<(FilePath, Vec<&Issue>)> {
+        let mut issues_by_file = self
+            .emitted_issues
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, v)| {
+                let mut file_issues = v.iter().collect::<Vec<_>>();
+                file_issues.sort_by(|a, b| a.pos.start_offset.cmp(&b.pos.start_offset));
+                (*k, file_issues)
+            })
+            .collect::<Vec<_>>();
+
+        issues_by_file
+            .sort_by_cached_key(|(file_path, _)| file_path.get_relative_path(interner, root_dir));
+
+        issues_by_file
+    }
+
+    /// Like `get_all_issues`, but only returns issues in files that were touched by
+    /// `codebase_diff` or analyzed in this run, so callers can report on just the files
+    /// that changed (e.g. in a pull request) rather than on every cached issue
+    pub fn get_issues_in_changed_files(
+        &self,
+        interner: &Interner,
+        root_dir: &str,
+        use_relative_path: bool,
+    ) -> BTreeMap<String, Vec<&Issue>> {
+        let changed_files = self
+            .codebase_diff
+            .diff_map
+            .keys()
+            .chain(self.codebase_diff.deletion_ranges_map.keys())
+            .chain(self.analyzed_files.iter())
+            .map(|file_path| {
+                if use_relative_path {
+                    file_path.get_relative_path(interner, root_dir)
+                } else {
+                    interner.lookup(&file_path.0).to_string()
+                }
+            })
+            .collect::<FxHashSet<_>>();
+
+        let mut issues = self.get_all_issues(interner, root_dir, use_relative_path);
+        issues.retain(|file_path, _| changed_files.contains(file_path));
+        issues
+    }
+
+    /// Counts the issues actually emitted (including definition issues), by kind.
+    ///
+    /// This differs from the `issue_counts` field, which also includes issues
+    /// suppressed by fixme comments.
+    pub fn issue_counts(&self) -> FxHashMap<IssueKind, usize> {
+        let mut counts = FxHashMap::default();
+
+        for issue in self
+            .emitted_issues
+            .values()
+            .chain(self.emitted_definition_issues.values())
+            .flatten()
+        {
+            *counts.entry(issue.kind.clone()).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// Returns a summary of emitted issues with one line per kind, e.g. `142 UnusedAssignment`,
+    /// with the most common kinds first
+    pub fn issue_summary(&self) -> String {
+        let mut counts = self
+            .issue_counts()
+            .into_iter()
+            .map(|(kind, count)| (kind.to_string(), count))
+            .collect::<Vec<_>>();
+
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        counts
+            .into_iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns a histogram of source-to-sink path lengths in the whole-program graph,
+    /// to help tell whether slow taint runs are caused by a few very long paths
+    pub fn get_taint_path_length_histogram(&self, max_depth: usize) -> BTreeMap<usize, usize> {
+        self.program_dataflow_graph
+            .get_source_to_sink_path_lengths(max_depth)
+    }
 }
 
 #[derive(Serialize)]
 pub struct FullEntry {
     pub kind: String,
+    pub severity: IssueSeverity,
     pub description: String,
     pub file_path: String,
     pub start_offset: u32,
@@ -159,9 +287,10 @@ pub struct FullEntry {
 }
 
 impl FullEntry {
-    pub fn from_issue(issue: &Issue, path: &str) -> Self {
+    pub fn from_issue(issue: &Issue, path: &str, severity: IssueSeverity) -> Self {
         Self {
             kind: issue.kind.to_string(),
+            severity,
             description: issue.description.clone(),
             file_path: path.to_string(),
             start_offset: issue.pos.start_offset,
@@ -178,6 +307,7 @@ impl FullEntry {
 #[serde(rename_all = "lowercase")]
 pub enum CheckPointEntryLevel {
     Failure,
+    Warning,
 }
 
 #[derive(Serialize)]
@@ -190,10 +320,13 @@ pub struct CheckPointEntry {
 }
 
 impl CheckPointEntry {
-    pub fn from_issue(issue: &Issue, path: &str) -> Self {
+    pub fn from_issue(issue: &Issue, path: &str, severity: IssueSeverity) -> Self {
         Self {
             output: issue.description.clone(),
-            level: CheckPointEntryLevel::Failure,
+            level: match severity {
+                IssueSeverity::Error => CheckPointEntryLevel::Failure,
+                IssueSeverity::Warning => CheckPointEntryLevel::Warning,
+            },
             filename: path.to_string(),
             line: issue.pos.start_line,
             case: issue.kind.to_string(),
@@ -223,3 +356,166 @@ impl HhClientEntry {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_location::HPos;
+
+    fn issue_at(file_path: FilePath, start_offset: u32) -> Issue {
+        issue_of_kind(IssueKind::InvalidArgument, file_path, start_offset)
+    }
+
+    fn issue_of_kind(kind: IssueKind, file_path: FilePath, start_offset: u32) -> Issue {
+        Issue::new(
+            kind,
+            String::new(),
+            HPos {
+                file_path,
+                start_offset,
+                end_offset: start_offset + 1,
+                start_line: 1,
+                end_line: 1,
+                start_column: 1,
+                end_column: 2,
+            },
+            &None,
+        )
+    }
+
+    #[test]
+    fn issues_by_file_orders_files_by_relative_path() {
+        let mut interner = Interner::default();
+        // interned before a.hack, so its id sorts first
+        let b_path = FilePath(interner.intern("/root/b.hack".to_string()));
+        let a_path = FilePath(interner.intern("/root/a.hack".to_string()));
+
+        let mut analysis_result =
+            AnalysisResult::new(GraphKind::FunctionBody, SymbolReferences::new());
+        analysis_result
+            .emitted_issues
+            .insert(b_path, vec![issue_at(b_path, 10), issue_at(b_path, 2)]);
+        analysis_result
+            .emitted_issues
+            .insert(a_path, vec![issue_at(a_path, 5)]);
+        analysis_result.emitted_issues.insert(
+            FilePath(interner.intern("/root/c.hack".to_string())),
+            vec![],
+        );
+
+        let issues_by_file = analysis_result.issues_by_file(&interner, "/root");
+
+        assert_eq!(
+            issues_by_file
+                .iter()
+                .map(|(file_path, issues)| (
+                    file_path.get_relative_path(&interner, "/root"),
+                    issues
+                        .iter()
+                        .map(|issue| issue.pos.start_offset)
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a.hack".to_string(), vec![5]),
+                ("b.hack".to_string(), vec![2, 10]),
+            ]
+        );
+    }
+
+    #[test]
+    fn issue_counts_sum_kinds_across_files() {
+        let mut interner = Interner::default();
+        let a_path = FilePath(interner.intern("/root/a.hack".to_string()));
+        let b_path = FilePath(interner.intern("/root/b.hack".to_string()));
+
+        let mut analysis_result =
+            AnalysisResult::new(GraphKind::FunctionBody, SymbolReferences::new());
+        analysis_result.emitted_issues.insert(
+            a_path,
+            vec![
+                issue_of_kind(IssueKind::UnusedAssignment, a_path, 1),
+                issue_of_kind(IssueKind::UnusedAssignment, a_path, 5),
+                issue_of_kind(IssueKind::InvalidArgument, a_path, 9),
+            ],
+        );
+        analysis_result.emitted_issues.insert(
+            b_path,
+            vec![
+                issue_of_kind(IssueKind::UnusedAssignment, b_path, 3),
+                issue_of_kind(IssueKind::DeadStore, b_path, 7),
+            ],
+        );
+        // suppressed issues are counted by the issue_counts field, not here
+        analysis_result.suppressed_issues.insert(
+            b_path,
+            vec![issue_of_kind(IssueKind::DeadStore, b_path, 11)],
+        );
+
+        let counts = analysis_result.issue_counts();
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&IssueKind::UnusedAssignment], 3);
+        assert_eq!(counts[&IssueKind::InvalidArgument], 1);
+        assert_eq!(counts[&IssueKind::DeadStore], 1);
+        assert_eq!(
+            analysis_result.issue_summary(),
+            "3 UnusedAssignment\n1 DeadStore\n1 InvalidArgument"
+        );
+    }
+
+    #[test]
+    fn issues_in_changed_files_skip_unchanged_cached_files() {
+        let mut interner = Interner::default();
+        let changed_path = FilePath(interner.intern("/root/changed.hack".to_string()));
+        let unchanged_path = FilePath(interner.intern("/root/unchanged.hack".to_string()));
+
+        let mut analysis_result =
+            AnalysisResult::new(GraphKind::FunctionBody, SymbolReferences::new());
+        analysis_result
+            .emitted_issues
+            .insert(changed_path, vec![issue_at(changed_path, 5)]);
+        analysis_result
+            .emitted_issues
+            .insert(unchanged_path, vec![issue_at(unchanged_path, 5)]);
+        analysis_result
+            .codebase_diff
+            .diff_map
+            .insert(changed_path, vec![(0, 10, 2, 0)]);
+
+        assert_eq!(
+            analysis_result
+                .get_issues_in_changed_files(&interner, "/root", true)
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec!["changed.hack".to_string()]
+        );
+
+        analysis_result.analyzed_files.insert(unchanged_path);
+
+        assert_eq!(
+            analysis_result
+                .get_issues_in_changed_files(&interner, "/root", true)
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec!["changed.hack".to_string(), "unchanged.hack".to_string()]
+        );
+    }
+
+    #[test]
+    fn output_entries_carry_the_issue_severity() {
+        let issue = issue_of_kind(IssueKind::DeprecatedUsage, FilePath(StrId::EMPTY), 0);
+
+        let checkpoint_json = |severity| {
+            serde_json::to_value(CheckPointEntry::from_issue(&issue, "a.hack", severity)).unwrap()
+        };
+        assert_eq!(checkpoint_json(IssueSeverity::Error)["level"], "failure");
+        assert_eq!(checkpoint_json(IssueSeverity::Warning)["level"], "warning");
+
+        let full_json = |severity| {
+            serde_json::to_value(FullEntry::from_issue(&issue, "a.hack", severity)).unwrap()
+        };
+        assert_eq!(full_json(IssueSeverity::Error)["severity"], "error");
+        assert_eq!(full_json(IssueSeverity::Warning)["severity"], "warning");
+    }
+}
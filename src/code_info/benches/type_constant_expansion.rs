@@ -0,0 +1,151 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hakana_code_info::classlike_info::{ClassConstantType, ClassLikeInfo};
+use hakana_code_info::code_location::{FilePath, HPos};
+use hakana_code_info::codebase_info::CodebaseInfo;
+use hakana_code_info::data_flow::graph::{DataFlowGraph, GraphKind};
+use hakana_code_info::functionlike_info::MetaStart;
+use hakana_code_info::t_atomic::TAtomic;
+use hakana_code_info::t_union::TUnion;
+use hakana_code_info::ttype::type_expander::{expand_union, TypeExpansionOptions};
+use hakana_code_info::ttype::{get_mixed, wrap_atomic};
+use hakana_code_info::type_definition_info::TypeDefinitionInfo;
+use hakana_str::{Interner, StrId};
+use rustc_hash::FxHashMap;
+
+const HIERARCHY_DEPTH: usize = 20;
+const ALIAS_COUNT: usize = 50;
+
+fn get_pos() -> HPos {
+    HPos {
+        file_path: FilePath(StrId::EMPTY),
+        start_offset: 0,
+        end_offset: 0,
+        start_line: 1,
+        end_line: 1,
+        start_column: 1,
+        end_column: 1,
+    }
+}
+
+/// Builds a chain of classes that all inherit a `T` type constant made up of many
+/// type aliases, and returns `C::T` for each class `C` in the chain
+fn get_class_hierarchy(interner: &mut Interner, codebase: &mut CodebaseInfo) -> Vec<TUnion> {
+    let member_name = interner.intern("T".to_string());
+
+    let aliases = (0..ALIAS_COUNT)
+        .map(|i| {
+            let name = interner.intern(format!("Alias{}", i));
+
+            codebase.type_definitions.insert(
+                name,
+                TypeDefinitionInfo {
+                    newtype_file: None,
+                    as_type: None,
+                    actual_type: wrap_atomic(TAtomic::TLiteralInt { value: i as i64 }),
+                    template_types: vec![],
+                    generic_variance: FxHashMap::default(),
+                    shape_field_taints: None,
+                    is_literal_string: false,
+                    location: get_pos(),
+                    user_defined: true,
+                    generated: false,
+                    attributes: vec![],
+                },
+            );
+
+            TAtomic::TTypeAlias {
+                name,
+                newtype: false,
+                type_params: None,
+                as_type: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut parent_classes = vec![];
+
+    (0..HIERARCHY_DEPTH)
+        .map(|i| {
+            let class_name = interner.intern(format!("Class{}", i));
+
+            let mut class_info = ClassLikeInfo::new(
+                class_name,
+                get_pos(),
+                MetaStart {
+                    start_offset: 0,
+                    start_line: 1,
+                    start_column: 1,
+                },
+                get_pos(),
+            );
+            class_info.direct_parent_class = parent_classes.last().copied();
+            class_info.all_parent_classes = parent_classes.clone();
+            class_info.type_constants.insert(
+                member_name,
+                ClassConstantType::Concrete(TUnion::new(aliases.clone())),
+            );
+            codebase.classlike_infos.insert(class_name, class_info);
+
+            parent_classes.push(class_name);
+
+            wrap_atomic(TAtomic::TClassTypeConstant {
+                class_type: Box::new(TAtomic::TNamedObject {
+                    name: class_name,
+                    type_params: None,
+                    is_this: false,
+                    extra_types: None,
+                    remapped_params: false,
+                }),
+                member_name,
+                as_type: Box::new(get_mixed()),
+            })
+        })
+        .collect()
+}
+
+fn expand_all(codebase: &CodebaseInfo, type_constants: &[TUnion]) {
+    let options = TypeExpansionOptions::default();
+    let mut data_flow_graph = DataFlowGraph::new(GraphKind::FunctionBody);
+    let mut cost = 0;
+
+    // each type constant is expanded several times, like one used in many signatures
+    for _ in 0..10 {
+        for type_constant in type_constants {
+            let mut type_constant = type_constant.clone();
+
+            expand_union(
+                codebase,
+                &None,
+                &FilePath(StrId::EMPTY),
+                &mut type_constant,
+                &options,
+                &mut data_flow_graph,
+                &mut cost,
+            );
+
+            black_box(type_constant);
+        }
+    }
+}
+
+fn type_constant_expansion(c: &mut Criterion) {
+    let mut interner = Interner::default();
+    let mut codebase = CodebaseInfo::new();
+    let type_constants = get_class_hierarchy(&mut interner, &mut codebase);
+
+    c.bench_function("expand type constants", |b| {
+        b.iter(|| expand_all(&codebase, &type_constants))
+    });
+
+    // a new generation for every iteration means nothing expanded in an earlier
+    // iteration is reused, to compare against expansion without the cache
+    c.bench_function("expand type constants, new codebase generation", |b| {
+        b.iter(|| {
+            codebase.bump_generation();
+            expand_all(&codebase, &type_constants)
+        })
+    });
+}
+
+criterion_group!(benches, type_constant_expansion);
+criterion_main!(benches);
@@ -25,4 +25,6 @@ pub struct ConstantInfo {
     pub suppressed_issues: Vec<(IssueKind, HPos)>,
 
     pub defining_class: StrId,
+
+    pub user_defined: bool,
 }
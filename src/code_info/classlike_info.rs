@@ -37,6 +37,9 @@ pub struct ClassLikeInfo {
 
     pub is_deprecated: bool,
 
+    /// The message given to `<<__Deprecated>>`, if any
+    pub deprecation_message: Option<StrId>,
+
     pub internal_to: Option<String>,
 
     pub name: StrId,
@@ -212,6 +215,7 @@ impl ClassLikeInfo {
             is_populated: false,
             is_stubbed: false,
             is_deprecated: false,
+            deprecation_message: None,
             is_abstract: false,
             is_final: false,
             kind: SymbolKind::Class,
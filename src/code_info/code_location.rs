@@ -57,4 +57,26 @@ impl HPos {
             end_column: end_column as u16,
         }
     }
+
+    /// Recomputes the 1-based start and end columns by counting Unicode scalar values
+    /// (rather than bytes) from the start of each line
+    pub fn set_char_columns(&mut self, file_contents: &str) {
+        self.start_column = get_char_column(file_contents, self.start_offset);
+        self.end_column = get_char_column(file_contents, self.end_offset);
+    }
+}
+
+fn get_char_column(file_contents: &str, offset: u32) -> u16 {
+    let bytes = &file_contents.as_bytes()[..(offset as usize).min(file_contents.len())];
+    let line_start = bytes
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+
+    // every byte that isn't a UTF-8 continuation byte starts a new scalar value
+    (bytes[line_start..]
+        .iter()
+        .filter(|byte| (**byte & 0xC0) != 0x80)
+        .count()
+        + 1) as u16
 }
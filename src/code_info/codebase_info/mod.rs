@@ -1,17 +1,23 @@
 pub mod symbols;
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use self::symbols::SymbolKind;
 pub use self::symbols::Symbols;
 use crate::classlike_info::ClassLikeInfo;
 use crate::code_location::HPos;
+use crate::data_flow::graph::{DataFlowGraph, GraphKind};
 use crate::file_info::FileInfo;
+use crate::functionlike_identifier::FunctionLikeIdentifier;
 use crate::functionlike_info::FunctionLikeInfo;
 use crate::method_identifier::MethodIdentifier;
 use crate::property_info::PropertyInfo;
 use crate::t_atomic::TAtomic;
 use crate::t_union::TUnion;
+use crate::ttype::type_expander::{
+    get_expanded_signature, ExpandedSignature, TypeExpansionOptions,
+};
 use crate::type_definition_info::TypeDefinitionInfo;
 use crate::{class_constant_info::ConstantInfo, code_location::FilePath};
 use hakana_str::StrId;
@@ -36,6 +42,18 @@ pub struct CodebaseInfo {
     pub safe_symbols: FxHashSet<StrId>,
     /* Symbol members that have already been checked on a previous Hakana run */
     pub safe_symbol_members: FxHashSet<(StrId, StrId)>,
+
+    /// Identifies this version of the codebase, so caches of types expanded against
+    /// it can tell when they're stale. Unique to the process, and changed whenever the
+    /// codebase is populated.
+    #[serde(skip, default = "next_generation")]
+    pub generation: u64,
+}
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Default for CodebaseInfo {
@@ -60,9 +78,16 @@ impl CodebaseInfo {
             files: FxHashMap::default(),
             safe_symbols: FxHashSet::default(),
             safe_symbol_members: FxHashSet::default(),
+            generation: next_generation(),
         }
     }
 
+    /// Must be called whenever the codebase changes, so types expanded against the
+    /// previous version of it aren't reused
+    pub fn bump_generation(&mut self) {
+        self.generation = next_generation();
+    }
+
     #[inline]
     pub fn class_or_interface_exists(&self, fq_class_name: &StrId) -> bool {
         matches!(
@@ -147,6 +172,9 @@ impl CodebaseInfo {
         false
     }
 
+    /// The populator flattens every ancestor into `all_parent_classes` and
+    /// `all_parent_interfaces`, so this is two hash lookups regardless of how deep
+    /// the hierarchy is, and doesn't need a separate cache.
     pub fn class_extends_or_implements(&self, child_class: &StrId, parent_class: &StrId) -> bool {
         if let Some(classlike_storage) = self.classlike_infos.get(child_class) {
             return classlike_storage.all_parent_classes.contains(parent_class)
@@ -457,6 +485,34 @@ impl CodebaseInfo {
         self.get_method(&self.get_declaring_method_id(method_id))
     }
 
+    /// Returns the parameter and return types of the given function or method with
+    /// all type aliases expanded, e.g. for generating documentation
+    pub fn expand_functionlike_signature(
+        &self,
+        id: &FunctionLikeIdentifier,
+        options: &TypeExpansionOptions,
+    ) -> Option<ExpandedSignature> {
+        let functionlike_info = match id {
+            FunctionLikeIdentifier::Function(name) => {
+                self.functionlike_infos.get(&(*name, StrId::EMPTY))?
+            }
+            FunctionLikeIdentifier::Method(classlike_name, method_name) => {
+                self.get_declaring_method(&MethodIdentifier(*classlike_name, *method_name))?
+            }
+            _ => return None,
+        };
+
+        Some(get_expanded_signature(
+            functionlike_info,
+            self,
+            &None,
+            &functionlike_info.def_location.file_path,
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+            options,
+            &mut 0,
+        ))
+    }
+
     pub fn extend(&mut self, other: CodebaseInfo) {
         self.classlike_infos.extend(other.classlike_infos);
         self.functionlike_infos.extend(other.functionlike_infos);
@@ -3,12 +3,67 @@ use super::{
     path::{DataFlowPath, PathKind},
 };
 use crate::{
-    code_location::FilePath, data_flow::node::VariableSourceKind,
-    function_context::FunctionLikeIdentifier, t_union::TUnion, taint::SinkType,
+    code_location::FilePath,
+    data_flow::node::VariableSourceKind,
+    function_context::FunctionLikeIdentifier,
+    t_union::TUnion,
+    taint::{get_sinks_for_sources, SinkType},
 };
 use hakana_str::StrId;
 use oxidized::ast_defs::Pos;
 use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// The longest chain of edges `reaches_any` follows before giving up. Searches that
+/// need to go further, like a taint analysis with a larger `max_depth`, can't rely on it.
+pub const MAX_TRAVERSAL_DEPTH: usize = 200;
+
+/// How often each path kind has been followed, shared between the graphs of a single
+/// analysis run
+pub type TraversalCounts = Arc<Mutex<BTreeMap<String, u64>>>;
+
+/// Tallies the path kinds followed during a single traversal, and adds them to the
+/// graph's counts when dropped so the shared map is only locked once per traversal
+struct TraversalCounter<'a> {
+    counts: Option<(&'a TraversalCounts, FxHashMap<String, u64>)>,
+}
+
+impl<'a> TraversalCounter<'a> {
+    fn new(totals: &'a Option<TraversalCounts>) -> Self {
+        Self {
+            counts: totals.as_ref().map(|totals| (totals, FxHashMap::default())),
+        }
+    }
+
+    #[inline]
+    fn record(&mut self, path_kind: &PathKind) {
+        if let Some((_, counts)) = &mut self.counts {
+            let name = match path_kind {
+                PathKind::Default => "default".to_string(),
+                _ => path_kind.to_string(),
+            };
+
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+}
+
+impl Drop for TraversalCounter<'_> {
+    fn drop(&mut self) {
+        if let Some((totals, counts)) = self.counts.take() {
+            if counts.is_empty() {
+                return;
+            }
+
+            let mut totals = totals.lock().unwrap();
+
+            for (name, count) in counts {
+                *totals.entry(name).or_insert(0) += count;
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WholeProgramKind {
@@ -20,6 +75,21 @@ pub enum WholeProgramKind {
 pub enum GraphKind {
     FunctionBody,
     WholeProgram(WholeProgramKind),
+    /// Nothing is recorded, for runs that only report type errors
+    Disabled,
+}
+
+/// The taint flows, as (source, sink type) pairs, that differ between two runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaintDiff {
+    pub added: Vec<(DataFlowNodeId, SinkType)>,
+    pub removed: Vec<(DataFlowNodeId, SinkType)>,
+}
+
+impl TaintDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +103,14 @@ pub struct DataFlowGraph {
     pub mixed_source_counts: FxHashMap<DataFlowNodeId, FxHashSet<String>>,
     pub specializations: FxHashMap<DataFlowNodeId, FxHashSet<(FilePath, u32)>>,
     specialized_calls: FxHashMap<(FilePath, u32), FxHashSet<DataFlowNodeId>>,
+    /// Arbitrary labels attached to nodes by custom analyses, e.g. "user-controlled"
+    tags: FxHashMap<DataFlowNodeId, FxHashSet<String>>,
+    /// Whether `backward_edges` is kept up to date. Always true for function body
+    /// graphs, and for whole-program graphs once `invert` has been called
+    has_backward_edges: bool,
+    /// Where to count the path kinds followed when looking for origin nodes or
+    /// checking whether a variable is used, if anywhere
+    pub traversal_counts: Option<TraversalCounts>,
 }
 
 impl DataFlowGraph {
@@ -47,10 +125,24 @@ impl DataFlowGraph {
             mixed_source_counts: FxHashMap::default(),
             specializations: FxHashMap::default(),
             specialized_calls: FxHashMap::default(),
+            tags: FxHashMap::default(),
+            has_backward_edges: kind == GraphKind::FunctionBody,
+            traversal_counts: None,
         }
     }
 
+    /// Counts the path kinds this graph's traversals follow into `traversal_counts`,
+    /// which is usually shared with the other graphs of the same analysis run
+    pub fn with_traversal_counts(mut self, traversal_counts: Option<TraversalCounts>) -> Self {
+        self.traversal_counts = traversal_counts;
+        self
+    }
+
     pub fn add_node(&mut self, node: DataFlowNode) {
+        if self.kind == GraphKind::Disabled {
+            return;
+        }
+
         match &node.kind {
             DataFlowNodeKind::Vertex { is_specialized, .. } => {
                 if let GraphKind::WholeProgram(_) = &self.kind {
@@ -90,11 +182,11 @@ impl DataFlowGraph {
         added_taints: Vec<SinkType>,
         removed_taints: Vec<SinkType>,
     ) {
-        if from_id == to_id {
+        if from_id == to_id || self.kind == GraphKind::Disabled {
             return;
         }
 
-        if let GraphKind::FunctionBody = self.kind {
+        if self.has_backward_edges {
             self.backward_edges
                 .entry(to_id.clone())
                 .or_default()
@@ -119,28 +211,50 @@ impl DataFlowGraph {
             panic!("Graph kinds are different");
         }
 
+        if self.has_backward_edges && !graph.has_backward_edges {
+            for (from_id, edges) in &graph.forward_edges {
+                for to_id in edges.keys() {
+                    self.backward_edges
+                        .entry(to_id.clone())
+                        .or_default()
+                        .insert(from_id.clone());
+                }
+            }
+        }
+
         for (key, edges) in graph.forward_edges {
             self.forward_edges.entry(key).or_default().extend(edges);
         }
 
-        if self.kind == GraphKind::FunctionBody {
+        if self.has_backward_edges && graph.has_backward_edges {
             for (key, edges) in graph.backward_edges {
                 self.backward_edges.entry(key).or_default().extend(edges);
             }
-            for (key, count) in graph.mixed_source_counts {
-                if let Some(existing_count) = self.mixed_source_counts.get_mut(&key) {
-                    existing_count.extend(count);
-                } else {
-                    self.mixed_source_counts.insert(key, count);
+        }
+
+        match self.kind {
+            GraphKind::FunctionBody => {
+                for (key, count) in graph.mixed_source_counts {
+                    if let Some(existing_count) = self.mixed_source_counts.get_mut(&key) {
+                        existing_count.extend(count);
+                    } else {
+                        self.mixed_source_counts.insert(key, count);
+                    }
                 }
             }
-        } else {
-            for (key, specializations) in graph.specializations {
-                self.specializations
-                    .entry(key)
-                    .or_default()
-                    .extend(specializations);
+            GraphKind::WholeProgram(_) => {
+                for (key, specializations) in graph.specializations {
+                    self.specializations
+                        .entry(key)
+                        .or_default()
+                        .extend(specializations);
+                }
             }
+            GraphKind::Disabled => {}
+        }
+
+        for (key, tags) in graph.tags {
+            self.tags.entry(key).or_default().extend(tags);
         }
 
         self.vertices.extend(graph.vertices);
@@ -148,6 +262,137 @@ impl DataFlowGraph {
         self.sinks.extend(graph.sinks);
     }
 
+    /// Attaches a tag to the given node, so that plugins can mark nodes without
+    /// needing a dedicated node kind
+    pub fn add_tag(&mut self, id: &DataFlowNodeId, tag: &str) {
+        if self.kind == GraphKind::Disabled {
+            return;
+        }
+
+        self.tags
+            .entry(id.clone())
+            .or_default()
+            .insert(tag.to_string());
+    }
+
+    /// Returns every node that has been given the tag, in a stable order
+    pub fn nodes_with_tag(&self, tag: &str) -> Vec<&DataFlowNodeId> {
+        let mut node_ids = self
+            .tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+
+        node_ids.sort();
+        node_ids
+    }
+
+    /// Builds `backward_edges` for a whole-program graph, which normally only keeps
+    /// forward edges to save memory, so that queries rooted at a sink don't need to
+    /// scan every forward edge. Edges added afterwards are indexed too, and calling
+    /// this again is a no-op.
+    pub fn invert(&mut self) {
+        if self.has_backward_edges || self.kind == GraphKind::Disabled {
+            return;
+        }
+
+        for (from_id, edges) in &self.forward_edges {
+            for to_id in edges.keys() {
+                self.backward_edges
+                    .entry(to_id.clone())
+                    .or_default()
+                    .insert(from_id.clone());
+            }
+        }
+
+        self.has_backward_edges = true;
+    }
+
+    /// Merges taint sources that only differ by their specialization into a single
+    /// unspecialized source, recording the specializations so that traversal still
+    /// visits each specialized call site. Sources are only merged when they have the
+    /// same kind and taint types.
+    pub fn merge_sources(&mut self) {
+        if !matches!(self.kind, GraphKind::WholeProgram(_)) {
+            return;
+        }
+
+        let mut grouped_sources: FxHashMap<DataFlowNodeId, Vec<DataFlowNodeId>> =
+            FxHashMap::default();
+
+        for source_id in self.sources.keys() {
+            if source_id.is_specialized() {
+                let (unspecialized_id, _) = source_id.unspecialize();
+                grouped_sources
+                    .entry(unspecialized_id)
+                    .or_default()
+                    .push(source_id.clone());
+            }
+        }
+
+        for (unspecialized_id, source_ids) in grouped_sources {
+            if source_ids.len() < 2 || self.sources.contains_key(&unspecialized_id) {
+                continue;
+            }
+
+            let mut merged_pos = None;
+            let mut merged_types = None;
+            let mut can_merge = true;
+
+            for (i, source_id) in source_ids.iter().enumerate() {
+                if let DataFlowNodeKind::TaintSource { pos, types } = &self.sources[source_id].kind
+                {
+                    if i == 0 {
+                        merged_pos = *pos;
+                        merged_types = Some(types);
+                    } else if merged_types != Some(types) {
+                        can_merge = false;
+                        break;
+                    } else if &merged_pos != pos {
+                        merged_pos = None;
+                    }
+                } else {
+                    can_merge = false;
+                    break;
+                }
+            }
+
+            if !can_merge {
+                continue;
+            }
+
+            let merged_types = merged_types.unwrap().clone();
+
+            for source_id in source_ids {
+                self.sources.remove(&source_id);
+
+                let (_, specialization_key) = source_id.unspecialize();
+
+                self.specializations
+                    .entry(unspecialized_id.clone())
+                    .or_default()
+                    .insert(specialization_key);
+
+                self.specialized_calls
+                    .entry(specialization_key)
+                    .or_default()
+                    .insert(unspecialized_id.clone());
+            }
+
+            self.sources.insert(
+                unspecialized_id.clone(),
+                DataFlowNode {
+                    id: unspecialized_id,
+                    kind: DataFlowNodeKind::TaintSource {
+                        pos: merged_pos,
+                        types: merged_types,
+                    },
+                },
+            );
+        }
+    }
+
     /// Returns a set of nodes that are origin nodes for the given assignment
     pub fn get_origin_node_ids(
         &self,
@@ -156,6 +401,7 @@ impl DataFlowGraph {
         var_ids_only: bool,
     ) -> Vec<DataFlowNodeId> {
         let mut visited_child_ids = FxHashSet::default();
+        let mut traversal_counter = TraversalCounter::new(&self.traversal_counts);
 
         let mut origin_nodes = vec![];
 
@@ -167,7 +413,9 @@ impl DataFlowGraph {
             child_node_ids.push(assignment_node_id.clone());
         }
 
-        for _ in 0..50 {
+        // every node is visited at most once, so this terminates once we've run out
+        // of unvisited parents, however deep (or cyclic) the graph is
+        loop {
             let mut all_parent_nodes = vec![];
 
             for child_node_id in child_node_ids {
@@ -194,6 +442,8 @@ impl DataFlowGraph {
                                 if ignore_paths.contains(&path.kind) {
                                     break;
                                 }
+
+                                traversal_counter.record(&path.kind);
                             }
                         }
 
@@ -311,6 +561,368 @@ impl DataFlowGraph {
         source_properties
     }
 
+    /// Returns every taint source whose data isn't allowed to reach a sink of the given
+    /// type, e.g. all the sources that could lead to SQL injection
+    pub fn sources_for_sink_type(&self, sink_type: &SinkType) -> Vec<&DataFlowNode> {
+        self.sources
+            .values()
+            .filter(|source| {
+                if let DataFlowNodeKind::TaintSource { types, .. } = &source.kind {
+                    types
+                        .iter()
+                        .any(|source_type| get_sinks_for_sources(source_type).contains(sink_type))
+                } else {
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the set of sink types reachable from the given node, taking into
+    /// account any taints removed (e.g. by sanitizers) along each path
+    pub fn reachable_sink_types(&self, from_id: &DataFlowNodeId) -> FxHashSet<SinkType> {
+        let mut sink_types = FxHashSet::default();
+
+        let mut visited = FxHashSet::default();
+        let mut queue = vec![(from_id.clone(), vec![])];
+
+        while let Some((node_id, removed_taints)) = queue.pop() {
+            if !visited.insert((node_id.clone(), removed_taints.clone())) {
+                continue;
+            }
+
+            if let Some(DataFlowNode {
+                kind: DataFlowNodeKind::TaintSink { types, .. },
+                ..
+            }) = self.sinks.get(&node_id)
+            {
+                sink_types.extend(
+                    types
+                        .iter()
+                        .filter(|t| !removed_taints.contains(*t))
+                        .cloned(),
+                );
+            }
+
+            let mut next_ids = vec![node_id.clone()];
+
+            if let Some(specializations) = self.specializations.get(&node_id) {
+                next_ids.extend(
+                    specializations
+                        .iter()
+                        .map(|(file_path, offset)| node_id.specialize(*file_path, *offset)),
+                );
+            }
+
+            for next_id in next_ids {
+                if let Some(forward_edges) = self.forward_edges.get(&next_id) {
+                    for (to_id, path) in forward_edges {
+                        if let PathKind::Aggregate = path.kind {
+                            continue;
+                        }
+
+                        let mut new_removed_taints = removed_taints.clone();
+
+                        for removed_taint in &path.removed_taints {
+                            if !new_removed_taints.contains(removed_taint) {
+                                new_removed_taints.push(removed_taint.clone());
+                            }
+                        }
+
+                        new_removed_taints.retain(|t| !path.added_taints.contains(t));
+                        new_removed_taints.sort_by_key(|t| t.to_string());
+
+                        queue.push((to_id.clone(), new_removed_taints));
+                    }
+                }
+            }
+        }
+
+        sink_types
+    }
+
+    /// Returns whether any of the given nodes can be reached from `start` by following
+    /// forward edges, including those of the specialized and unspecialized versions of
+    /// each node in whole-program graphs. `ignore_path_fn` receives each path along with
+    /// the kinds of the paths taken to get to it, and returns true for paths that
+    /// shouldn't be followed.
+    pub fn reaches_any(
+        &self,
+        start: &DataFlowNodeId,
+        targets: &FxHashSet<DataFlowNodeId>,
+        ignore_path_fn: impl Fn(&DataFlowPath, &[PathKind]) -> bool,
+    ) -> bool {
+        let mut visited = FxHashSet::default();
+        let mut traversal_counter = TraversalCounter::new(&self.traversal_counts);
+        let mut current_nodes = FxHashMap::default();
+        current_nodes.insert(start.clone(), vec![]);
+
+        let mut depth = 0;
+
+        while !current_nodes.is_empty() && depth < MAX_TRAVERSAL_DEPTH {
+            let mut next_nodes = FxHashMap::default();
+
+            for (node_id, path_types) in current_nodes {
+                visited.insert(node_id.clone());
+
+                for from_id in self.get_specialization_variants(node_id) {
+                    let Some(forward_edges) = self.forward_edges.get(&from_id) else {
+                        continue;
+                    };
+
+                    for (to_id, path) in forward_edges {
+                        if targets.contains(to_id) {
+                            return true;
+                        }
+
+                        if visited.contains(to_id) || ignore_path_fn(path, &path_types) {
+                            continue;
+                        }
+
+                        traversal_counter.record(&path.kind);
+
+                        let mut new_path_types = path_types.clone();
+                        new_path_types.push(path.kind.clone());
+
+                        next_nodes.insert(to_id.clone(), new_path_types);
+                    }
+                }
+            }
+
+            current_nodes = next_nodes;
+            depth += 1;
+        }
+
+        false
+    }
+
+    /// Returns the node along with the ids its edges may be recorded under in a
+    /// whole-program graph: the unspecialized id of a specialized node, and the
+    /// specialized ids of an unspecialized one
+    fn get_specialization_variants(&self, node_id: DataFlowNodeId) -> Vec<DataFlowNodeId> {
+        if !matches!(self.kind, GraphKind::WholeProgram(_)) {
+            return vec![node_id];
+        }
+
+        let mut variants = vec![];
+
+        if node_id.is_specialized() {
+            variants.push(node_id.unspecialize().0);
+        } else if let Some(specializations) = self.specializations.get(&node_id) {
+            variants.extend(
+                specializations
+                    .iter()
+                    .map(|(file_path, offset)| node_id.specialize(*file_path, *offset)),
+            );
+        }
+
+        variants.push(node_id);
+        variants
+    }
+
+    /// Returns a histogram mapping path length (in edges) to the number of
+    /// source-sink pairs whose shortest path has that length. Sinks further than
+    /// `max_depth` edges from a source aren't counted.
+    pub fn get_source_to_sink_path_lengths(&self, max_depth: usize) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+
+        for source_id in self.sources.keys() {
+            let mut visited = FxHashSet::default();
+            let mut queue = VecDeque::from([(source_id.clone(), 0)]);
+
+            while let Some((node_id, depth)) = queue.pop_front() {
+                if !visited.insert(node_id.clone()) {
+                    continue;
+                }
+
+                if depth > 0 && self.sinks.contains_key(&node_id) {
+                    *histogram.entry(depth).or_insert(0) += 1;
+                }
+
+                if depth == max_depth {
+                    continue;
+                }
+
+                let mut next_ids = vec![node_id.clone()];
+
+                if let Some(specializations) = self.specializations.get(&node_id) {
+                    next_ids.extend(
+                        specializations
+                            .iter()
+                            .map(|(file_path, offset)| node_id.specialize(*file_path, *offset)),
+                    );
+                }
+
+                for next_id in next_ids {
+                    if let Some(forward_edges) = self.forward_edges.get(&next_id) {
+                        for (to_id, path) in forward_edges {
+                            if let PathKind::Aggregate = path.kind {
+                                continue;
+                            }
+
+                            queue.push_back((to_id.clone(), depth + 1));
+                        }
+                    }
+                }
+            }
+        }
+
+        histogram
+    }
+
+    /// Returns the sinks that can't be reached from any source, e.g. because a sink
+    /// is misconfigured or the code that feeds it is genuinely safe
+    pub fn find_dead_sinks(&self) -> Vec<&DataFlowNode> {
+        let mut visited = FxHashSet::default();
+        let mut queue = self.sources.keys().cloned().collect::<Vec<_>>();
+
+        while let Some(node_id) = queue.pop() {
+            if !visited.insert(node_id.clone()) {
+                continue;
+            }
+
+            let mut next_ids = vec![node_id.clone()];
+
+            if let Some(specializations) = self.specializations.get(&node_id) {
+                next_ids.extend(
+                    specializations
+                        .iter()
+                        .map(|(file_path, offset)| node_id.specialize(*file_path, *offset)),
+                );
+            }
+
+            for next_id in next_ids {
+                if let Some(forward_edges) = self.forward_edges.get(&next_id) {
+                    for to_id in forward_edges.keys() {
+                        if !visited.contains(to_id) {
+                            queue.push(to_id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        self.sinks
+            .iter()
+            .filter(|(sink_id, _)| !visited.contains(*sink_id))
+            .map(|(_, sink)| sink)
+            .collect()
+    }
+
+    /// Removes vertices with no incoming or outgoing edges, which can't be visited by
+    /// any traversal and just take up memory once many function graphs have been
+    /// merged. Sources and sinks are always kept. Returns how many were removed.
+    pub fn prune_orphans(&mut self) -> usize {
+        let mut connected_ids = FxHashSet::default();
+
+        for (from_id, edges) in &self.forward_edges {
+            if !edges.is_empty() {
+                connected_ids.insert(from_id);
+                connected_ids.extend(edges.keys());
+            }
+        }
+
+        let mut pruned_ids = vec![];
+
+        self.vertices.retain(|vertex_id, _| {
+            if connected_ids.contains(vertex_id) {
+                true
+            } else {
+                pruned_ids.push(vertex_id.clone());
+                false
+            }
+        });
+
+        for pruned_id in &pruned_ids {
+            self.tags.remove(pruned_id);
+        }
+
+        pruned_ids.len()
+    }
+
+    /// Returns every pair of taint source and sink type such that tainted data from the
+    /// source can reach a sink of that type which it isn't allowed to reach
+    pub fn get_taint_flows(&self) -> FxHashSet<(DataFlowNodeId, SinkType)> {
+        let mut taint_flows = FxHashSet::default();
+
+        for (source_id, source) in &self.sources {
+            let DataFlowNodeKind::TaintSource { types, .. } = &source.kind else {
+                continue;
+            };
+
+            let reachable_sink_types = self.reachable_sink_types(source_id);
+
+            for source_type in types {
+                for sink_type in get_sinks_for_sources(source_type) {
+                    if reachable_sink_types.contains(&sink_type) {
+                        taint_flows.insert((source_id.clone(), sink_type));
+                    }
+                }
+            }
+        }
+
+        taint_flows
+    }
+
+    /// Compares the taint flows in this graph against those in a graph from an earlier
+    /// run, e.g. to check that a change didn't open up any new paths to a sink
+    pub fn diff_taint_flows(&self, previous: &DataFlowGraph) -> TaintDiff {
+        let current_flows = self.get_taint_flows();
+        let previous_flows = previous.get_taint_flows();
+
+        let sorted = |flows: Vec<&(DataFlowNodeId, SinkType)>| {
+            let mut flows = flows.into_iter().cloned().collect::<Vec<_>>();
+            flows.sort_by(|a, b| (&a.0, a.1.to_string()).cmp(&(&b.0, b.1.to_string())));
+            flows
+        };
+
+        TaintDiff {
+            added: sorted(current_flows.difference(&previous_flows).collect()),
+            removed: sorted(previous_flows.difference(&current_flows).collect()),
+        }
+    }
+
+    /// Returns the call sites at which the given function's arguments, return value
+    /// or `$this` were specialized, i.e. the places whose taint results depend on
+    /// that function's body
+    pub fn get_specialized_call_sites(
+        &self,
+        functionlike_id: &FunctionLikeIdentifier,
+    ) -> FxHashSet<(FilePath, u32)> {
+        let mut call_sites = FxHashSet::default();
+
+        for (node_id, specializations) in &self.specializations {
+            let node_functionlike_id = match node_id {
+                DataFlowNodeId::CallTo(id)
+                | DataFlowNodeId::FunctionLikeArg(id, _)
+                | DataFlowNodeId::FunctionLikeOut(id, _) => *id,
+                DataFlowNodeId::ThisBeforeMethod(method_id)
+                | DataFlowNodeId::ThisAfterMethod(method_id) => {
+                    FunctionLikeIdentifier::Method(method_id.0, method_id.1)
+                }
+                _ => continue,
+            };
+
+            if &node_functionlike_id == functionlike_id {
+                call_sites.extend(specializations.iter().copied());
+            }
+        }
+
+        call_sites
+    }
+
+    /// Returns a taint summary for a function, mapping each of the given parameter
+    /// nodes (either `Param` or `FunctionLikeArg` ids) to the sink types it can reach
+    pub fn get_param_sink_types(
+        &self,
+        param_ids: &[DataFlowNodeId],
+    ) -> FxHashMap<DataFlowNodeId, FxHashSet<SinkType>> {
+        param_ids
+            .iter()
+            .map(|param_id| (param_id.clone(), self.reachable_sink_types(param_id)))
+            .collect()
+    }
+
     pub fn is_from_param(&self, stmt_var_type: &TUnion) -> bool {
         let mut origin_node_ids = vec![];
         for parent_node in &stmt_var_type.parent_nodes {
@@ -331,3 +943,536 @@ impl DataFlowGraph {
         has_param_source
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_location::HPos;
+    use crate::taint::SourceType;
+    use hakana_str::Interner;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn id(name: &str) -> DataFlowNodeId {
+        DataFlowNodeId::String(name.to_string())
+    }
+
+    fn pos() -> HPos {
+        HPos {
+            file_path: FilePath(StrId::EMPTY),
+            start_offset: 0,
+            end_offset: 0,
+            start_line: 1,
+            end_line: 1,
+            start_column: 1,
+            end_column: 1,
+        }
+    }
+
+    fn vertex(name: &str) -> DataFlowNode {
+        DataFlowNode {
+            id: id(name),
+            kind: DataFlowNodeKind::Vertex {
+                pos: None,
+                is_specialized: false,
+            },
+        }
+    }
+
+    fn source(name: &str, types: Vec<SourceType>) -> DataFlowNode {
+        DataFlowNode {
+            id: id(name),
+            kind: DataFlowNodeKind::TaintSource { pos: None, types },
+        }
+    }
+
+    fn sink(name: &str, types: Vec<SinkType>) -> DataFlowNode {
+        DataFlowNode {
+            id: id(name),
+            kind: DataFlowNodeKind::TaintSink { pos: pos(), types },
+        }
+    }
+
+    /// `get` flows through `a` into an HTML sink, and `orphan` isn't connected to anything
+    fn get_taint_graph() -> DataFlowGraph {
+        let mut graph = DataFlowGraph::new(GraphKind::WholeProgram(WholeProgramKind::Taint));
+
+        graph.add_node(source("get", vec![SourceType::UriRequestHeader]));
+        graph.add_node(vertex("a"));
+        graph.add_node(vertex("orphan"));
+        graph.add_node(sink("echo", vec![SinkType::HtmlTag]));
+
+        graph.add_path(&id("get"), &id("a"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&id("a"), &id("echo"), PathKind::Default, vec![], vec![]);
+
+        graph
+    }
+
+    #[test]
+    fn pruning_orphans_keeps_sinks_reachable() {
+        let mut graph = get_taint_graph();
+        let taint_flows = graph.get_taint_flows();
+
+        assert_eq!(graph.prune_orphans(), 1);
+
+        assert!(graph.vertices.contains_key(&id("a")));
+        assert!(!graph.vertices.contains_key(&id("orphan")));
+        assert_eq!(
+            graph.reachable_sink_types(&id("get")),
+            FxHashSet::from_iter([SinkType::HtmlTag])
+        );
+        assert_eq!(graph.get_taint_flows(), taint_flows);
+        assert!(graph.find_dead_sinks().is_empty());
+    }
+
+    fn get_reachable_ids(
+        from_id: &DataFlowNodeId,
+        get_next_ids: impl Fn(&DataFlowNodeId) -> Vec<DataFlowNodeId>,
+    ) -> FxHashSet<DataFlowNodeId> {
+        let mut visited = FxHashSet::default();
+        let mut queue = vec![from_id.clone()];
+
+        while let Some(node_id) = queue.pop() {
+            for next_id in get_next_ids(&node_id) {
+                if visited.insert(next_id.clone()) {
+                    queue.push(next_id);
+                }
+            }
+        }
+
+        visited
+    }
+
+    #[test]
+    fn inverted_edges_match_forward_reachability() {
+        let mut graph = get_taint_graph();
+        graph.add_node(vertex("b"));
+        graph.add_path(&id("get"), &id("b"), PathKind::Default, vec![], vec![]);
+
+        assert!(graph.backward_edges.is_empty());
+
+        graph.invert();
+
+        // edges added after inverting are indexed too
+        graph.add_path(&id("b"), &id("echo"), PathKind::Default, vec![], vec![]);
+
+        let node_ids = ["get", "a", "b", "orphan", "echo"].map(id);
+
+        for from_id in &node_ids {
+            let forward_ids = get_reachable_ids(from_id, |node_id| {
+                graph
+                    .forward_edges
+                    .get(node_id)
+                    .map(|edges| edges.keys().cloned().collect())
+                    .unwrap_or_default()
+            });
+
+            for to_id in &node_ids {
+                let backward_ids = get_reachable_ids(to_id, |node_id| {
+                    graph
+                        .backward_edges
+                        .get(node_id)
+                        .map(|edges| edges.iter().cloned().collect())
+                        .unwrap_or_default()
+                });
+
+                assert_eq!(
+                    forward_ids.contains(to_id),
+                    backward_ids.contains(from_id),
+                    "{:?} -> {:?}",
+                    from_id,
+                    to_id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn taint_diff_reports_a_new_path_to_a_sink() {
+        let previous = get_taint_graph();
+        let mut current = get_taint_graph();
+
+        current.add_node(sink("query", vec![SinkType::Sql]));
+        current.add_path(&id("a"), &id("query"), PathKind::Default, vec![], vec![]);
+
+        assert!(current.diff_taint_flows(&current.clone()).is_empty());
+        assert_eq!(
+            current.diff_taint_flows(&previous),
+            TaintDiff {
+                added: vec![(id("get"), SinkType::Sql)],
+                removed: vec![],
+            }
+        );
+        assert_eq!(
+            previous.diff_taint_flows(&current),
+            TaintDiff {
+                added: vec![],
+                removed: vec![(id("get"), SinkType::Sql)],
+            }
+        );
+    }
+
+    #[test]
+    fn tags_survive_merges_and_are_pruned_with_their_nodes() {
+        let mut graph = get_taint_graph();
+        graph.add_tag(&id("get"), "user-controlled");
+        graph.add_tag(&id("orphan"), "user-controlled");
+
+        let mut other_graph = DataFlowGraph::new(GraphKind::WholeProgram(WholeProgramKind::Taint));
+        other_graph.add_node(vertex("b"));
+        other_graph.add_path(&id("b"), &id("echo"), PathKind::Default, vec![], vec![]);
+        other_graph.add_tag(&id("b"), "user-controlled");
+        other_graph.add_tag(&id("b"), "escaped");
+
+        graph.add_graph(other_graph);
+
+        assert_eq!(
+            graph.nodes_with_tag("user-controlled"),
+            vec![&id("b"), &id("get"), &id("orphan")]
+        );
+        assert_eq!(graph.nodes_with_tag("escaped"), vec![&id("b")]);
+
+        graph.prune_orphans();
+
+        assert_eq!(
+            graph.nodes_with_tag("user-controlled"),
+            vec![&id("b"), &id("get")]
+        );
+    }
+
+    #[test]
+    fn reaches_any_follows_specialized_calls() {
+        let mut interner = Interner::default();
+        let echo_arg = DataFlowNodeId::FunctionLikeArg(
+            FunctionLikeIdentifier::Function(interner.intern("echo_it".to_string())),
+            0,
+        );
+        let call_arg = echo_arg.specialize(FilePath(StrId::EMPTY), 10);
+
+        let mut graph = get_taint_graph();
+        graph.add_node(DataFlowNode {
+            id: call_arg.clone(),
+            kind: DataFlowNodeKind::Vertex {
+                pos: None,
+                is_specialized: true,
+            },
+        });
+        graph.add_node(sink("query", vec![SinkType::Sql]));
+
+        // the call site's argument flows into the function body's unspecialized argument
+        graph.add_path(&id("get"), &call_arg, PathKind::Default, vec![], vec![]);
+        graph.add_path(&echo_arg, &id("query"), PathKind::Default, vec![], vec![]);
+
+        let targets = FxHashSet::from_iter([id("query")]);
+
+        assert!(graph.reaches_any(&id("get"), &targets, |_, _| false));
+        assert!(!graph.reaches_any(&id("a"), &targets, |_, _| false));
+        assert!(!graph.reaches_any(&id("get"), &targets, |path, _| {
+            path.kind == PathKind::Default
+        }));
+    }
+
+    fn get_reachable_sink_types_by_function(
+        graph: &DataFlowGraph,
+    ) -> FxHashMap<DataFlowNodeId, FxHashSet<SinkType>> {
+        let mut sink_types = FxHashMap::<_, FxHashSet<_>>::default();
+
+        for source_id in graph.sources.keys() {
+            let function_id = if source_id.is_specialized() {
+                source_id.unspecialize().0
+            } else {
+                source_id.clone()
+            };
+
+            sink_types
+                .entry(function_id)
+                .or_default()
+                .extend(graph.reachable_sink_types(source_id));
+        }
+
+        sink_types
+    }
+
+    fn add_call_source(
+        graph: &mut DataFlowGraph,
+        function_id: &DataFlowNodeId,
+        offset: u32,
+        source_type: SourceType,
+    ) -> DataFlowNodeId {
+        let call_id = function_id.specialize(FilePath(StrId::EMPTY), offset);
+        graph.add_node(DataFlowNode {
+            id: call_id.clone(),
+            kind: DataFlowNodeKind::TaintSource {
+                pos: None,
+                types: vec![source_type],
+            },
+        });
+        call_id
+    }
+
+    #[test]
+    fn merged_sources_reach_the_same_sinks() {
+        let mut interner = Interner::default();
+        let mut call_to = |name: &str| {
+            DataFlowNodeId::CallTo(FunctionLikeIdentifier::Function(
+                interner.intern(name.to_string()),
+            ))
+        };
+        let get_header = call_to("get_header");
+        let get_secret = call_to("get_secret");
+
+        let mut graph = get_taint_graph();
+        graph.add_node(sink("query", vec![SinkType::Sql]));
+
+        // one get_header call flows into the HTML sink, the other into the query
+        let header = add_call_source(&mut graph, &get_header, 10, SourceType::UriRequestHeader);
+        graph.add_path(&header, &id("a"), PathKind::Default, vec![], vec![]);
+        let header = add_call_source(&mut graph, &get_header, 20, SourceType::UriRequestHeader);
+        graph.add_path(&header, &id("query"), PathKind::Default, vec![], vec![]);
+
+        // get_secret calls have different source types, so they're left alone
+        let secret = add_call_source(&mut graph, &get_secret, 30, SourceType::SystemSecret);
+        graph.add_path(&secret, &id("query"), PathKind::Default, vec![], vec![]);
+        let user_pii = add_call_source(&mut graph, &get_secret, 40, SourceType::UserPII);
+
+        let sink_types = get_reachable_sink_types_by_function(&graph);
+
+        graph.merge_sources();
+
+        assert_eq!(
+            graph.sources.keys().cloned().collect::<FxHashSet<_>>(),
+            FxHashSet::from_iter([id("get"), get_header, secret, user_pii])
+        );
+        assert_eq!(get_reachable_sink_types_by_function(&graph), sink_types);
+    }
+
+    /// The ancestors of `node_id` (including itself) that have no parents of their own
+    fn get_roots_by_search(
+        graph: &DataFlowGraph,
+        node_id: &DataFlowNodeId,
+    ) -> FxHashSet<DataFlowNodeId> {
+        let mut roots = FxHashSet::default();
+        let mut visited = FxHashSet::from_iter([node_id.clone()]);
+        let mut queue = vec![node_id.clone()];
+
+        while let Some(node_id) = queue.pop() {
+            match graph.backward_edges.get(&node_id) {
+                Some(parent_ids) if !parent_ids.is_empty() => {
+                    for parent_id in parent_ids {
+                        if visited.insert(parent_id.clone()) {
+                            queue.push(parent_id.clone());
+                        }
+                    }
+                }
+                _ => {
+                    roots.insert(node_id);
+                }
+            }
+        }
+
+        roots
+    }
+
+    #[test]
+    fn origin_node_ids_are_found_beyond_fifty_levels() {
+        let mut graph = DataFlowGraph::new(GraphKind::FunctionBody);
+
+        for i in 0..200 {
+            graph.add_node(vertex(&i.to_string()));
+
+            if i > 0 {
+                graph.add_path(
+                    &id(&(i - 1).to_string()),
+                    &id(&i.to_string()),
+                    PathKind::Default,
+                    vec![],
+                    vec![],
+                );
+            }
+        }
+
+        assert_eq!(
+            graph.get_origin_node_ids(&id("199"), &[], false),
+            vec![id("0")]
+        );
+    }
+
+    #[test]
+    fn origin_node_ids_are_the_roots_of_random_graphs() {
+        let mut rng = StdRng::seed_from_u64(609);
+
+        for i in 0..500 {
+            // even iterations only add edges to later nodes, so the graph is a DAG
+            let is_dag = i % 2 == 0;
+            let node_count = rng.gen_range(1..80);
+            let edge_count = rng.gen_range(0..node_count * 2);
+
+            let mut graph = DataFlowGraph::new(GraphKind::FunctionBody);
+
+            for node in 0..node_count {
+                graph.add_node(vertex(&node.to_string()));
+            }
+
+            for _ in 0..edge_count {
+                let mut from = rng.gen_range(0..node_count);
+                let mut to = rng.gen_range(0..node_count);
+
+                if is_dag && from > to {
+                    std::mem::swap(&mut from, &mut to);
+                }
+
+                graph.add_path(
+                    &id(&from.to_string()),
+                    &id(&to.to_string()),
+                    PathKind::Default,
+                    vec![],
+                    vec![],
+                );
+            }
+
+            let node_id = id(&rng.gen_range(0..node_count).to_string());
+            let origin_node_ids = graph.get_origin_node_ids(&node_id, &[], false);
+            let unique_origin_node_ids = origin_node_ids.iter().cloned().collect::<FxHashSet<_>>();
+
+            assert_eq!(unique_origin_node_ids.len(), origin_node_ids.len());
+            assert_eq!(
+                unique_origin_node_ids,
+                get_roots_by_search(&graph, &node_id)
+            );
+        }
+    }
+
+    #[test]
+    fn path_length_histogram_counts_shortest_paths() {
+        // `get` reaches `echo` in 2 edges and `log` in 3, `cookie` reaches `echo` in 1
+        let mut graph = get_taint_graph();
+        graph.add_node(source("cookie", vec![SourceType::UserData]));
+        graph.add_node(vertex("b"));
+        graph.add_node(vertex("c"));
+        graph.add_node(sink("log", vec![SinkType::FileSystem]));
+        graph.add_path(
+            &id("cookie"),
+            &id("echo"),
+            PathKind::Default,
+            vec![],
+            vec![],
+        );
+        graph.add_path(&id("get"), &id("b"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&id("b"), &id("c"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&id("c"), &id("log"), PathKind::Default, vec![], vec![]);
+        // a longer way to `echo`, which isn't counted
+        graph.add_path(&id("c"), &id("echo"), PathKind::Default, vec![], vec![]);
+
+        assert_eq!(
+            graph.get_source_to_sink_path_lengths(10),
+            BTreeMap::from([(1, 1), (2, 1), (3, 1)])
+        );
+        assert_eq!(
+            graph.get_source_to_sink_path_lengths(2),
+            BTreeMap::from([(1, 1), (2, 1)])
+        );
+    }
+
+    #[test]
+    fn sinks_no_source_reaches_are_dead() {
+        let mut graph = get_taint_graph();
+
+        // `b` flows into the log, but nothing tainted flows into `b`
+        graph.add_node(vertex("b"));
+        graph.add_node(sink("log", vec![SinkType::FileSystem]));
+        graph.add_path(&id("b"), &id("log"), PathKind::Default, vec![], vec![]);
+
+        let dead_sink_ids = graph
+            .find_dead_sinks()
+            .into_iter()
+            .map(|sink| sink.id.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(dead_sink_ids, vec![id("log")]);
+    }
+
+    #[test]
+    fn only_sources_that_can_taint_the_sink_type_are_listed() {
+        let mut graph = get_taint_graph();
+        graph.add_node(source("email", vec![SourceType::UserPII]));
+
+        let source_ids = |sink_type| {
+            graph
+                .sources_for_sink_type(&sink_type)
+                .into_iter()
+                .map(|source| source.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(source_ids(SinkType::Sql), vec![id("get")]);
+        assert_eq!(source_ids(SinkType::Logging), vec![id("email")]);
+    }
+
+    #[test]
+    fn only_params_that_reach_a_sink_report_its_type() {
+        let mut interner = Interner::default();
+        let query = FunctionLikeIdentifier::Function(interner.intern("query".to_string()));
+        let table_arg = DataFlowNodeId::FunctionLikeArg(query, 0);
+        let where_arg = DataFlowNodeId::FunctionLikeArg(query, 1);
+
+        let mut graph = DataFlowGraph::new(GraphKind::WholeProgram(WholeProgramKind::Taint));
+
+        // `$table` is escaped before it reaches the query, `$where` isn't
+        graph.add_node(vertex("esc"));
+        graph.add_node(vertex("sql"));
+        graph.add_node(sink("exec", vec![SinkType::Sql]));
+
+        graph.add_path(
+            &table_arg,
+            &id("esc"),
+            PathKind::Default,
+            vec![],
+            vec![SinkType::Sql],
+        );
+        graph.add_path(&id("esc"), &id("sql"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&where_arg, &id("sql"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&id("sql"), &id("exec"), PathKind::Default, vec![], vec![]);
+
+        assert_eq!(
+            graph.get_param_sink_types(&[table_arg.clone(), where_arg.clone()]),
+            FxHashMap::from_iter([
+                (table_arg, FxHashSet::default()),
+                (where_arg, FxHashSet::from_iter([SinkType::Sql])),
+            ])
+        );
+    }
+
+    #[test]
+    fn traversal_counts_are_kept_per_graph() {
+        let traversal_counts = TraversalCounts::default();
+
+        let mut graph = DataFlowGraph::new(GraphKind::FunctionBody)
+            .with_traversal_counts(Some(traversal_counts.clone()));
+
+        for name in ["a", "b", "c", "d"] {
+            graph.add_node(vertex(name));
+        }
+
+        graph.add_path(&id("a"), &id("c"), PathKind::Default, vec![], vec![]);
+        graph.add_path(&id("b"), &id("c"), PathKind::Serialize, vec![], vec![]);
+        graph.add_path(&id("c"), &id("d"), PathKind::Default, vec![], vec![]);
+
+        let mut origin_node_ids = graph.get_origin_node_ids(&id("d"), &[], false);
+        origin_node_ids.sort_by_key(|node_id| format!("{:?}", node_id));
+        assert_eq!(origin_node_ids, vec![id("a"), id("b")]);
+
+        assert_eq!(
+            *traversal_counts.lock().unwrap(),
+            BTreeMap::from([
+                ("default".to_string(), 2),
+                (PathKind::Serialize.to_string(), 1)
+            ])
+        );
+
+        // an identical graph that isn't counting doesn't add to another run's counts
+        let uncounted_graph = graph.clone().with_traversal_counts(None);
+        uncounted_graph.get_origin_node_ids(&id("d"), &[], false);
+
+        assert_eq!(traversal_counts.lock().unwrap().values().sum::<u64>(), 3);
+    }
+}
@@ -58,7 +58,10 @@ impl DataFlowNodeId {
         match self {
             DataFlowNodeId::String(str) => str.clone(),
             DataFlowNodeId::LocalString(str, file_path, start_offset, end_offset) => {
-                format!("{}-{}:{}-{}", str, file_path.0 .0, start_offset, end_offset)
+                format!(
+                    "local-{}-{}:{}-{}",
+                    str, file_path.0 .0, start_offset, end_offset
+                )
             }
             DataFlowNodeId::Param(var_id, file_path, start_offset, end_offset) => {
                 format!(
@@ -228,6 +231,224 @@ impl DataFlowNodeId {
         }
     }
 
+    /// Parses the output of `to_string` back into an id, returning `None` if the
+    /// string isn't a well-formed id or references names that aren't interned. An
+    /// interned plain string is read as a `Symbol` rather than a `String`.
+    pub fn from_string(str: &str, interner: &Interner) -> Option<DataFlowNodeId> {
+        if str.is_empty() {
+            return None;
+        }
+
+        if let Some(rest) = str.strip_prefix("call to ") {
+            if let Some((functionlike_id, file_path, start_offset)) = split_offset(rest) {
+                if let Some(functionlike_id) =
+                    FunctionLikeIdentifier::from_string(functionlike_id, interner)
+                {
+                    return Some(DataFlowNodeId::SpecializedCallTo(
+                        functionlike_id,
+                        file_path,
+                        start_offset,
+                    ));
+                }
+            }
+
+            return FunctionLikeIdentifier::from_string(rest, interner).map(DataFlowNodeId::CallTo);
+        }
+
+        if let Some(rest) = str.strip_prefix("out ") {
+            if let Some((head, file_path, start_offset)) = split_offset(rest) {
+                if let Some((functionlike_id, arg)) = split_functionlike_arg(head, interner) {
+                    return Some(DataFlowNodeId::SpecializedFunctionLikeOut(
+                        functionlike_id,
+                        arg,
+                        file_path,
+                        start_offset,
+                    ));
+                }
+            }
+
+            return split_functionlike_arg(rest, interner).map(|(functionlike_id, arg)| {
+                DataFlowNodeId::FunctionLikeOut(functionlike_id, arg)
+            });
+        }
+
+        if let Some(rest) = str.strip_prefix("$this in ") {
+            let (rest, position) = match split_offset(rest) {
+                Some((head, file_path, start_offset)) => (head, Some((file_path, start_offset))),
+                None => (rest, None),
+            };
+
+            let (method_id, is_before) =
+                if let Some(method_id) = split_method(rest, " before ", interner) {
+                    (method_id, true)
+                } else {
+                    (split_method(rest, " after ", interner)?, false)
+                };
+
+            return Some(match (is_before, position) {
+                (true, None) => DataFlowNodeId::ThisBeforeMethod(method_id),
+                (true, Some((file_path, start_offset))) => {
+                    DataFlowNodeId::SpecializedThisBeforeMethod(method_id, file_path, start_offset)
+                }
+                (false, None) => DataFlowNodeId::ThisAfterMethod(method_id),
+                (false, Some((file_path, start_offset))) => {
+                    DataFlowNodeId::SpecializedThisAfterMethod(method_id, file_path, start_offset)
+                }
+            });
+        }
+
+        if let Some(rest) = str.strip_prefix("fnref-") {
+            return FunctionLikeIdentifier::from_string(rest, interner)
+                .map(DataFlowNodeId::ReferenceTo);
+        }
+
+        if let Some(rest) = str.strip_prefix("for-init-") {
+            let (start_offset, end_offset) = rest.split_once('-')?;
+
+            return Some(DataFlowNodeId::ForInit(
+                start_offset.parse().ok()?,
+                end_offset.parse().ok()?,
+            ));
+        }
+
+        if let Some((head, file_path, start_offset, end_offset)) = split_range(str) {
+            match head {
+                "array-assignment" => {
+                    return Some(DataFlowNodeId::ArrayAssignment(
+                        file_path,
+                        start_offset,
+                        end_offset,
+                    ))
+                }
+                "return" => {
+                    return Some(DataFlowNodeId::Return(file_path, start_offset, end_offset))
+                }
+                "composition" => {
+                    return Some(DataFlowNodeId::Composition(
+                        file_path,
+                        start_offset,
+                        end_offset,
+                    ))
+                }
+                "unlabelled-sink" => {
+                    return Some(DataFlowNodeId::UnlabelledSink(
+                        file_path,
+                        start_offset,
+                        end_offset,
+                    ))
+                }
+                "instance-method-call" => {
+                    return Some(DataFlowNodeId::InstanceMethodCall(
+                        file_path,
+                        start_offset,
+                        end_offset,
+                    ))
+                }
+                _ => {}
+            }
+
+            if let Some(str) = head.strip_prefix("local-") {
+                return Some(DataFlowNodeId::LocalString(
+                    str.to_string(),
+                    file_path,
+                    start_offset,
+                    end_offset,
+                ));
+            }
+
+            if let Some(var_name) = head.strip_prefix("param-") {
+                return Some(DataFlowNodeId::Param(
+                    VarId(interner.get(var_name)?),
+                    file_path,
+                    start_offset,
+                    end_offset,
+                ));
+            }
+
+            if let Some(key_value) = head
+                .strip_prefix("array[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                return Some(DataFlowNodeId::ArrayItem(
+                    key_value.to_string(),
+                    file_path,
+                    start_offset,
+                    end_offset,
+                ));
+            }
+
+            if let Some((classlike_name, property_name)) = split_property(head, interner) {
+                return Some(DataFlowNodeId::SpecializedProperty(
+                    classlike_name,
+                    property_name,
+                    file_path,
+                    start_offset,
+                    end_offset,
+                ));
+            }
+
+            if head.starts_with('$') {
+                return Some(DataFlowNodeId::Var(
+                    VarId(interner.get(head)?),
+                    file_path,
+                    start_offset,
+                    end_offset,
+                ));
+            }
+
+            return None;
+        }
+
+        if let Some((head, file_path, start_offset)) = split_offset(str) {
+            if let Some((var_id, symbol)) = head.split_once(" narrowed to ") {
+                return Some(DataFlowNodeId::VarNarrowedTo(
+                    var_id.to_string(),
+                    interner.get(symbol)?,
+                    file_path,
+                    start_offset,
+                ));
+            }
+
+            if let Some((lhs_var_id, property_name)) = head.split_once("->") {
+                return Some(DataFlowNodeId::PropertyFetch(
+                    VarId(interner.get(lhs_var_id)?),
+                    interner.get(property_name)?,
+                    file_path,
+                    start_offset,
+                ));
+            }
+
+            let (functionlike_id, arg) = split_functionlike_arg(head, interner)?;
+
+            return Some(DataFlowNodeId::SpecializedFunctionLikeArg(
+                functionlike_id,
+                arg,
+                file_path,
+                start_offset,
+            ));
+        }
+
+        if let Some((classlike_name, property_name)) = split_property(str, interner) {
+            return Some(DataFlowNodeId::Property(classlike_name, property_name));
+        }
+
+        if let Some((functionlike_id, arg)) = split_functionlike_arg(str, interner) {
+            return Some(DataFlowNodeId::FunctionLikeArg(functionlike_id, arg));
+        }
+
+        if let Some((type_name, key)) = str.strip_suffix(']').and_then(|v| v.split_once('[')) {
+            if let Some(type_name) = interner.get(type_name) {
+                return Some(DataFlowNodeId::ShapeFieldAccess(type_name, key.to_string()));
+            }
+        }
+
+        Some(if let Some(symbol) = interner.get(str) {
+            DataFlowNodeId::Symbol(symbol)
+        } else {
+            DataFlowNodeId::String(str.to_string())
+        })
+    }
+
     pub fn to_label(&self, interner: &Interner) -> String {
         match self {
             DataFlowNodeId::String(str) | DataFlowNodeId::LocalString(str, ..) => str.clone(),
@@ -332,6 +553,17 @@ impl DataFlowNodeId {
         }
     }
 
+    pub fn is_specialized(&self) -> bool {
+        matches!(
+            self,
+            DataFlowNodeId::SpecializedCallTo(..)
+                | DataFlowNodeId::SpecializedFunctionLikeArg(..)
+                | DataFlowNodeId::SpecializedFunctionLikeOut(..)
+                | DataFlowNodeId::SpecializedThisBeforeMethod(..)
+                | DataFlowNodeId::SpecializedThisAfterMethod(..)
+        )
+    }
+
     pub fn unspecialize(&self) -> (DataFlowNodeId, (FilePath, u32)) {
         match self {
             DataFlowNodeId::SpecializedCallTo(id, file_path, offset) => {
@@ -364,6 +596,54 @@ impl DataFlowNodeId {
     }
 }
 
+// splits a `{head}-{file_path}:{start_offset}` string
+fn split_offset(str: &str) -> Option<(&str, FilePath, u32)> {
+    let (rest, start_offset) = str.rsplit_once(':')?;
+    let (head, file_path) = rest.rsplit_once('-')?;
+
+    Some((
+        head,
+        FilePath(StrId(file_path.parse().ok()?)),
+        start_offset.parse().ok()?,
+    ))
+}
+
+// splits a `{head}-{file_path}:{start_offset}-{end_offset}` string
+fn split_range(str: &str) -> Option<(&str, FilePath, u32, u32)> {
+    let (rest, end_offset) = str.rsplit_once('-')?;
+    let (head, file_path, start_offset) = split_offset(rest)?;
+
+    Some((head, file_path, start_offset, end_offset.parse().ok()?))
+}
+
+// splits a `{functionlike_id}#{arg + 1}` string
+fn split_functionlike_arg(str: &str, interner: &Interner) -> Option<(FunctionLikeIdentifier, u8)> {
+    let (functionlike_id, arg) = str.rsplit_once('#')?;
+    let arg = arg.parse::<u8>().ok()?.checked_sub(1)?;
+
+    Some((
+        FunctionLikeIdentifier::from_string(functionlike_id, interner)?,
+        arg,
+    ))
+}
+
+// splits a `{classlike_name}{separator}{method_name}` string
+fn split_method(str: &str, separator: &str, interner: &Interner) -> Option<MethodIdentifier> {
+    let (classlike_name, method_name) = str.split_once(separator)?;
+
+    Some(MethodIdentifier(
+        interner.get(classlike_name)?,
+        interner.get(method_name)?,
+    ))
+}
+
+// splits a `{classlike_name}::${property_name}` string
+fn split_property(str: &str, interner: &Interner) -> Option<(StrId, StrId)> {
+    let (classlike_name, property_name) = str.split_once("::$")?;
+
+    Some((interner.get(classlike_name)?, interner.get(property_name)?))
+}
+
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct DataFlowNode {
     pub id: DataFlowNodeId,
@@ -839,3 +1119,87 @@ impl DataFlowNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(id: DataFlowNodeId, interner: &Interner) {
+        assert_eq!(
+            DataFlowNodeId::from_string(&id.to_string(interner), interner),
+            Some(id)
+        );
+    }
+
+    #[test]
+    fn from_string_round_trips_every_variant() {
+        let mut interner = Interner::default();
+        let var_id = VarId(interner.intern("$x".to_string()));
+        let classlike_name = interner.intern("Foo".to_string());
+        let property_name = interner.intern("bar".to_string());
+        let method_name = interner.intern("baz".to_string());
+        let file_path = FilePath(interner.intern("input.hack".to_string()));
+
+        let function_id = FunctionLikeIdentifier::Function(method_name);
+        let method_id = FunctionLikeIdentifier::Method(classlike_name, method_name);
+        let closure_id = FunctionLikeIdentifier::Closure(file_path, 12);
+        let this_method_id = MethodIdentifier(classlike_name, method_name);
+
+        for id in [
+            DataFlowNodeId::String("some string".to_string()),
+            DataFlowNodeId::LocalString("$x".to_string(), file_path, 1, 3),
+            DataFlowNodeId::LocalString("$unknown".to_string(), file_path, 1, 3),
+            DataFlowNodeId::ArrayAssignment(file_path, 1, 3),
+            DataFlowNodeId::ArrayItem("a-b".to_string(), file_path, 1, 3),
+            DataFlowNodeId::Return(file_path, 1, 3),
+            DataFlowNodeId::ForInit(1, 3),
+            DataFlowNodeId::Composition(file_path, 1, 3),
+            DataFlowNodeId::Var(var_id, file_path, 1, 3),
+            DataFlowNodeId::VarNarrowedTo("$x".to_string(), classlike_name, file_path, 1),
+            DataFlowNodeId::Param(var_id, file_path, 1, 3),
+            DataFlowNodeId::UnlabelledSink(file_path, 1, 3),
+            DataFlowNodeId::ReferenceTo(function_id),
+            DataFlowNodeId::CallTo(method_id),
+            DataFlowNodeId::CallTo(closure_id),
+            DataFlowNodeId::SpecializedCallTo(closure_id, file_path, 1),
+            DataFlowNodeId::FunctionLikeArg(method_id, 0),
+            DataFlowNodeId::SpecializedFunctionLikeArg(closure_id, 1, file_path, 1),
+            DataFlowNodeId::Property(classlike_name, property_name),
+            DataFlowNodeId::SpecializedProperty(classlike_name, property_name, file_path, 1, 3),
+            DataFlowNodeId::PropertyFetch(var_id, property_name, file_path, 1),
+            DataFlowNodeId::FunctionLikeOut(function_id, 2),
+            DataFlowNodeId::SpecializedFunctionLikeOut(method_id, 2, file_path, 1),
+            DataFlowNodeId::ThisBeforeMethod(this_method_id),
+            DataFlowNodeId::SpecializedThisBeforeMethod(this_method_id, file_path, 1),
+            DataFlowNodeId::ThisAfterMethod(this_method_id),
+            DataFlowNodeId::SpecializedThisAfterMethod(this_method_id, file_path, 1),
+            DataFlowNodeId::Symbol(classlike_name),
+            DataFlowNodeId::ShapeFieldAccess(classlike_name, "key".to_string()),
+            DataFlowNodeId::InstanceMethodCall(file_path, 1, 3),
+        ] {
+            assert_round_trips(id, &interner);
+        }
+    }
+
+    #[test]
+    fn from_string_rejects_malformed_ids() {
+        let mut interner = Interner::default();
+        interner.intern("Foo".to_string());
+
+        for str in [
+            "",
+            "call to Unknown::method",
+            "out Foo#0",
+            "$this in Foo before unknown",
+            "fnref-unknown",
+            "for-init-1-x",
+            "param-$unknown-1:1-3",
+            "$unknown-1:1-3",
+            "Foo-1:1-3",
+            "$x narrowed to Unknown-1:1",
+            "Foo#0-1:1",
+        ] {
+            assert_eq!(DataFlowNodeId::from_string(str, &interner), None, "{}", str);
+        }
+    }
+}
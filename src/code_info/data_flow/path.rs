@@ -26,6 +26,8 @@ pub enum PathKind {
     RefineSymbol(StrId),
     ScalarTypeGuard,
     Aggregate,
+    /// An inout parameter's final value being written back to the caller
+    InoutWriteback,
 }
 
 impl PathKind {
@@ -83,6 +85,7 @@ impl PathKind {
             PathKind::ScalarTypeGuard => "scalar-type-guard".to_string(),
             PathKind::Serialize => "serialize".to_string(),
             PathKind::Aggregate => "aggregate".to_string(),
+            PathKind::InoutWriteback => "inout-writeback".to_string(),
         }
     }
 }
@@ -108,6 +111,7 @@ impl std::fmt::Display for PathKind {
             PathKind::ScalarTypeGuard => write!(f, "scalar-type-guard"),
             PathKind::Serialize => write!(f, "serialize"),
             PathKind::Aggregate => write!(f, "aggregate"),
+            PathKind::InoutWriteback => write!(f, "inout-writeback"),
         }
     }
 }
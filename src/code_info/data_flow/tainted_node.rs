@@ -135,7 +135,7 @@ impl TaintedNode {
             + self
                 .path_types
                 .iter()
-                .filter(|t| !matches!(t, PathKind::Default))
+                .filter(|t| !matches!(t, PathKind::Default | PathKind::InoutWriteback))
                 .map(|k| k.to_unique_string())
                 .collect::<Vec<_>>()
                 .join("-")
@@ -3,10 +3,15 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::code_location::FilePath;
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct CodebaseDiff {
+    /// Symbols whose signature and body are both unchanged
     pub keep: FxHashSet<(StrId, StrId)>,
+    /// Symbols whose body changed but whose signature didn't. Only these symbols
+    /// are re-analyzed; code that references them is left alone.
     pub keep_signature: FxHashSet<(StrId, StrId)>,
+    /// Symbols whose signature changed, or that were added or removed. Anything
+    /// referencing these is re-analyzed too.
     pub add_or_delete: FxHashSet<(StrId, StrId)>,
     pub diff_map: FxHashMap<FilePath, Vec<(u32, u32, isize, isize)>>,
     pub deletion_ranges_map: FxHashMap<FilePath, Vec<(u32, u32)>>,
@@ -21,3 +26,13 @@ impl CodebaseDiff {
         self.deletion_ranges_map.extend(other.deletion_ranges_map);
     }
 }
+
+/// Records which files had their cached issues modified when applying a diff
+#[derive(Clone, Debug, Default)]
+pub struct IssueDiffUpdates {
+    /// Files where at least one cached issue was repositioned
+    pub shifted_files: FxHashSet<FilePath>,
+    /// Files where at least one cached issue was discarded, either because its
+    /// symbol was invalidated or because it fell inside a deleted range
+    pub dropped_files: FxHashSet<FilePath>,
+}
@@ -35,6 +35,28 @@ impl FunctionLikeIdentifier {
         }
     }
 
+    /// Parses the output of `to_string` back into an identifier, returning `None`
+    /// if the referenced names aren't interned
+    pub fn from_string(str: &str, interner: &Interner) -> Option<Self> {
+        if let Some((fq_classlike_name, method_name)) = str.split_once("::") {
+            return Some(FunctionLikeIdentifier::Method(
+                interner.get(fq_classlike_name)?,
+                interner.get(method_name)?,
+            ));
+        }
+
+        if let Some((file_path, offset)) = str.split_once(':') {
+            if let (Ok(file_path), Ok(offset)) = (file_path.parse(), offset.parse()) {
+                return Some(FunctionLikeIdentifier::Closure(
+                    FilePath(StrId(file_path)),
+                    offset,
+                ));
+            }
+        }
+
+        Some(FunctionLikeIdentifier::Function(interner.get(str)?))
+    }
+
     pub fn to_hash(&self) -> String {
         match self {
             FunctionLikeIdentifier::Function(fn_name) => fn_name.0.to_string(),
@@ -86,6 +86,9 @@ pub struct FunctionLikeInfo {
     /// Whether this function is deprecated
     pub deprecated: bool,
 
+    /// The message given to `<<__Deprecated>>`, if any
+    pub deprecation_message: Option<StrId>,
+
     /**
     An array holding the class template "as" types.
 
@@ -196,6 +199,7 @@ impl FunctionLikeInfo {
             user_defined: false,
             suppressed_issues: vec![],
             deprecated: false,
+            deprecation_message: None,
             template_types: vec![],
             has_visitor_issues: false,
             has_yield: false,
@@ -86,6 +86,8 @@ impl FunctionLikeParameter {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct FnParameter {
+    /// For inout params this is also the type written back to the caller, so
+    /// expanding it covers both directions
     pub signature_type: Option<Box<TUnion>>,
     pub is_inout: bool,
     pub is_variadic: bool,
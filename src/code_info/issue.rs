@@ -15,10 +15,13 @@ use crate::{
 #[derive(Clone, PartialEq, Eq, Hash, Display, Debug, Serialize, Deserialize, EnumString)]
 pub enum IssueKind {
     AbstractInstantiation,
+    AnalysisTimeout,
     BannedFunction,
     CannotInferGenericParam,
     CloneInsideLoop,
     CustomIssue(Box<String>),
+    DeadStore,
+    DeprecatedUsage,
     DuplicateEnumValue,
     EmptyBlock,
     ExclusiveEnumValueReused,
@@ -111,12 +114,14 @@ pub enum IssueKind {
     PossiblyUndefinedIntArrayOffset,
     PossiblyUndefinedStringArrayOffset,
     PropertyTypeCoercion,
+    RedundantAwait,
     RedundantIssetCheck,
     RedundantKeyCheck,
     RedundantNonnullEntryCheck,
     RedundantNonnullTypeComparison,
     RedundantTruthinessCheck,
     RedundantTypeComparison,
+    ShadowedClosureUse,
     ShadowedLoopVar,
     StrictObjectEquality,
     TaintedData(Box<SinkType>),
@@ -128,8 +133,11 @@ pub enum IssueKind {
     UndefinedVariable,
     UnevaluatedCode,
     UnnecessaryAsyncAnnotation,
+    UnnecessaryNullCheck,
     UnnecessaryServiceCallsAttribute,
     UnnecessaryShapesIdx,
+    UnreachableCatch,
+    UnreachableLoopBody,
     UnrecognizedBinaryOp,
     UnrecognizedExpression,
     UnrecognizedStatement,
@@ -141,6 +149,8 @@ pub enum IssueKind {
     UnusedBuiltinReturnValue,
     UnusedClass,
     UnusedClosureParameter,
+    UnusedConstant,
+    UnusedElseBranch,
     UnusedFunction,
     UnusedFunctionCall,
     UnusedInheritedMethod,
@@ -155,7 +165,9 @@ pub enum IssueKind {
     UnusedPublicOrProtectedProperty,
     UnusedStatement,
     UnusedTrait,
-    UnusedTypeDefinition,
+    /// Accepts the kind's former name so existing configs and fixmes keep working
+    #[strum(to_string = "UnusedTypeAlias", serialize = "UnusedTypeDefinition")]
+    UnusedTypeAlias,
     UnusedXhpAttribute,
     UpcastAwaitable,
     UselessControlFlow,
@@ -218,7 +230,8 @@ impl IssueKind {
         matches!(
             self,
             Self::UnusedClass
-                | Self::UnusedTypeDefinition
+                | Self::UnusedConstant
+                | Self::UnusedTypeAlias
                 | Self::UnusedFunction
                 | Self::UnusedInterface
                 | Self::UnusedPrivateProperty
@@ -237,6 +250,7 @@ impl IssueKind {
             self,
             Self::UnusedAssignment
                 | Self::UnusedAssignmentStatement
+                | Self::DeadStore
                 | Self::UnusedInoutAssignment
                 | Self::UnusedAssignmentInClosure
                 | Self::UnusedParameter
@@ -248,6 +262,14 @@ impl IssueKind {
     }
 }
 
+/// How an emitted issue is presented. Only errors make a run fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
 #[derive(Clone, Debug, Eq, Serialize, Deserialize)]
 pub struct Issue {
     pub kind: IssueKind,
@@ -298,9 +320,28 @@ impl Issue {
         }
     }
 
+    /// Identifies the issue by its kind, enclosing symbol and description, but not by
+    /// its offsets, so an issue keeps the same fingerprint when whitespace edits move it
+    pub fn get_fingerprint(&self) -> u64 {
+        let mut hasher = rustc_hash::FxHasher::default();
+        self.kind.hash(&mut hasher);
+        self.symbol.hash(&mut hasher);
+        self.description.hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn format(&self, path: &String) -> String {
+        self.format_with_severity(path, "ERROR")
+    }
+
+    pub fn format_as_warning(&self, path: &String) -> String {
+        self.format_with_severity(path, "WARNING")
+    }
+
+    fn format_with_severity(&self, path: &String, severity: &str) -> String {
         format!(
-            "ERROR: {} - {}:{}:{} - {}\n",
+            "{}: {} - {}:{}:{} - {}\n",
+            severity,
             self.kind.to_string(),
             path,
             self.pos.start_line,
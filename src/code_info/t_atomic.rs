@@ -155,6 +155,18 @@ pub struct TClosure {
     pub closure_id: (FilePath, u32),
 }
 
+impl TClosure {
+    /// A closure with a required `nothing` param can never be called, since no
+    /// argument inhabits that type.
+    pub fn is_uncallable(&self) -> bool {
+        self.params.iter().any(|param| {
+            !param.is_optional
+                && !param.is_variadic
+                && matches!(&param.signature_type, Some(t) if t.is_nothing())
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Derivative)]
 #[derivative(Hash)]
 pub struct TVec {
@@ -1,5 +1,8 @@
 use crate::code_location::FilePath;
+use crate::data_flow::graph::{DataFlowGraph, GraphKind};
+use crate::functionlike_identifier::FunctionLikeIdentifier;
 use crate::t_atomic::{TDict, TVec};
+use crate::ttype::type_expander::get_closure_from_id;
 use crate::ttype::{get_arrayish_params, get_value_param, wrap_atomic};
 use crate::{class_constant_info::ConstantInfo, codebase_info::CodebaseInfo, t_atomic::TAtomic};
 use hakana_str::StrId;
@@ -212,6 +215,27 @@ pub fn is_contained_by(
             );
         }
 
+        if let TAtomic::TNamedObject { name, .. } = input_type_part {
+            if codebase.method_exists(name, &StrId::INVOKE) {
+                if let Some(invokable_closure) = get_closure_from_id(
+                    &FunctionLikeIdentifier::Method(*name, StrId::INVOKE),
+                    codebase,
+                    &None,
+                    file_path,
+                    &mut DataFlowGraph::new(GraphKind::FunctionBody),
+                    &mut 0,
+                ) {
+                    return closure_type_comparator::is_contained_by(
+                        codebase,
+                        file_path,
+                        &invokable_closure,
+                        container_type_part,
+                        atomic_comparison_result,
+                    );
+                }
+            }
+        }
+
         return false;
     }
 
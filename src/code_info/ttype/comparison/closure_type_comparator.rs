@@ -1,5 +1,6 @@
 use crate::{
-    code_location::FilePath, codebase_info::CodebaseInfo, t_atomic::TAtomic, ttype::get_mixed_any,
+    code_location::FilePath, codebase_info::CodebaseInfo, functionlike_parameter::FnParameter,
+    t_atomic::TAtomic, ttype::get_mixed_any,
 };
 
 use super::{type_comparison_result::TypeComparisonResult, union_type_comparator};
@@ -33,34 +34,14 @@ pub(crate) fn is_contained_by(
                 }
 
                 if let Some(container_param) = container_param {
-                    if let Some(container_param_type) = &container_param.signature_type {
-                        let mut param_comparison_result = TypeComparisonResult::new();
-
-                        if !container_param_type.is_mixed()
-                            && !union_type_comparator::is_contained_by(
-                                codebase,
-                                file_path,
-                                container_param_type,
-                                &input_param
-                                    .signature_type
-                                    .clone()
-                                    .unwrap_or(Box::new(get_mixed_any())),
-                                false,
-                                false,
-                                false,
-                                &mut param_comparison_result,
-                            )
-                        {
-                            return false;
-                        }
-
-                        atomic_comparison_result
-                            .type_variable_lower_bounds
-                            .extend(param_comparison_result.type_variable_upper_bounds);
-
-                        atomic_comparison_result
-                            .type_variable_upper_bounds
-                            .extend(param_comparison_result.type_variable_lower_bounds);
+                    if !is_param_contained_by(
+                        codebase,
+                        file_path,
+                        input_param,
+                        container_param,
+                        atomic_comparison_result,
+                    ) {
+                        return false;
                     }
                 } else {
                     if input_param.is_optional {
@@ -71,6 +52,29 @@ pub(crate) fn is_contained_by(
                 }
             }
 
+            // any extra arguments the container passes all end up in the variadic param
+            if let Some(input_variadic_param) = input_closure
+                .params
+                .last()
+                .filter(|param| param.is_variadic)
+            {
+                for container_param in container_closure
+                    .params
+                    .iter()
+                    .skip(input_closure.params.len())
+                {
+                    if !is_param_contained_by(
+                        codebase,
+                        file_path,
+                        input_variadic_param,
+                        container_param,
+                        atomic_comparison_result,
+                    ) {
+                        return false;
+                    }
+                }
+            }
+
             if let Some(container_return_type) = &container_closure.return_type {
                 if let Some(input_return_type) = &input_closure.return_type {
                     if input_return_type.is_void() && container_return_type.is_nullable() {
@@ -105,3 +109,43 @@ pub(crate) fn is_contained_by(
 
     false
 }
+
+fn is_param_contained_by(
+    codebase: &CodebaseInfo,
+    file_path: &FilePath,
+    input_param: &FnParameter,
+    container_param: &FnParameter,
+    atomic_comparison_result: &mut TypeComparisonResult,
+) -> bool {
+    if let Some(container_param_type) = &container_param.signature_type {
+        let mut param_comparison_result = TypeComparisonResult::new();
+
+        if !container_param_type.is_mixed()
+            && !union_type_comparator::is_contained_by(
+                codebase,
+                file_path,
+                container_param_type,
+                &input_param
+                    .signature_type
+                    .clone()
+                    .unwrap_or(Box::new(get_mixed_any())),
+                false,
+                false,
+                false,
+                &mut param_comparison_result,
+            )
+        {
+            return false;
+        }
+
+        atomic_comparison_result
+            .type_variable_lower_bounds
+            .extend(param_comparison_result.type_variable_upper_bounds);
+
+        atomic_comparison_result
+            .type_variable_upper_bounds
+            .extend(param_comparison_result.type_variable_lower_bounds);
+    }
+
+    true
+}
@@ -95,7 +95,15 @@ pub fn is_contained_by(
                     continue;
                 }
             }
-            TAtomic::TClassTypeConstant { .. } => continue,
+            TAtomic::TClassTypeConstant { as_type, .. } => {
+                // an abstract type constant is some unknown subtype of its bound, so
+                // the bound has to fit wherever the constant is used
+                if !as_type.is_mixed() {
+                    input_atomic_types.extend(as_type.types.iter().collect::<Vec<_>>());
+                }
+
+                continue;
+            }
             _ => (),
         }
 
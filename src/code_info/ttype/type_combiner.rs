@@ -294,6 +294,9 @@ fn scrape_type_properties(
 
                 combination.mixed_from_loop_isset = Some(false);
 
+                // falsy values include null, so an earlier nonnull refinement can't survive
+                combination.nonnull_mixed = Some(false);
+
                 if combination.truthy_mixed.unwrap_or(false) {
                     combination.vanilla_mixed = true;
                     combination.truthy_mixed = Some(false);
@@ -617,7 +620,9 @@ fn scrape_type_properties(
                 }
             }
 
-            if !has_defined_keys {
+            // a dict explicitly marked as non-empty stays non-empty even when all of
+            // its known keys are optional, otherwise expansion would drop the marker
+            if !has_defined_keys && !non_empty {
                 combination.dict_always_filled = false;
             }
 
@@ -880,7 +885,13 @@ fn scrape_type_properties(
                 enum_name.0.to_string(),
                 TAtomic::TEnum {
                     name: enum_name,
-                    as_type,
+                    // expanded cases carry their own literal value, which doesn't
+                    // describe the enum as a whole
+                    as_type: as_type.map(|as_type| match as_type.as_ref() {
+                        TAtomic::TLiteralString { .. } => Arc::new(TAtomic::TString),
+                        TAtomic::TLiteralInt { .. } => Arc::new(TAtomic::TInt),
+                        _ => as_type,
+                    }),
                     underlying_type,
                 },
             );
@@ -1224,6 +1235,17 @@ fn scrape_type_properties(
         return;
     }
 
+    if let TAtomic::TTypeAlias { as_type: None, .. } = atomic {
+        // an opaque alias from another file may only have had its constraint resolved in
+        // one of the branches, and dropping that constraint would widen the alias to mixed
+        if let Some(TAtomic::TTypeAlias {
+            as_type: Some(_), ..
+        }) = combination.value_types.get(&atomic.get_key())
+        {
+            return;
+        }
+    }
+
     combination.value_types.insert(atomic.get_key(), atomic);
 }
 
@@ -1,11 +1,15 @@
-use std::sync::Arc;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    sync::Arc,
+};
 
 use crate::{
     classlike_info::ClassConstantType,
     code_location::FilePath,
-    codebase_info::CodebaseInfo,
+    codebase_info::{symbols::SymbolKind, CodebaseInfo},
     data_flow::{
-        graph::DataFlowGraph,
+        graph::{DataFlowGraph, GraphKind},
         node::{DataFlowNode, DataFlowNodeId, DataFlowNodeKind},
         path::{ArrayDataKind, PathKind},
     },
@@ -15,22 +19,27 @@ use crate::{
     t_union::TUnion,
     ttype::intersect_union_types_simple,
     type_definition_info::TypeDefinitionInfo,
+    GenericParent, VarId,
 };
 use crate::{functionlike_identifier::FunctionLikeIdentifier, method_identifier::MethodIdentifier};
 use hakana_str::{Interner, StrId};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use crate::ttype::{extend_dataflow_uniquely, get_nothing, template, type_combiner, wrap_atomic};
+use crate::ttype::{
+    combine_union_types, extend_dataflow_uniquely, get_mixed, get_nothing,
+    template::{self, standin_type_replacer::get_most_specific_type_from_bounds, TemplateResult},
+    type_combiner, wrap_atomic,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StaticClassType<'b> {
     None,
     Name(StrId),
     Object(&'b TAtomic),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypeExpansionOptions<'a> {
     pub self_class: Option<StrId>,
     pub static_class_type: StaticClassType<'a>,
@@ -39,12 +48,37 @@ pub struct TypeExpansionOptions<'a> {
     pub evaluate_class_constants: bool,
     pub evaluate_conditional_types: bool,
     pub function_is_final: bool,
+    /// Erase generic params to their (expanded) `as` bounds
     pub expand_generic: bool,
     pub expand_templates: bool,
     pub expand_hakana_types: bool,
     pub force_alias_expansion: bool,
     pub expand_type_aliases: bool,
+    /// Expand enums into the union of their individual cases, e.g. for
+    /// exhaustiveness checks
+    pub expand_enum_cases: bool,
+    /// Replace enums whose underlying type is `arraykey` with `int|string` rather than
+    /// preserving the enum identity
+    pub expand_arraykey_enums: bool,
+    /// If expanding a type alias visits more than this many atomics, nested ones
+    /// included, stop and use the alias's `as` constraint (or `mixed`) instead
+    pub max_type_alias_atomics: Option<usize>,
+    /// How many levels of self-referential generic bounds (e.g. the `T` in
+    /// `T as Traversable<T>`) to expand before the innermost bound becomes `mixed`
+    pub max_generic_bound_depth: usize,
+    /// How many type constants that refer to other type constants (e.g.
+    /// `const type TFoo = B::TBar`) to follow, so cyclic definitions terminate
+    pub max_type_constant_depth: usize,
+    /// When set, only type aliases and class type constants with these names are
+    /// expanded, and every other alias or type constant is left as-is
+    pub expand_only_aliases: Option<&'a FxHashSet<StrId>>,
     pub where_constraints: Option<&'a Vec<(StrId, TUnion)>>,
+    /// Concrete template arguments (e.g. from a call site) to substitute for
+    /// generic params during expansion
+    pub template_result: Option<&'a TemplateResult>,
+    /// When set, collects every type alias, and every class whose constants or type
+    /// constants were looked up, so callers can record them as dependencies
+    pub consulted_symbols: Option<&'a RefCell<FxHashSet<StrId>>>,
 }
 
 impl Default for TypeExpansionOptions<'_> {
@@ -61,11 +95,161 @@ impl Default for TypeExpansionOptions<'_> {
             expand_hakana_types: true,
             force_alias_expansion: false,
             expand_type_aliases: true,
+            expand_enum_cases: false,
+            expand_arraykey_enums: false,
+            max_type_alias_atomics: None,
+            max_generic_bound_depth: 3,
+            max_type_constant_depth: 10,
+            expand_only_aliases: None,
             where_constraints: None,
+            template_result: None,
+            consulted_symbols: None,
         }
     }
 }
 
+/// Everything a type constant's expansion can depend on, besides the codebase itself
+#[derive(PartialEq, Eq, Hash)]
+struct TypeConstantCacheKey {
+    class_name: StrId,
+    member_name: StrId,
+    file_path: FilePath,
+    self_class: Option<StrId>,
+    static_class: Option<StrId>,
+    parent_class: Option<StrId>,
+    flags: [bool; 10],
+    max_type_alias_atomics: Option<usize>,
+    remaining_generic_bound_depth: usize,
+    remaining_type_constant_depth: usize,
+}
+
+/// A definition whose expansion is in progress further up the stack
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExpansionFrame {
+    TypeAlias(StrId),
+    GenericBound,
+    TypeConstant,
+}
+
+/// Expanded class type constants, along with the cost of expanding them, for a single
+/// generation of the codebase
+#[derive(Default)]
+struct TypeConstantCache {
+    codebase_generation: u64,
+    entries: FxHashMap<TypeConstantCacheKey, (TUnion, u32)>,
+}
+
+thread_local! {
+    /// Expanded class type constants, so that e.g. a type constant used in many
+    /// signatures is only expanded once per thread and codebase generation
+    static TYPE_CONSTANT_CACHE: RefCell<TypeConstantCache> =
+        RefCell::new(TypeConstantCache::default());
+
+    /// Definitions currently being expanded on this thread, innermost last. Recursive
+    /// type aliases, generic bounds and type constants check this to terminate.
+    static EXPANSION_STACK: RefCell<Vec<ExpansionFrame>> = RefCell::new(vec![]);
+
+    /// The cost past which the type aliases being expanded on this thread are over
+    /// their `max_type_alias_atomics` budget, and further expansion is wasted
+    static EXPANSION_COST_LIMIT: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// Lowers the expansion cost limit until dropped
+struct CostLimitGuard {
+    previous_limit: Option<u32>,
+}
+
+impl CostLimitGuard {
+    fn push(limit: u32) -> Self {
+        let previous_limit = EXPANSION_COST_LIMIT.with(|cost_limit| {
+            cost_limit.replace(Some(match cost_limit.get() {
+                Some(previous_limit) => previous_limit.min(limit),
+                None => limit,
+            }))
+        });
+
+        Self { previous_limit }
+    }
+}
+
+impl Drop for CostLimitGuard {
+    fn drop(&mut self) {
+        EXPANSION_COST_LIMIT.with(|cost_limit| cost_limit.set(self.previous_limit));
+    }
+}
+
+fn is_over_cost_limit(cost: u32) -> bool {
+    EXPANSION_COST_LIMIT.with(|cost_limit| cost_limit.get().is_some_and(|limit| cost > limit))
+}
+
+/// Keeps a frame on the expansion stack until dropped
+struct ExpansionFrameGuard;
+
+impl ExpansionFrameGuard {
+    fn push(frame: ExpansionFrame) -> Self {
+        EXPANSION_STACK.with(|stack| stack.borrow_mut().push(frame));
+        Self
+    }
+}
+
+impl Drop for ExpansionFrameGuard {
+    fn drop(&mut self) {
+        EXPANSION_STACK.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+fn get_expansion_depth(frame: ExpansionFrame) -> usize {
+    EXPANSION_STACK.with(|stack| stack.borrow().iter().filter(|f| **f == frame).count())
+}
+
+fn is_expanding_type_alias() -> bool {
+    EXPANSION_STACK.with(|stack| {
+        stack
+            .borrow()
+            .iter()
+            .any(|frame| matches!(frame, ExpansionFrame::TypeAlias(_)))
+    })
+}
+
+/// Frees the type constants expanded on this thread. Entries for an older codebase
+/// generation are never reused, and are dropped as soon as the current one is cached.
+pub fn clear_type_constant_cache() {
+    TYPE_CONSTANT_CACHE.with(|cache| cache.borrow_mut().entries.clear());
+}
+
+fn get_cached_type_constant(
+    codebase: &CodebaseInfo,
+    cache_key: &TypeConstantCacheKey,
+) -> Option<(TUnion, u32)> {
+    TYPE_CONSTANT_CACHE.with(|cache| {
+        let cache = cache.borrow();
+
+        if cache.codebase_generation != codebase.generation {
+            return None;
+        }
+
+        cache.entries.get(cache_key).cloned()
+    })
+}
+
+fn cache_type_constant(
+    codebase: &CodebaseInfo,
+    cache_key: TypeConstantCacheKey,
+    type_: TUnion,
+    expansion_cost: u32,
+) {
+    TYPE_CONSTANT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        if cache.codebase_generation != codebase.generation {
+            cache.codebase_generation = codebase.generation;
+            cache.entries.clear();
+        }
+
+        cache.entries.insert(cache_key, (type_, expansion_cost));
+    })
+}
+
 pub fn expand_union(
     codebase: &CodebaseInfo,
     // interner is only used for data_flow_graph addition, so it's optional
@@ -83,6 +267,12 @@ pub fn expand_union(
     let original_types = std::mem::take(&mut return_type.types);
 
     for mut current_atomic_being_processed in original_types {
+        // the type alias being expanded is over budget and will fall back to its `as`
+        // constraint, so whatever's left of this union would be discarded anyway
+        if is_over_cost_limit(*cost) {
+            break;
+        }
+
         let mut skip_this_atomic = false;
         // This vector will receive replacements if current_atomic_being_processed is skipped.
         let mut replacements_for_current_atomic = Vec::new();
@@ -110,15 +300,157 @@ pub fn expand_union(
         }
     }
 
-    if overall_new_atomic_types.len() > 1 {
-        return_type.types = type_combiner::combine(overall_new_atomic_types, codebase, false);
+    // identical atomics (e.g. from two aliases with the same definition) are cheap to
+    // drop here, and often leave a single atomic that doesn't need combining
+    let deduped_atomic_types = overall_new_atomic_types
+        .into_iter()
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    if deduped_atomic_types.len() > 1 {
+        return_type.types = type_combiner::combine(deduped_atomic_types, codebase, false);
     } else {
-        return_type.types = overall_new_atomic_types;
+        return_type.types = deduped_atomic_types;
     }
 
     extend_dataflow_uniquely(&mut return_type.parent_nodes, overall_extra_data_flow_nodes);
 }
 
+/// Expands a union once for each candidate `static` class (e.g. each member of an
+/// `A|B` receiver) and combines the results, so `this` resolves to every candidate
+/// rather than to a single object.
+pub fn expand_union_for_static_classes(
+    codebase: &CodebaseInfo,
+    interner: &Option<&Interner>,
+    file_path: &FilePath,
+    return_type: &TUnion,
+    static_classes: &[TAtomic],
+    options: &TypeExpansionOptions,
+    data_flow_graph: &mut DataFlowGraph,
+    cost: &mut u32,
+) -> TUnion {
+    let mut combined_type: Option<TUnion> = None;
+
+    for static_class in static_classes {
+        let mut candidate_type = return_type.clone();
+
+        expand_union(
+            codebase,
+            interner,
+            file_path,
+            &mut candidate_type,
+            &TypeExpansionOptions {
+                static_class_type: StaticClassType::Object(static_class),
+                ..options.clone()
+            },
+            data_flow_graph,
+            cost,
+        );
+
+        combined_type = Some(if let Some(combined_type) = combined_type {
+            combine_union_types(&combined_type, &candidate_type, codebase, false)
+        } else {
+            candidate_type
+        });
+    }
+
+    combined_type.unwrap_or_else(|| {
+        let mut return_type = return_type.clone();
+        expand_union(
+            codebase,
+            interner,
+            file_path,
+            &mut return_type,
+            options,
+            data_flow_graph,
+            cost,
+        );
+        return_type
+    })
+}
+
+/// Lazily expands the atomics of a union, one at a time, so callers that only need
+/// to inspect the first few (e.g. to check whether any is a dict) can stop early.
+///
+/// Unlike `expand_union` the results are not combined, and any data flow nodes added
+/// along the way are only available via `into_extra_data_flow_nodes`.
+pub fn expand_union_iter<'a>(
+    codebase: &'a CodebaseInfo,
+    interner: &'a Option<&'a Interner>,
+    file_path: &'a FilePath,
+    atomics: Vec<TAtomic>,
+    options: &'a TypeExpansionOptions<'a>,
+    data_flow_graph: &'a mut DataFlowGraph,
+    cost: &'a mut u32,
+) -> ExpandedAtomicIter<'a> {
+    ExpandedAtomicIter {
+        codebase,
+        interner,
+        file_path,
+        options,
+        data_flow_graph,
+        cost,
+        remaining_atomics: atomics.into_iter(),
+        pending_replacements: VecDeque::new(),
+        extra_data_flow_nodes: vec![],
+    }
+}
+
+pub struct ExpandedAtomicIter<'a> {
+    codebase: &'a CodebaseInfo,
+    interner: &'a Option<&'a Interner>,
+    file_path: &'a FilePath,
+    options: &'a TypeExpansionOptions<'a>,
+    data_flow_graph: &'a mut DataFlowGraph,
+    cost: &'a mut u32,
+    remaining_atomics: std::vec::IntoIter<TAtomic>,
+    pending_replacements: VecDeque<TAtomic>,
+    extra_data_flow_nodes: Vec<DataFlowNode>,
+}
+
+impl ExpandedAtomicIter<'_> {
+    pub fn into_extra_data_flow_nodes(self) -> Vec<DataFlowNode> {
+        self.extra_data_flow_nodes
+    }
+}
+
+impl Iterator for ExpandedAtomicIter<'_> {
+    type Item = TAtomic;
+
+    fn next(&mut self) -> Option<TAtomic> {
+        loop {
+            if let Some(replacement) = self.pending_replacements.pop_front() {
+                return Some(replacement);
+            }
+
+            let mut atomic = self.remaining_atomics.next()?;
+            let mut skip_atomic = false;
+            let mut replacements = vec![];
+
+            expand_atomic(
+                &mut atomic,
+                self.codebase,
+                self.interner,
+                self.file_path,
+                self.options,
+                self.data_flow_graph,
+                self.cost,
+                &mut skip_atomic,
+                &mut replacements,
+                &mut self.extra_data_flow_nodes,
+            );
+
+            if !skip_atomic {
+                return Some(atomic);
+            }
+
+            // an atomic can expand to nothing, in which case we move on to the next one
+            self.pending_replacements.extend(replacements);
+        }
+    }
+}
+
 fn expand_atomic(
     return_type_part: &mut TAtomic,
     codebase: &CodebaseInfo,
@@ -140,6 +472,8 @@ fn expand_atomic(
         ..
     }) = return_type_part
     {
+        // open shapes (`shape('a' => int, ...)`) carry their catch-all key and value
+        // types in params, so these get expanded alongside the known items
         if let Some(params) = params {
             expand_union(
                 codebase,
@@ -163,6 +497,12 @@ fn expand_atomic(
 
         if let Some(known_items) = known_items {
             for (_, item_type) in known_items.values_mut() {
+                // make_mut clones item types that are shared with other shapes, so
+                // skip the ones that expansion would leave as-is anyway
+                if !may_need_expansion(item_type) {
+                    continue;
+                }
+
                 expand_union(
                     codebase,
                     interner,
@@ -240,6 +580,7 @@ fn expand_atomic(
         ref mut name,
         ref mut type_params,
         ref mut is_this,
+        ref mut extra_types,
         ..
     } = return_type_part
     {
@@ -287,6 +628,37 @@ fn expand_atomic(
             }
         }
 
+        if let Some(extra_types) = extra_types {
+            // intersected types (e.g. a shape alias) are expanded just like the object
+            let mut new_extra_types = vec![];
+
+            for mut extra_type in std::mem::take(extra_types) {
+                let mut skip_extra_type = false;
+                let mut extra_type_parts = vec![];
+
+                expand_atomic(
+                    &mut extra_type,
+                    codebase,
+                    interner,
+                    file_path,
+                    options,
+                    data_flow_graph,
+                    cost,
+                    &mut skip_extra_type,
+                    &mut extra_type_parts,
+                    extra_data_flow_nodes,
+                );
+
+                if skip_extra_type {
+                    new_extra_types.extend(extra_type_parts);
+                } else {
+                    new_extra_types.push(extra_type);
+                }
+            }
+
+            *extra_types = new_extra_types;
+        }
+
         return;
     } else if let TAtomic::TClosure(ref mut closure) = return_type_part {
         if let Some(ref mut return_type) = closure.return_type {
@@ -316,10 +688,39 @@ fn expand_atomic(
         }
     } else if let TAtomic::TGenericParam {
         param_name,
+        defining_entity,
         ref mut as_type,
         ..
     } = return_type_part
     {
+        if let Some(bounds) = options.template_result.and_then(|template_result| {
+            template_result
+                .lower_bounds
+                .get(&*param_name)?
+                .get(&*defining_entity)
+        }) {
+            let mut replacement_type = get_most_specific_type_from_bounds(bounds, codebase);
+
+            // don't expand a replacement that still refers to generic params, since
+            // it could refer back to the param we're replacing
+            if !replacement_type.has_template_types() {
+                expand_union(
+                    codebase,
+                    interner,
+                    file_path,
+                    &mut replacement_type,
+                    options,
+                    data_flow_graph,
+                    cost,
+                );
+            }
+
+            *skip_key = true;
+            extra_data_flow_nodes.extend(replacement_type.parent_nodes);
+            new_return_type_parts.extend(replacement_type.types);
+            return;
+        }
+
         if let Some(where_constraints) = options.where_constraints {
             for (_, constraint_type) in where_constraints.iter().filter(|(k, _)| k == param_name) {
                 *as_type = Box::new(
@@ -328,16 +729,94 @@ fn expand_atomic(
                 );
             }
         }
-        expand_union(
+
+        // a `this` bound should resolve against the static class, just like a bare `this`
+        // would. We only rename the bound here (rather than re-expanding the object) so a
+        // self-referential bound can't send us round in circles.
+        if let StaticClassType::Name(static_class_name) = options.static_class_type {
+            for bound_atomic in as_type.types.iter_mut() {
+                if let TAtomic::TNamedObject {
+                    name: bound_name,
+                    is_this: true,
+                    ..
+                } = bound_atomic
+                {
+                    if *bound_name != static_class_name
+                        && codebase.class_extends_or_implements(&static_class_name, bound_name)
+                    {
+                        *bound_name = static_class_name;
+                    }
+                }
+            }
+        }
+
+        if get_expansion_depth(ExpansionFrame::GenericBound) >= options.max_generic_bound_depth {
+            *as_type = Box::new(get_mixed());
+        } else {
+            let _frame = ExpansionFrameGuard::push(ExpansionFrame::GenericBound);
+
+            expand_union(
+                codebase,
+                interner,
+                file_path,
+                as_type,
+                options,
+                data_flow_graph,
+                cost,
+            );
+        }
+
+        if options.expand_generic {
+            *skip_key = true;
+            new_return_type_parts.extend(as_type.types.drain(..));
+        }
+
+        return;
+    } else if let TAtomic::TGenericClassname {
+        param_name,
+        defining_entity,
+        ref mut as_type,
+    } = return_type_part
+    {
+        // once the param is known to be a concrete class, classname<T> refers to that
+        // class rather than to the param's bound
+        if let Some(bounds) = options.template_result.and_then(|template_result| {
+            template_result
+                .lower_bounds
+                .get(&*param_name)?
+                .get(&*defining_entity)
+        }) {
+            let replacement_type = get_most_specific_type_from_bounds(bounds, codebase);
+
+            if let [replacement_atomic @ TAtomic::TNamedObject { .. }] =
+                replacement_type.types.as_slice()
+            {
+                *skip_key = true;
+                new_return_type_parts.push(TAtomic::TClassname {
+                    as_type: Box::new(replacement_atomic.clone()),
+                });
+                return;
+            }
+        }
+
+        let mut atomic_return_type_parts = vec![];
+        expand_atomic(
+            as_type,
             codebase,
             interner,
             file_path,
-            as_type,
             options,
             data_flow_graph,
             cost,
+            &mut false,
+            &mut atomic_return_type_parts,
+            extra_data_flow_nodes,
         );
 
+        if !atomic_return_type_parts.is_empty() {
+            *as_type = Box::new(atomic_return_type_parts.remove(0));
+        }
+
         return;
     } else if let TAtomic::TClassname {
         ref mut as_type, ..
@@ -352,7 +831,13 @@ fn expand_atomic(
             codebase,
             interner,
             file_path,
-            options,
+            // the bound names the enum itself (e.g. for MyEnum::class), so it mustn't be
+            // split into its cases or its underlying type
+            &TypeExpansionOptions {
+                expand_enum_cases: false,
+                expand_arraykey_enums: false,
+                ..options.clone()
+            },
             data_flow_graph,
             cost,
             &mut false,
@@ -404,7 +889,71 @@ fn expand_atomic(
                     data_flow_graph,
                     cost,
                 );
-                *underlying_type = Some(Arc::new(underlying_type_union.get_single_owned()));
+
+                // an enum can be backed by another enum, whose own underlying type has
+                // already been resolved (transitively) by the expansion above
+                *underlying_type = Some(match underlying_type_union.get_single_owned() {
+                    TAtomic::TEnum {
+                        underlying_type: Some(inner_underlying_type),
+                        ..
+                    } => inner_underlying_type,
+                    expanded_underlying_type => Arc::new(expanded_underlying_type),
+                });
+            }
+        }
+
+        if let TAtomic::TEnumLiteralCase {
+            enum_name,
+            member_name,
+            as_type,
+            ..
+        } = return_type_part
+        {
+            *as_type = get_enum_case_as_type(codebase, enum_name, member_name, as_type);
+        }
+
+        if options.expand_enum_cases {
+            if let TAtomic::TEnum {
+                name: enum_name,
+                as_type,
+                underlying_type,
+            } = return_type_part
+            {
+                if let Some(enum_storage) = codebase.classlike_infos.get(enum_name) {
+                    if !enum_storage.constants.is_empty() {
+                        *skip_key = true;
+
+                        new_return_type_parts.extend(enum_storage.constants.keys().map(
+                            |member_name| TAtomic::TEnumLiteralCase {
+                                enum_name: *enum_name,
+                                member_name: *member_name,
+                                as_type: get_enum_case_as_type(
+                                    codebase,
+                                    enum_name,
+                                    member_name,
+                                    as_type,
+                                ),
+                                underlying_type: underlying_type.clone(),
+                            },
+                        ));
+                    }
+                }
+            }
+        } else if options.expand_arraykey_enums {
+            if let TAtomic::TEnum {
+                as_type,
+                underlying_type,
+                ..
+            } = return_type_part
+            {
+                if let Some(TAtomic::TArraykey { .. }) =
+                    as_type.as_deref().or(underlying_type.as_deref())
+                {
+                    *skip_key = true;
+
+                    new_return_type_parts.push(TAtomic::TInt);
+                    new_return_type_parts.push(TAtomic::TString);
+                }
             }
         }
 
@@ -416,10 +965,57 @@ fn expand_atomic(
     {
         *skip_key = true;
 
-        if let Some(literal_value) =
-            codebase.get_classconst_literal_value(classlike_name, member_name)
+        record_consulted_symbol(options, *classlike_name);
+
+        // a reference to a case the enum doesn't declare falls through to the
+        // constant type lookup below, which widens it to mixed
+        let is_enum =
+            codebase
+                .classlike_infos
+                .get(classlike_name)
+                .map_or(false, |classlike_info| {
+                    matches!(classlike_info.kind, SymbolKind::Enum)
+                        && classlike_info.constants.contains_key(member_name)
+                });
+
+        // enum class members have a declared element type (possibly inherited from a
+        // parent enum class) that should win over the literal value
+        let is_enum_class = codebase
+            .classlike_infos
+            .get(classlike_name)
+            .map_or(false, |classlike_info| {
+                matches!(classlike_info.kind, SymbolKind::EnumClass)
+            });
+
+        if is_enum {
+            // keep the enum case identity instead of widening to the case's value
+            let mut enum_case = TAtomic::TEnumLiteralCase {
+                enum_name: *classlike_name,
+                member_name: *member_name,
+                as_type: None,
+                underlying_type: None,
+            };
+
+            expand_atomic(
+                &mut enum_case,
+                codebase,
+                interner,
+                file_path,
+                options,
+                data_flow_graph,
+                cost,
+                &mut false,
+                new_return_type_parts,
+                extra_data_flow_nodes,
+            );
+
+            new_return_type_parts.push(enum_case);
+        } else if let Some(literal_value) = codebase
+            .get_classconst_literal_value(classlike_name, member_name)
+            .filter(|_| !is_enum_class)
         {
             let mut literal_value = literal_value.clone();
+            let mut skip_literal_value = false;
 
             expand_atomic(
                 &mut literal_value,
@@ -429,12 +1025,14 @@ fn expand_atomic(
                 options,
                 data_flow_graph,
                 cost,
-                skip_key,
+                &mut skip_literal_value,
                 new_return_type_parts,
                 extra_data_flow_nodes,
             );
 
-            new_return_type_parts.push(literal_value);
+            if !skip_literal_value {
+                new_return_type_parts.push(literal_value);
+            }
         } else {
             let const_type = codebase.get_class_constant_type(
                 classlike_name,
@@ -454,6 +1052,9 @@ fn expand_atomic(
                     cost,
                 );
 
+                // the constant's expansion can add data flow nodes (e.g. for tainted
+                // shape fields) that need to survive the replacement
+                extra_data_flow_nodes.extend(const_type.parent_nodes);
                 new_return_type_parts.extend(const_type.types);
             } else {
                 new_return_type_parts.push(TAtomic::TMixed);
@@ -468,7 +1069,31 @@ fn expand_atomic(
         ..
     } = return_type_part
     {
-        if !options.expand_type_aliases {
+        record_consulted_symbol(options, *type_name);
+
+        if !options.expand_type_aliases
+            || options
+                .expand_only_aliases
+                .is_some_and(|names| !names.contains(type_name))
+        {
+            // the alias stays opaque, but its constraint is still useful for subtype checks
+            if as_type.is_none() {
+                if let Some(type_definition) = codebase.type_definitions.get(type_name) {
+                    if let Some(definition_as_type) = get_type_alias_as_type(
+                        type_definition,
+                        type_params,
+                        codebase,
+                        interner,
+                        file_path,
+                        options,
+                        data_flow_graph,
+                        cost,
+                    ) {
+                        *as_type = Some(Box::new(definition_as_type));
+                    }
+                }
+            }
+
             return;
         }
 
@@ -480,6 +1105,10 @@ fn expand_atomic(
             return;
         };
 
+        if get_expansion_depth(ExpansionFrame::TypeAlias(*type_name)) > 0 {
+            return;
+        }
+
         let can_expand_type =
             options.force_alias_expansion || can_expand_type_in_file(file_path, type_definition);
 
@@ -493,47 +1122,67 @@ fn expand_atomic(
             *skip_key = true;
 
             let mut actual_type = if let Some(type_params) = type_params {
-                let mut new_template_types = IndexMap::new();
-
-                for (i, (k, v)) in type_definition.template_types.iter().enumerate() {
-                    if i < type_params.len() {
-                        let mut h = FxHashMap::default();
-                        for (kk, _) in v {
-                            h.insert(*kk, type_params[i].clone());
-                        }
-
-                        new_template_types.insert(*k, h);
-                    }
-                }
-
                 template::inferred_type_replacer::replace(
                     &type_definition.actual_type,
-                    &template::TemplateResult::new(IndexMap::new(), new_template_types),
+                    &get_type_alias_template_result(type_definition, type_params),
                     codebase,
                 )
             } else {
                 type_definition.actual_type.clone()
             };
 
-            expand_union(
-                codebase,
-                interner,
-                file_path,
-                &mut actual_type,
-                options,
-                data_flow_graph,
-                cost,
-            );
+            let cost_limit = options
+                .max_type_alias_atomics
+                .map(|max_type_alias_atomics| {
+                    cost.saturating_add(u32::try_from(max_type_alias_atomics).unwrap_or(u32::MAX))
+                });
 
-            let expanded_types = actual_type
-                .types
-                .into_iter()
-                .map(|mut v| {
-                    if type_params.is_none() {
-                        if let TAtomic::TDict(TDict {
-                            known_items: Some(_),
-                            ref mut shape_name,
-                            ..
+            {
+                let _frame = ExpansionFrameGuard::push(ExpansionFrame::TypeAlias(*type_name));
+                let _cost_limit = cost_limit.map(CostLimitGuard::push);
+
+                expand_union(
+                    codebase,
+                    interner,
+                    file_path,
+                    &mut actual_type,
+                    options,
+                    data_flow_graph,
+                    cost,
+                );
+            }
+
+            if let Some(cost_limit) = cost_limit {
+                if *cost > cost_limit {
+                    if let Some(definition_as_type) = get_type_alias_as_type(
+                        type_definition,
+                        type_params,
+                        codebase,
+                        interner,
+                        file_path,
+                        options,
+                        data_flow_graph,
+                        cost,
+                    ) {
+                        extra_data_flow_nodes.extend(definition_as_type.parent_nodes);
+                        new_return_type_parts.extend(definition_as_type.types);
+                    } else {
+                        new_return_type_parts.push(TAtomic::TMixed);
+                    }
+
+                    return;
+                }
+            }
+
+            let expanded_types = actual_type
+                .types
+                .into_iter()
+                .map(|mut v| {
+                    if type_params.is_none() {
+                        if let TAtomic::TDict(TDict {
+                            known_items: Some(_),
+                            ref mut shape_name,
+                            ..
                         }) = v
                         {
                             if let (Some(shape_field_taints), Some(interner)) =
@@ -564,7 +1213,9 @@ fn expand_atomic(
                                             match field_name {
                                                 DictKey::Int(i) => i.to_string(),
                                                 DictKey::String(k) => k.clone(),
-                                                DictKey::Enum(_, _) => todo!(),
+                                                DictKey::Enum(_, _) => {
+                                                    field_name.to_string(Some(interner))
+                                                }
                                             },
                                         ),
                                         vec![],
@@ -589,45 +1240,16 @@ fn expand_atomic(
                 .collect::<Vec<_>>();
 
             new_return_type_parts.extend(expanded_types);
-        } else if let Some(definition_as_type) = &type_definition.as_type {
-            let mut definition_as_type = if let Some(type_params) = type_params {
-                let mut new_template_types = IndexMap::new();
-
-                for (i, (k, v)) in type_definition.template_types.iter().enumerate() {
-                    let mut h = FxHashMap::default();
-                    for (kk, _) in v {
-                        h.insert(
-                            *kk,
-                            if let Some(t) = type_params.get(i) {
-                                t.clone()
-                            } else {
-                                get_nothing()
-                            },
-                        );
-                    }
-
-                    new_template_types.insert(*k, h);
-                }
-
-                template::inferred_type_replacer::replace(
-                    definition_as_type,
-                    &template::TemplateResult::new(IndexMap::new(), new_template_types),
-                    codebase,
-                )
-            } else {
-                definition_as_type.clone()
-            };
-
-            expand_union(
-                codebase,
-                interner,
-                file_path,
-                &mut definition_as_type,
-                options,
-                data_flow_graph,
-                cost,
-            );
-
+        } else if let Some(definition_as_type) = get_type_alias_as_type(
+            type_definition,
+            type_params,
+            codebase,
+            interner,
+            file_path,
+            options,
+            data_flow_graph,
+            cost,
+        ) {
             *as_type = Some(Box::new(definition_as_type));
         }
 
@@ -652,6 +1274,13 @@ fn expand_atomic(
         as_type,
     } = return_type_part
     {
+        if options
+            .expand_only_aliases
+            .is_some_and(|names| !names.contains(member_name))
+        {
+            return;
+        }
+
         let mut atomic_return_type_parts = vec![];
         expand_atomic(
             class_type,
@@ -674,8 +1303,11 @@ fn expand_atomic(
             TAtomic::TNamedObject {
                 name: class_name,
                 is_this,
+                type_params,
                 ..
             } => {
+                record_consulted_symbol(options, *class_name);
+
                 let classlike_storage = if let Some(c) = codebase.classlike_infos.get(class_name) {
                     c
                 } else {
@@ -684,7 +1316,7 @@ fn expand_atomic(
                     return;
                 };
 
-                let type_constant = if let Some(t) =
+                let mut type_constant = if let Some(t) =
                     classlike_storage.type_constants.get(member_name)
                 {
                     t.clone()
@@ -694,6 +1326,46 @@ fn expand_atomic(
                     return;
                 };
 
+                // the type constant may reference the class's own generics, so substitute
+                // in the instance's type params
+                if let Some(type_params) = type_params {
+                    let mut new_template_types = IndexMap::new();
+
+                    for (i, (template_name, _)) in
+                        classlike_storage.template_types.iter().enumerate()
+                    {
+                        if let Some(type_param) = type_params.get(i) {
+                            new_template_types.insert(
+                                *template_name,
+                                FxHashMap::from_iter([(
+                                    GenericParent::ClassLike(*class_name),
+                                    type_param.clone(),
+                                )]),
+                            );
+                        }
+                    }
+
+                    let template_result = TemplateResult::new(IndexMap::new(), new_template_types);
+
+                    type_constant = match type_constant {
+                        ClassConstantType::Concrete(type_) => {
+                            ClassConstantType::Concrete(template::inferred_type_replacer::replace(
+                                &type_,
+                                &template_result,
+                                codebase,
+                            ))
+                        }
+                        ClassConstantType::Abstract(Some(type_)) => ClassConstantType::Abstract(
+                            Some(template::inferred_type_replacer::replace(
+                                &type_,
+                                &template_result,
+                                codebase,
+                            )),
+                        ),
+                        ClassConstantType::Abstract(None) => ClassConstantType::Abstract(None),
+                    };
+                }
+
                 let mut is_this = *is_this;
 
                 if is_this {
@@ -712,20 +1384,69 @@ fn expand_atomic(
                     }
                 }
 
+                if get_expansion_depth(ExpansionFrame::TypeConstant)
+                    >= options.max_type_constant_depth
+                {
+                    *skip_key = true;
+                    new_return_type_parts.push(TAtomic::TMixedWithFlags(true, false, false, false));
+                    return;
+                }
+
+                // the resolved type may itself refer to another type constant
+                let _frame = ExpansionFrameGuard::push(ExpansionFrame::TypeConstant);
+
                 match (is_this, type_constant) {
                     (_, ClassConstantType::Concrete(mut type_))
                     | (false, ClassConstantType::Abstract(Some(mut type_))) => {
-                        expand_union(
-                            codebase,
-                            interner,
-                            file_path,
-                            &mut type_,
-                            options,
-                            data_flow_graph,
-                            cost,
-                        );
+                        let cache_key = if type_params.is_none() {
+                            get_type_constant_cache_key(
+                                *class_name,
+                                *member_name,
+                                file_path,
+                                options,
+                                data_flow_graph,
+                            )
+                        } else {
+                            None
+                        };
+
+                        let cached_type = cache_key
+                            .as_ref()
+                            .and_then(|cache_key| get_cached_type_constant(codebase, cache_key));
+
+                        if let Some((cached_type, expansion_cost)) = cached_type {
+                            type_ = cached_type;
+                            // count the expansion the cache saved, so budgets don't depend
+                            // on what was expanded before
+                            *cost += expansion_cost;
+                        } else {
+                            let cost_before_expansion = *cost;
+
+                            expand_union(
+                                codebase,
+                                interner,
+                                file_path,
+                                &mut type_,
+                                options,
+                                data_flow_graph,
+                                cost,
+                            );
+
+                            // data flow nodes are tied to where the type was expanded
+                            if let Some(cache_key) = cache_key {
+                                if type_.parent_nodes.is_empty() {
+                                    cache_type_constant(
+                                        codebase,
+                                        cache_key,
+                                        type_.clone(),
+                                        *cost - cost_before_expansion,
+                                    );
+                                }
+                            }
+                        }
 
                         *skip_key = true;
+                        extra_data_flow_nodes.extend(std::mem::take(&mut type_.parent_nodes));
                         new_return_type_parts.extend(type_.types.into_iter().map(|mut v| {
                             if let TAtomic::TDict(TDict {
                                 known_items: Some(_),
@@ -773,6 +1494,209 @@ fn expand_atomic(
     }
 }
 
+fn record_consulted_symbol(options: &TypeExpansionOptions, symbol: StrId) {
+    if let Some(consulted_symbols) = options.consulted_symbols {
+        consulted_symbols.borrow_mut().insert(symbol);
+    }
+}
+
+/// Returns None when the expansion depends on more than the options captured in the
+/// key, e.g. on the caller's template arguments, or has side effects a cache hit would
+/// skip, like recording consulted symbols or adding whole-program data flow nodes.
+fn get_type_constant_cache_key(
+    class_name: StrId,
+    member_name: StrId,
+    file_path: &FilePath,
+    options: &TypeExpansionOptions,
+    data_flow_graph: &DataFlowGraph,
+) -> Option<TypeConstantCacheKey> {
+    if matches!(data_flow_graph.kind, GraphKind::WholeProgram(_))
+        || options.consulted_symbols.is_some()
+        || options.expand_only_aliases.is_some()
+        || options.where_constraints.is_some()
+        || options.template_result.is_some()
+        || is_expanding_type_alias()
+    {
+        return None;
+    }
+
+    let static_class = match &options.static_class_type {
+        StaticClassType::None => None,
+        StaticClassType::Name(name) => Some(*name),
+        StaticClassType::Object(_) => return None,
+    };
+
+    Some(TypeConstantCacheKey {
+        class_name,
+        member_name,
+        file_path: *file_path,
+        self_class: options.self_class,
+        static_class,
+        parent_class: options.parent_class,
+        flags: [
+            options.evaluate_class_constants,
+            options.evaluate_conditional_types,
+            options.function_is_final,
+            options.expand_generic,
+            options.expand_templates,
+            options.expand_hakana_types,
+            options.force_alias_expansion,
+            options.expand_type_aliases,
+            options.expand_enum_cases,
+            options.expand_arraykey_enums,
+        ],
+        max_type_alias_atomics: options.max_type_alias_atomics,
+        remaining_generic_bound_depth: options
+            .max_generic_bound_depth
+            .saturating_sub(get_expansion_depth(ExpansionFrame::GenericBound)),
+        remaining_type_constant_depth: options
+            .max_type_constant_depth
+            .saturating_sub(get_expansion_depth(ExpansionFrame::TypeConstant)),
+    })
+}
+
+/// Returns false for types made up only of scalars (and collections of them), which
+/// expansion never changes. Everything else is assumed to need expanding.
+fn may_need_expansion(union: &TUnion) -> bool {
+    union.types.iter().any(|atomic| match atomic {
+        TAtomic::TDict(TDict {
+            known_items,
+            params,
+            shape_name,
+            ..
+        }) => {
+            shape_name.is_some()
+                || known_items.as_ref().is_some_and(|known_items| {
+                    known_items
+                        .values()
+                        .any(|(_, item_type)| may_need_expansion(item_type))
+                })
+                || params.as_ref().is_some_and(|params| {
+                    may_need_expansion(&params.0) || may_need_expansion(&params.1)
+                })
+        }
+        TAtomic::TVec(TVec {
+            known_items,
+            type_param,
+            ..
+        }) => {
+            may_need_expansion(type_param)
+                || known_items.as_ref().is_some_and(|known_items| {
+                    known_items
+                        .values()
+                        .any(|(_, item_type)| may_need_expansion(item_type))
+                })
+        }
+        TAtomic::TKeyset { type_param, .. } => may_need_expansion(type_param),
+        TAtomic::TArraykey { .. }
+        | TAtomic::TBool
+        | TAtomic::TFalse
+        | TAtomic::TFloat
+        | TAtomic::TInt
+        | TAtomic::TLiteralInt { .. }
+        | TAtomic::TLiteralString { .. }
+        | TAtomic::TMixed
+        | TAtomic::TMixedFromLoopIsset
+        | TAtomic::TMixedWithFlags(..)
+        | TAtomic::TNothing
+        | TAtomic::TNull
+        | TAtomic::TNum
+        | TAtomic::TObject
+        | TAtomic::TResource
+        | TAtomic::TScalar
+        | TAtomic::TString
+        | TAtomic::TStringWithFlags(..)
+        | TAtomic::TTrue
+        | TAtomic::TVoid => false,
+        _ => true,
+    })
+}
+
+/// Narrows a transparent enum case's `as` constraint to the case's literal value, so
+/// that e.g. `MyEnum::A` in an `enum MyEnum: string as string` is known to be `"a"`.
+/// Wider constraints like `arraykey` are left alone, since treating the case as its
+/// literal there would let it flow into places Hack itself rejects.
+fn get_enum_case_as_type(
+    codebase: &CodebaseInfo,
+    enum_name: &StrId,
+    member_name: &StrId,
+    as_type: &Option<Arc<TAtomic>>,
+) -> Option<Arc<TAtomic>> {
+    match (
+        as_type.as_deref(),
+        codebase.get_classconst_literal_value(enum_name, member_name),
+    ) {
+        (Some(TAtomic::TString), Some(literal @ TAtomic::TLiteralString { .. }))
+        | (Some(TAtomic::TInt), Some(literal @ TAtomic::TLiteralInt { .. })) => {
+            Some(Arc::new(literal.clone()))
+        }
+        _ => as_type.clone(),
+    }
+}
+
+/// Maps each of the alias's template params to the corresponding type argument. When
+/// fewer arguments are given than the alias declares, the remaining params fall back
+/// to their bounds rather than being left unsubstituted.
+fn get_type_alias_template_result(
+    type_definition: &TypeDefinitionInfo,
+    type_params: &[TUnion],
+) -> TemplateResult {
+    let mut new_template_types = IndexMap::new();
+
+    for (i, (k, v)) in type_definition.template_types.iter().enumerate() {
+        let mut h = FxHashMap::default();
+        for (kk, bound) in v {
+            h.insert(
+                *kk,
+                if let Some(t) = type_params.get(i) {
+                    t.clone()
+                } else {
+                    (**bound).clone()
+                },
+            );
+        }
+
+        new_template_types.insert(*k, h);
+    }
+
+    TemplateResult::new(IndexMap::new(), new_template_types)
+}
+
+fn get_type_alias_as_type(
+    type_definition: &TypeDefinitionInfo,
+    type_params: &Option<Vec<TUnion>>,
+    codebase: &CodebaseInfo,
+    interner: &Option<&Interner>,
+    file_path: &FilePath,
+    options: &TypeExpansionOptions,
+    data_flow_graph: &mut DataFlowGraph,
+    cost: &mut u32,
+) -> Option<TUnion> {
+    let definition_as_type = type_definition.as_type.as_ref()?;
+
+    let mut definition_as_type = if let Some(type_params) = type_params {
+        template::inferred_type_replacer::replace(
+            definition_as_type,
+            &get_type_alias_template_result(type_definition, type_params),
+            codebase,
+        )
+    } else {
+        definition_as_type.clone()
+    };
+
+    expand_union(
+        codebase,
+        interner,
+        file_path,
+        &mut definition_as_type,
+        options,
+        data_flow_graph,
+        cost,
+    );
+
+    Some(definition_as_type)
+}
+
 pub fn can_expand_type_in_file(file_path: &FilePath, type_definition: &TypeDefinitionInfo) -> bool {
     if let Some(type_file_path) = &type_definition.newtype_file {
         file_path == type_file_path
@@ -831,7 +1755,23 @@ pub fn get_closure_from_id(
     None
 }
 
-fn get_expanded_closure(
+/// A function or method signature with all of its types expanded
+#[derive(Debug, Clone)]
+pub struct ExpandedSignature {
+    pub params: Vec<ExpandedParam>,
+    pub return_type: Option<TUnion>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpandedParam {
+    pub name: VarId,
+    pub signature_type: Option<TUnion>,
+    pub is_inout: bool,
+    pub is_variadic: bool,
+    pub is_optional: bool,
+}
+
+pub fn get_expanded_signature(
     functionlike_info: &FunctionLikeInfo,
     codebase: &CodebaseInfo,
     interner: &Option<&Interner>,
@@ -839,12 +1779,13 @@ fn get_expanded_closure(
     data_flow_graph: &mut DataFlowGraph,
     options: &TypeExpansionOptions,
     cost: &mut u32,
-) -> TAtomic {
-    TAtomic::TClosure(Box::new(TClosure {
+) -> ExpandedSignature {
+    ExpandedSignature {
         params: functionlike_info
             .params
             .iter()
-            .map(|param| FnParameter {
+            .map(|param| ExpandedParam {
+                name: param.name,
                 signature_type: if let Some(t) = &param.signature_type {
                     let mut t = t.clone();
                     expand_union(
@@ -856,7 +1797,7 @@ fn get_expanded_closure(
                         data_flow_graph,
                         cost,
                     );
-                    Some(Box::new(t))
+                    Some(t)
                 } else {
                     None
                 },
@@ -880,6 +1821,58 @@ fn get_expanded_closure(
         } else {
             None
         },
+    }
+}
+
+fn get_expanded_closure(
+    functionlike_info: &FunctionLikeInfo,
+    codebase: &CodebaseInfo,
+    interner: &Option<&Interner>,
+    file_path: &FilePath,
+    data_flow_graph: &mut DataFlowGraph,
+    options: &TypeExpansionOptions,
+    cost: &mut u32,
+) -> TAtomic {
+    let signature = get_expanded_signature(
+        functionlike_info,
+        codebase,
+        interner,
+        file_path,
+        data_flow_graph,
+        options,
+        cost,
+    );
+
+    // a param with a default can't actually be omitted when a required param follows
+    // it, so it's only optional in the callable's contract if every later param is
+    // optional or variadic too
+    let mut has_later_required_param = false;
+
+    let mut params = signature
+        .params
+        .into_iter()
+        .rev()
+        .map(|param| {
+            let is_optional = param.is_optional && !has_later_required_param;
+
+            if !param.is_optional && !param.is_variadic {
+                has_later_required_param = true;
+            }
+
+            FnParameter {
+                signature_type: param.signature_type.map(Box::new),
+                is_inout: param.is_inout,
+                is_variadic: param.is_variadic,
+                is_optional,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    params.reverse();
+
+    TAtomic::TClosure(Box::new(TClosure {
+        params,
+        return_type: signature.return_type,
         effects: functionlike_info.effects.to_u8(),
         closure_id: (
             functionlike_info.def_location.file_path,
@@ -887,3 +1880,817 @@ fn get_expanded_closure(
         ),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class_constant_info::ConstantInfo;
+    use crate::classlike_info::ClassLikeInfo;
+    use crate::code_location::HPos;
+    use crate::functionlike_info::MetaStart;
+    use crate::functionlike_parameter::FunctionLikeParameter;
+    use crate::ttype::comparison::{
+        type_comparison_result::TypeComparisonResult, union_type_comparator,
+    };
+    use crate::ttype::{get_int, get_string, get_vec};
+    use std::collections::BTreeMap;
+
+    fn expand(union: &mut TUnion, options: &TypeExpansionOptions) {
+        expand_union(
+            &CodebaseInfo::new(),
+            &None,
+            &FilePath(StrId::EMPTY),
+            union,
+            options,
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+            &mut 0,
+        );
+    }
+
+    fn get_generic_param(interner: &mut Interner) -> TUnion {
+        wrap_atomic(TAtomic::TGenericParam {
+            param_name: interner.intern("T".to_string()),
+            as_type: Box::new(get_int()),
+            defining_entity: GenericParent::FunctionLike(interner.intern("foo".to_string())),
+            extra_types: None,
+        })
+    }
+
+    fn get_pos() -> HPos {
+        HPos {
+            file_path: FilePath(StrId::EMPTY),
+            start_offset: 0,
+            end_offset: 0,
+            start_line: 1,
+            end_line: 1,
+            start_column: 1,
+            end_column: 1,
+        }
+    }
+
+    fn add_type_alias(codebase: &mut CodebaseInfo, name: StrId, actual_type: TUnion) -> TAtomic {
+        codebase.type_definitions.insert(
+            name,
+            TypeDefinitionInfo {
+                newtype_file: None,
+                as_type: None,
+                actual_type,
+                template_types: vec![],
+                generic_variance: FxHashMap::default(),
+                shape_field_taints: None,
+                is_literal_string: false,
+                location: get_pos(),
+                user_defined: true,
+                generated: false,
+                attributes: vec![],
+            },
+        );
+
+        TAtomic::TTypeAlias {
+            name,
+            newtype: false,
+            type_params: None,
+            as_type: None,
+        }
+    }
+
+    fn get_large_type_alias(interner: &mut Interner, codebase: &mut CodebaseInfo) -> TUnion {
+        let name = interner.intern("LargeAlias".to_string());
+        let type_alias = add_type_alias(
+            codebase,
+            name,
+            TUnion::new(
+                (0..500)
+                    .map(|value| TAtomic::TLiteralInt { value })
+                    .collect(),
+            ),
+        );
+
+        codebase.type_definitions.get_mut(&name).unwrap().as_type = Some(get_int());
+
+        wrap_atomic(type_alias)
+    }
+
+    fn expand_in(codebase: &CodebaseInfo, union: &mut TUnion, options: &TypeExpansionOptions) {
+        expand_union(
+            codebase,
+            &None,
+            &FilePath(StrId::EMPTY),
+            union,
+            options,
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+            &mut 0,
+        );
+    }
+
+    #[test]
+    fn type_alias_within_budget_is_fully_expanded() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let mut union = get_large_type_alias(&mut interner, &mut codebase);
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                max_type_alias_atomics: Some(500),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(union.types.len(), 500);
+    }
+
+    #[test]
+    fn type_alias_over_budget_falls_back_to_its_as_constraint() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let mut union = get_large_type_alias(&mut interner, &mut codebase);
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                max_type_alias_atomics: Some(100),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(union, get_int());
+    }
+
+    #[test]
+    fn type_alias_expansion_stops_once_over_budget() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+
+        let nested_aliases = (0..10)
+            .map(|i| {
+                let name = interner.intern(format!("Nested{}", i));
+                add_type_alias(
+                    &mut codebase,
+                    name,
+                    TUnion::new(
+                        (0..100)
+                            .map(|value| TAtomic::TLiteralInt {
+                                value: i * 100 + value,
+                            })
+                            .collect(),
+                    ),
+                )
+            })
+            .collect();
+
+        let name = interner.intern("Outer".to_string());
+        let type_alias = add_type_alias(&mut codebase, name, TUnion::new(nested_aliases));
+        codebase.type_definitions.get_mut(&name).unwrap().as_type = Some(get_int());
+
+        let mut union = wrap_atomic(type_alias);
+        let mut cost = 0;
+
+        expand_union(
+            &codebase,
+            &None,
+            &FilePath(StrId::EMPTY),
+            &mut union,
+            &TypeExpansionOptions {
+                max_type_alias_atomics: Some(50),
+                ..Default::default()
+            },
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+            &mut cost,
+        );
+
+        assert_eq!(union, get_int());
+        // only a little over the budget, rather than the 1000 literals
+        assert!(cost < 60, "expansion cost {}", cost);
+    }
+
+    #[test]
+    fn expand_only_aliases_leaves_other_aliases_symbolic() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let user_id = interner.intern("UserId".to_string());
+        let user_id_alias = add_type_alias(&mut codebase, user_id, get_int());
+        let user_name = interner.intern("UserName".to_string());
+        let user_name_alias = add_type_alias(&mut codebase, user_name, get_string());
+
+        let mut union = get_vec(TUnion::new(vec![user_id_alias, user_name_alias.clone()]));
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                expand_only_aliases: Some(&FxHashSet::from_iter([user_id])),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            union,
+            get_vec(TUnion::new(vec![TAtomic::TInt, user_name_alias]))
+        );
+    }
+
+    #[test]
+    fn consulted_symbols_include_transitively_expanded_aliases() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let user_id = interner.intern("UserId".to_string());
+        let user_id_alias = add_type_alias(&mut codebase, user_id, get_int());
+        let user_ids = interner.intern("UserIds".to_string());
+        let user_ids_alias =
+            add_type_alias(&mut codebase, user_ids, get_vec(wrap_atomic(user_id_alias)));
+
+        let consulted_symbols = RefCell::new(FxHashSet::default());
+        let mut union = wrap_atomic(user_ids_alias);
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                consulted_symbols: Some(&consulted_symbols),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(union, get_vec(get_int()));
+        assert_eq!(
+            consulted_symbols.into_inner(),
+            FxHashSet::from_iter([user_ids, user_id])
+        );
+    }
+
+    #[test]
+    fn generic_params_keep_their_identity_by_default() {
+        let mut interner = Interner::default();
+        let mut union = get_generic_param(&mut interner);
+
+        expand(&mut union, &TypeExpansionOptions::default());
+
+        assert_eq!(union, get_generic_param(&mut interner));
+    }
+
+    #[test]
+    fn expand_generic_erases_generic_params_to_their_bounds() {
+        let mut interner = Interner::default();
+        let mut union = get_generic_param(&mut interner);
+
+        expand(
+            &mut union,
+            &TypeExpansionOptions {
+                expand_generic: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(union, get_int());
+    }
+
+    fn add_enum(
+        codebase: &mut CodebaseInfo,
+        name: StrId,
+        backing_type: TAtomic,
+        cases: Vec<(StrId, TAtomic)>,
+    ) {
+        let mut enum_info = ClassLikeInfo::new(
+            name,
+            get_pos(),
+            MetaStart {
+                start_offset: 0,
+                start_line: 1,
+                start_column: 1,
+            },
+            get_pos(),
+        );
+        enum_info.kind = SymbolKind::Enum;
+        enum_info.enum_as_type = Some(backing_type.clone());
+        enum_info.enum_underlying_type = Some(backing_type);
+
+        for (case_name, case_value) in cases {
+            enum_info.constants.insert(
+                case_name,
+                ConstantInfo {
+                    pos: get_pos(),
+                    type_pos: None,
+                    provided_type: None,
+                    inferred_type: Some(case_value),
+                    unresolved_value: None,
+                    is_abstract: false,
+                    allow_non_exclusive_enum_values: false,
+                    suppressed_issues: vec![],
+                    defining_class: name,
+                    user_defined: true,
+                },
+            );
+        }
+
+        codebase.classlike_infos.insert(name, enum_info);
+    }
+
+    #[test]
+    fn enum_expands_into_all_of_its_cases() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let suit = interner.intern("Suit".to_string());
+        let case_names =
+            ["Hearts", "Spades", "Clubs"].map(|case_name| interner.intern(case_name.to_string()));
+
+        add_enum(
+            &mut codebase,
+            suit,
+            TAtomic::TString,
+            case_names
+                .iter()
+                .map(|case_name| {
+                    (
+                        *case_name,
+                        TAtomic::TLiteralString {
+                            value: interner.lookup(case_name).to_lowercase(),
+                        },
+                    )
+                })
+                .collect(),
+        );
+
+        let mut union = wrap_atomic(TAtomic::TEnum {
+            name: suit,
+            as_type: None,
+            underlying_type: None,
+        });
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                expand_enum_cases: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            union.types,
+            case_names
+                .into_iter()
+                .map(|case_name| TAtomic::TEnumLiteralCase {
+                    enum_name: suit,
+                    member_name: case_name,
+                    as_type: Some(Arc::new(TAtomic::TLiteralString {
+                        value: interner.lookup(&case_name).to_lowercase(),
+                    })),
+                    underlying_type: Some(Arc::new(TAtomic::TString)),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn functionlike_signature_is_fully_expanded() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let user_id = interner.intern("UserId".to_string());
+        let user_id_alias = add_type_alias(&mut codebase, user_id, get_int());
+        let get_user = interner.intern("get_user".to_string());
+        let id_param_name = VarId(interner.intern("$id".to_string()));
+        let name_param_name = VarId(interner.intern("$name".to_string()));
+
+        let mut functionlike_info = FunctionLikeInfo::new(
+            get_pos(),
+            MetaStart {
+                start_offset: 0,
+                start_line: 1,
+                start_column: 1,
+            },
+        );
+
+        let mut id_param = FunctionLikeParameter::new(id_param_name, get_pos(), get_pos());
+        id_param.signature_type = Some(wrap_atomic(user_id_alias.clone()));
+        let mut name_param = FunctionLikeParameter::new(name_param_name, get_pos(), get_pos());
+        name_param.signature_type = Some(get_string());
+        name_param.is_optional = true;
+        functionlike_info.params = vec![id_param, name_param];
+
+        let get_shape = |id_type: TAtomic| {
+            wrap_atomic(TAtomic::TDict(TDict {
+                known_items: Some(BTreeMap::from([(
+                    DictKey::String("id".to_string()),
+                    (false, Arc::new(wrap_atomic(id_type))),
+                )])),
+                params: None,
+                non_empty: true,
+                shape_name: None,
+            }))
+        };
+        functionlike_info.return_type = Some(get_shape(user_id_alias));
+
+        codebase
+            .functionlike_infos
+            .insert((get_user, StrId::EMPTY), functionlike_info);
+
+        let signature = codebase
+            .expand_functionlike_signature(
+                &FunctionLikeIdentifier::Function(get_user),
+                &TypeExpansionOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            signature
+                .params
+                .iter()
+                .map(|param| (param.name, param.signature_type.clone(), param.is_optional))
+                .collect::<Vec<_>>(),
+            vec![
+                (id_param_name, Some(get_int()), false),
+                (name_param_name, Some(get_string()), true),
+            ]
+        );
+        assert_eq!(signature.return_type, Some(get_shape(TAtomic::TInt)));
+    }
+
+    #[test]
+    fn non_empty_sealed_dict_aliases_stay_non_empty_when_combined() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+
+        let get_optional_field_shape = |field_name: &str, field_type: TUnion| {
+            wrap_atomic(TAtomic::TDict(TDict {
+                known_items: Some(BTreeMap::from([(
+                    DictKey::String(field_name.to_string()),
+                    (true, Arc::new(field_type)),
+                )])),
+                params: None,
+                non_empty: true,
+                shape_name: None,
+            }))
+        };
+
+        let with_id = add_type_alias(
+            &mut codebase,
+            interner.intern("WithId".to_string()),
+            get_optional_field_shape("id", get_int()),
+        );
+        let with_name = add_type_alias(
+            &mut codebase,
+            interner.intern("WithName".to_string()),
+            get_optional_field_shape("name", get_string()),
+        );
+
+        let mut union = TUnion::new(vec![with_id, with_name]);
+
+        expand_in(&codebase, &mut union, &TypeExpansionOptions::default());
+
+        assert_eq!(
+            union.types,
+            vec![TAtomic::TDict(TDict {
+                known_items: Some(BTreeMap::from([
+                    (
+                        DictKey::String("id".to_string()),
+                        (true, Arc::new(get_int()))
+                    ),
+                    (
+                        DictKey::String("name".to_string()),
+                        (true, Arc::new(get_string()))
+                    ),
+                ])),
+                params: None,
+                non_empty: true,
+                shape_name: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn intersected_shape_alias_and_object_type_params_both_expand() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+
+        let id_alias = add_type_alias(
+            &mut codebase,
+            interner.intern("IdAlias".to_string()),
+            get_int(),
+        );
+        let id_shape_alias_name = interner.intern("IdShapeAlias".to_string());
+        let id_shape_alias = add_type_alias(
+            &mut codebase,
+            id_shape_alias_name,
+            wrap_atomic(TAtomic::TDict(TDict {
+                known_items: Some(BTreeMap::from([(
+                    DictKey::String("id".to_string()),
+                    (false, Arc::new(wrap_atomic(id_alias.clone()))),
+                )])),
+                params: None,
+                non_empty: true,
+                shape_name: None,
+            })),
+        );
+        let has_id = interner.intern("HasId".to_string());
+
+        // HasId<IdAlias> & IdShapeAlias
+        let mut union = wrap_atomic(TAtomic::TNamedObject {
+            name: has_id,
+            type_params: Some(vec![wrap_atomic(id_alias)]),
+            is_this: false,
+            extra_types: Some(vec![id_shape_alias]),
+            remapped_params: false,
+        });
+
+        expand_in(&codebase, &mut union, &TypeExpansionOptions::default());
+
+        assert_eq!(
+            union.types,
+            vec![TAtomic::TNamedObject {
+                name: has_id,
+                type_params: Some(vec![get_int()]),
+                is_this: false,
+                extra_types: Some(vec![TAtomic::TDict(TDict {
+                    known_items: Some(BTreeMap::from([(
+                        DictKey::String("id".to_string()),
+                        (false, Arc::new(get_int())),
+                    )])),
+                    params: None,
+                    non_empty: true,
+                    shape_name: Some((id_shape_alias_name, None)),
+                })]),
+                remapped_params: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn generic_params_are_substituted_from_template_result() {
+        let mut interner = Interner::default();
+        let generic_param = get_generic_param(&mut interner);
+        let template_result = TemplateResult::new(
+            IndexMap::new(),
+            IndexMap::from([(
+                interner.intern("T".to_string()),
+                FxHashMap::from_iter([(
+                    GenericParent::FunctionLike(interner.intern("foo".to_string())),
+                    get_string(),
+                )]),
+            )]),
+        );
+
+        let mut union = get_vec(generic_param);
+
+        expand(
+            &mut union,
+            &TypeExpansionOptions {
+                template_result: Some(&template_result),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(union, get_vec(get_string()));
+    }
+
+    #[test]
+    fn arraykey_enums_only_expand_to_int_and_string_when_asked() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let key = interner.intern("Key".to_string());
+        let arraykey = TAtomic::TArraykey { from_any: false };
+        add_enum(&mut codebase, key, arraykey.clone(), vec![]);
+
+        let enum_type = wrap_atomic(TAtomic::TEnum {
+            name: key,
+            as_type: None,
+            underlying_type: None,
+        });
+
+        let mut union = enum_type.clone();
+        expand_in(&codebase, &mut union, &TypeExpansionOptions::default());
+
+        assert_eq!(
+            union,
+            wrap_atomic(TAtomic::TEnum {
+                name: key,
+                as_type: Some(Arc::new(arraykey.clone())),
+                underlying_type: Some(Arc::new(arraykey)),
+            })
+        );
+
+        let mut union = enum_type;
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                expand_arraykey_enums: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            union.types.into_iter().collect::<FxHashSet<_>>(),
+            FxHashSet::from_iter([TAtomic::TInt, TAtomic::TString])
+        );
+    }
+
+    #[test]
+    fn opaque_type_alias_keeps_its_as_constraint() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let user_id = interner.intern("UserId".to_string());
+        let user_id_alias = add_type_alias(&mut codebase, user_id, get_int());
+        codebase.type_definitions.get_mut(&user_id).unwrap().as_type = Some(get_int());
+
+        let mut union = wrap_atomic(user_id_alias);
+
+        expand_in(
+            &codebase,
+            &mut union,
+            &TypeExpansionOptions {
+                expand_type_aliases: false,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            union,
+            wrap_atomic(TAtomic::TTypeAlias {
+                name: user_id,
+                newtype: false,
+                type_params: None,
+                as_type: Some(Box::new(get_int())),
+            })
+        );
+
+        let is_contained_by = |container_type: &TUnion| {
+            union_type_comparator::is_contained_by(
+                &codebase,
+                &FilePath(StrId::EMPTY),
+                &union,
+                container_type,
+                false,
+                false,
+                false,
+                &mut TypeComparisonResult::new(),
+            )
+        };
+
+        assert!(is_contained_by(&get_int()));
+        assert!(!is_contained_by(&get_string()));
+    }
+
+    #[test]
+    fn expand_union_iter_only_expands_pulled_atomics() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let aliases = ["A", "B", "C"]
+            .into_iter()
+            .map(|name| {
+                let name = interner.intern(name.to_string());
+                add_type_alias(&mut codebase, name, get_int())
+            })
+            .collect::<Vec<_>>();
+
+        let options = TypeExpansionOptions::default();
+        let mut data_flow_graph = DataFlowGraph::new(GraphKind::FunctionBody);
+        let mut cost = 0;
+        let mut expanded_atomics = expand_union_iter(
+            &codebase,
+            &None,
+            &FilePath(StrId::EMPTY),
+            aliases.clone(),
+            &options,
+            &mut data_flow_graph,
+            &mut cost,
+        );
+
+        assert_eq!(expanded_atomics.next(), Some(TAtomic::TInt));
+        assert_eq!(expanded_atomics.remaining_atomics.as_slice(), &aliases[1..]);
+        assert!(expanded_atomics.pending_replacements.is_empty());
+    }
+
+    /// Adds a class whose `T` type constant is a union of aliases, and returns `Foo::T`
+    fn add_class_with_type_constant(
+        interner: &mut Interner,
+        codebase: &mut CodebaseInfo,
+        alias_types: Vec<TUnion>,
+    ) -> TUnion {
+        let class_name = interner.intern("Foo".to_string());
+        let member_name = interner.intern("T".to_string());
+
+        let aliases = alias_types
+            .into_iter()
+            .enumerate()
+            .map(|(i, alias_type)| {
+                let alias_name = interner.intern(format!("Alias{}", i));
+                add_type_alias(codebase, alias_name, alias_type)
+            })
+            .collect();
+
+        let mut class_info = ClassLikeInfo::new(
+            class_name,
+            get_pos(),
+            MetaStart {
+                start_offset: 0,
+                start_line: 1,
+                start_column: 1,
+            },
+            get_pos(),
+        );
+        class_info.type_constants.insert(
+            member_name,
+            ClassConstantType::Concrete(TUnion::new(aliases)),
+        );
+        codebase.classlike_infos.insert(class_name, class_info);
+
+        wrap_atomic(TAtomic::TClassTypeConstant {
+            class_type: Box::new(TAtomic::TNamedObject {
+                name: class_name,
+                type_params: None,
+                is_this: false,
+                extra_types: None,
+                remapped_params: false,
+            }),
+            member_name,
+            as_type: Box::new(get_mixed()),
+        })
+    }
+
+    /// Expands the union in the given codebase, returning the result and its cost
+    fn expand_with_cost(codebase: &CodebaseInfo, union: &TUnion) -> (TUnion, u32) {
+        let mut union = union.clone();
+        let mut cost = 0;
+
+        expand_union(
+            codebase,
+            &None,
+            &FilePath(StrId::EMPTY),
+            &mut union,
+            &TypeExpansionOptions::default(),
+            &mut DataFlowGraph::new(GraphKind::FunctionBody),
+            &mut cost,
+        );
+
+        (union, cost)
+    }
+
+    #[test]
+    fn cached_type_constant_matches_a_fresh_expansion() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let type_constant = add_class_with_type_constant(
+            &mut interner,
+            &mut codebase,
+            vec![get_int(), get_string()],
+        );
+
+        let fresh_expansion = expand_with_cost(&codebase, &type_constant);
+        assert_eq!(fresh_expansion.0.types.len(), 2);
+        assert!(fresh_expansion.0.types.contains(&TAtomic::TInt));
+        assert!(fresh_expansion.0.types.contains(&TAtomic::TString));
+
+        // the second expansion is served from the cache, but is indistinguishable
+        // from the first, cost included
+        assert_eq!(expand_with_cost(&codebase, &type_constant), fresh_expansion);
+
+        clear_type_constant_cache();
+        assert_eq!(expand_with_cost(&codebase, &type_constant), fresh_expansion);
+    }
+
+    #[test]
+    fn cached_type_constants_are_not_reused_once_invalidated() {
+        let mut interner = Interner::default();
+        let mut codebase = CodebaseInfo::new();
+        let type_constant =
+            add_class_with_type_constant(&mut interner, &mut codebase, vec![get_int()]);
+        let alias_name = interner.get("Alias0").unwrap();
+
+        assert_eq!(expand_with_cost(&codebase, &type_constant).0, get_int());
+
+        // without invalidation, the cached expansion hides the alias's new definition
+        codebase
+            .type_definitions
+            .get_mut(&alias_name)
+            .unwrap()
+            .actual_type = get_string();
+        assert_eq!(expand_with_cost(&codebase, &type_constant).0, get_int());
+
+        clear_type_constant_cache();
+        assert_eq!(expand_with_cost(&codebase, &type_constant).0, get_string());
+
+        codebase
+            .type_definitions
+            .get_mut(&alias_name)
+            .unwrap()
+            .actual_type = get_int();
+        codebase.bump_generation();
+        assert_eq!(expand_with_cost(&codebase, &type_constant).0, get_int());
+
+        // another codebase never sees this one's entries
+        let mut other_codebase = codebase.clone();
+        other_codebase.bump_generation();
+        other_codebase
+            .type_definitions
+            .get_mut(&alias_name)
+            .unwrap()
+            .actual_type = get_string();
+        assert_eq!(
+            expand_with_cost(&other_codebase, &type_constant).0,
+            get_string()
+        );
+    }
+}
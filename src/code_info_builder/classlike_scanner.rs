@@ -615,6 +615,15 @@ pub(crate) fn scan(
             StrId::HAKANA_NOT_TEST_ONLY => {
                 storage.is_production_code = true;
             }
+            StrId::DEPRECATED => {
+                storage.is_deprecated = true;
+
+                if let Some(attribute_param_expr) = user_attribute.params.first() {
+                    if let aast::Expr_::String(str) = &attribute_param_expr.2 {
+                        storage.deprecation_message = Some(interner.intern(str.to_string()));
+                    }
+                }
+            }
             _ => {}
         }
 
@@ -1022,6 +1031,7 @@ fn visit_class_const_declaration(
         allow_non_exclusive_enum_values: false,
         suppressed_issues: suppressed_issues,
         defining_class: *class_name,
+        user_defined: classlike_storage.user_defined,
     };
 
     for user_attribute in &const_node.user_attributes {
@@ -418,6 +418,16 @@ pub(crate) fn get_functionlike(
             StrId::OVERRIDE => {
                 functionlike_info.overriding = true;
             }
+            StrId::DEPRECATED => {
+                functionlike_info.deprecated = true;
+
+                if let Some(attribute_param_expr) = user_attribute.params.first() {
+                    if let aast::Expr_::String(str) = &attribute_param_expr.2 {
+                        functionlike_info.deprecation_message =
+                            Some(interner.intern(str.to_string()));
+                    }
+                }
+            }
             StrId::HAKANA_IGNORE_NORETURN_CALLS => {
                 functionlike_info.ignore_noreturn_calls = true;
             }
@@ -1,11 +1,12 @@
 use std::hash::Hash;
 use std::sync::Arc;
 
+use crate::functionlike_scanner::adjust_location_from_comments;
 use crate::typehint_resolver::get_type_from_hint;
 use hakana_aast_helper::Uses;
 use hakana_code_info::attribute_info::AttributeInfo;
 use hakana_code_info::file_info::{FileInfo, ParserError};
-use hakana_code_info::functionlike_info::FunctionLikeInfo;
+use hakana_code_info::functionlike_info::{FunctionLikeInfo, MetaStart};
 use hakana_code_info::issue::IssueKind;
 use hakana_code_info::t_atomic::TDict;
 use hakana_code_info::t_union::TUnion;
@@ -145,6 +146,25 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
 
         let definition_location = HPos::new(&gc.name.0, self.file_source.file_path);
 
+        let def_pos = HPos::new(&gc.span, self.file_source.file_path);
+
+        let mut meta_start = MetaStart {
+            start_offset: def_pos.start_offset,
+            start_line: def_pos.start_line,
+            start_column: def_pos.start_column,
+        };
+
+        // lets a constant that's part of a public API opt out of unused constant checks
+        let mut suppressed_issues = vec![];
+
+        adjust_location_from_comments(
+            self.file_source.comments,
+            &mut meta_start,
+            &self.file_source,
+            &mut suppressed_issues,
+            self.all_custom_issues,
+        );
+
         let uses_hash = get_uses_hash(self.uses.symbol_uses.get(&name).unwrap_or(&vec![]));
 
         self.ast_nodes.push(DefSignatureNode {
@@ -187,8 +207,9 @@ impl<'ast> Visitor<'ast> for Scanner<'_> {
                 unresolved_value: None,
                 is_abstract: false,
                 allow_non_exclusive_enum_values: false,
-                suppressed_issues: vec![],
+                suppressed_issues,
                 defining_class: StrId::EMPTY,
+                user_defined: self.user_defined,
             },
         );
 
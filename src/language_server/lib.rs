@@ -6,13 +6,20 @@ use std::time::Duration;
 use hakana_analyzer::config::{self, Config};
 use hakana_analyzer::custom_hook::CustomHook;
 use hakana_code_info::analysis_result::AnalysisResult;
-use hakana_code_info::code_location::HPos;
+use hakana_code_info::code_location::{FilePath, HPos};
+use hakana_code_info::codebase_info::CodebaseInfo;
+use hakana_code_info::functionlike_identifier::FunctionLikeIdentifier;
+use hakana_code_info::issue::IssueSeverity;
+use hakana_code_info::method_identifier::MethodIdentifier;
+use hakana_code_info::ttype::type_expander::{
+    ExpandedSignature, StaticClassType, TypeExpansionOptions,
+};
 use hakana_orchestrator::file::FileStatus;
 #[cfg(target_arch = "wasm32")]
 use hakana_orchestrator::SuccessfulScanData;
 #[cfg(not(target_arch = "wasm32"))]
 use hakana_orchestrator::{scan_and_analyze_async, SuccessfulScanData};
-use hakana_str::Interner;
+use hakana_str::{Interner, StrId};
 use rustc_hash::{FxHashMap, FxHashSet};
 use tokio::sync::RwLock;
 use tokio::time::sleep;
@@ -62,6 +69,31 @@ impl Backend {
 
         offset
     }
+
+    /// Finds the symbol (or class member) referenced at the given position, using the
+    /// definition locations collected in the last analysis
+    fn get_symbol_at_position(
+        &self,
+        file_path: &str,
+        position: Position,
+        scan_data: &SuccessfulScanData,
+        analysis_result: &AnalysisResult,
+    ) -> Option<(StrId, StrId)> {
+        let file_contents = std::fs::read_to_string(file_path).ok()?;
+        let offset = self.position_to_offset(&file_contents, position) as u32;
+
+        let file_path = FilePath(scan_data.interner.get(file_path)?);
+
+        // check for approximate position matches (within a range)
+        analysis_result
+            .definition_locations
+            .get(&file_path)?
+            .iter()
+            .find(|((start_offset, end_offset), _)| {
+                offset >= *start_offset && offset <= *end_offset
+            })
+            .map(|(_, symbol)| *symbol)
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -85,6 +117,7 @@ impl LanguageServer for Backend {
                 } else {
                     Some(OneOf::Left(true))
                 },
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
                 ..ServerCapabilities::default()
             },
             ..InitializeResult::default()
@@ -204,7 +237,41 @@ impl LanguageServer for Backend {
         }
     }
 
-    async fn hover(&self, _: HoverParams) -> Result<Option<Hover>> {
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params.position;
+        let uri = params.text_document_position_params.text_document.uri;
+        let file_path = uri.path().to_string();
+
+        let scan_data_guard = self.previous_scan_data.read().await;
+        let analysis_result_guard = self.previous_analysis_result.read().await;
+        if let (Some(scan_data), Some(analysis_result)) =
+            (scan_data_guard.as_ref(), analysis_result_guard.as_ref())
+        {
+            if let Some((classlike_name, member_name)) =
+                self.get_symbol_at_position(&file_path, position, scan_data, analysis_result)
+            {
+                let functionlike_id = if member_name == StrId::EMPTY {
+                    FunctionLikeIdentifier::Function(classlike_name)
+                } else {
+                    FunctionLikeIdentifier::Method(classlike_name, member_name)
+                };
+
+                if let Some(signature) = scan_data.codebase.expand_functionlike_signature(
+                    &functionlike_id,
+                    &get_signature_expansion_options(&functionlike_id, &scan_data.codebase),
+                ) {
+                    return Ok(Some(Hover {
+                        contents: HoverContents::Scalar(MarkedString::String(format_signature(
+                            &functionlike_id,
+                            &signature,
+                            &scan_data.interner,
+                        ))),
+                        range: None,
+                    }));
+                }
+            }
+        }
+
         Ok(None)
     }
 
@@ -218,39 +285,17 @@ impl LanguageServer for Backend {
 
         let scan_data_guard = self.previous_scan_data.read().await;
         let analysis_result_guard = self.previous_analysis_result.read().await;
-        if let Some(scan_data) = scan_data_guard.as_ref() {
-            // Convert LSP position to offset
-            if let Ok(file_contents) = std::fs::read_to_string(&file_path) {
-                let offset = self.position_to_offset(&file_contents, position);
-
-                // Create FilePath from the file path string
-                if let Some(file_path_id) = scan_data.interner.get(&file_path) {
-                    let file_path_obj = hakana_code_info::code_location::FilePath(file_path_id);
-
-                    // Check member definition locations (methods, properties, constants)
-                    if let Some(analysis_result) = analysis_result_guard.as_ref() {
-                        if let Some(definition_locations) =
-                            analysis_result.definition_locations.get(&file_path_obj)
-                        {
-                            // check for approximate position matches (within a range)
-                            for ((start_offset, end_offset), (classlike_name, member_name)) in
-                                definition_locations
-                            {
-                                if (offset as u32) >= *start_offset
-                                    && (offset as u32) <= *end_offset
-                                {
-                                    if let Some(pos) = scan_data
-                                        .codebase
-                                        .get_symbol_pos(classlike_name, member_name)
-                                    {
-                                        return Ok(pos_to_offset(pos, &scan_data.interner));
-                                    }
-
-                                    return Ok(None);
-                                }
-                            }
-                        }
-                    }
+        if let (Some(scan_data), Some(analysis_result)) =
+            (scan_data_guard.as_ref(), analysis_result_guard.as_ref())
+        {
+            if let Some((classlike_name, member_name)) =
+                self.get_symbol_at_position(&file_path, position, scan_data, analysis_result)
+            {
+                if let Some(pos) = scan_data
+                    .codebase
+                    .get_symbol_pos(&classlike_name, &member_name)
+                {
+                    return Ok(pos_to_offset(pos, &scan_data.interner));
                 }
             }
         }
@@ -263,6 +308,72 @@ impl LanguageServer for Backend {
     }
 }
 
+/// Resolves `self`, `static` and `parent` in a method's signature against the class the
+/// method was referenced through, like the analyzer does inside the method itself
+fn get_signature_expansion_options(
+    functionlike_id: &FunctionLikeIdentifier,
+    codebase: &CodebaseInfo,
+) -> TypeExpansionOptions<'static> {
+    let FunctionLikeIdentifier::Method(classlike_name, method_name) = functionlike_id else {
+        return TypeExpansionOptions::default();
+    };
+
+    let declaring_class = codebase
+        .get_declaring_method_id(&MethodIdentifier(*classlike_name, *method_name))
+        .0;
+
+    TypeExpansionOptions {
+        self_class: Some(declaring_class),
+        static_class_type: StaticClassType::Name(*classlike_name),
+        parent_class: codebase
+            .classlike_infos
+            .get(&declaring_class)
+            .and_then(|classlike_info| classlike_info.direct_parent_class),
+        ..Default::default()
+    }
+}
+
+fn format_signature(
+    functionlike_id: &FunctionLikeIdentifier,
+    signature: &ExpandedSignature,
+    interner: &Interner,
+) -> String {
+    let params = signature
+        .params
+        .iter()
+        .map(|param| {
+            let mut param_str = String::new();
+            if param.is_inout {
+                param_str += "inout ";
+            }
+            if let Some(signature_type) = &param.signature_type {
+                param_str += &signature_type.get_id(Some(interner));
+                param_str += " ";
+            }
+            if param.is_variadic {
+                param_str += "...";
+            }
+            param_str += interner.lookup(&param.name.0);
+            if param.is_optional {
+                param_str += " = _";
+            }
+            param_str
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "function {}({}){}",
+        functionlike_id.to_string(interner),
+        params,
+        signature
+            .return_type
+            .as_ref()
+            .map(|return_type| format!(": {}", return_type.get_id(Some(interner))))
+            .unwrap_or_default()
+    )
+}
+
 fn pos_to_offset(def_pos: HPos, interner: &Interner) -> Option<GotoDefinitionResponse> {
     if let Ok(def_uri) = Url::from_file_path(interner.lookup(&def_pos.file_path.0)) {
         return Some(GotoDefinitionResponse::Scalar(Location {
@@ -346,7 +457,12 @@ impl Backend {
                                     character: emitted_issue.pos.end_column as u32 - 1,
                                 },
                             },
-                            Some(DiagnosticSeverity::ERROR),
+                            Some(
+                                match self.analysis_config.get_issue_severity(&emitted_issue.kind) {
+                                    IssueSeverity::Error => DiagnosticSeverity::ERROR,
+                                    IssueSeverity::Warning => DiagnosticSeverity::WARNING,
+                                },
+                            ),
                             Some(NumberOrString::String(emitted_issue.kind.to_string())),
                             Some("Hakana".to_string()),
                             emitted_issue.description.clone(),
@@ -447,7 +563,8 @@ pub fn get_config(
     config.find_unused_definitions = true;
     config.ignore_mixed_issues = true;
     config.ast_diff = true;
-    config.collect_goto_definition_locations = !cfg!(target_os = "linux");
+    // hovering needs the locations too, so they're collected on every platform
+    config.collect_goto_definition_locations = true;
 
     config.hooks = plugins;
 
@@ -7,12 +7,15 @@ use hakana_code_info::analysis_result::AnalysisResult;
 use hakana_code_info::code_location::{FilePath, HPos};
 use hakana_code_info::codebase_info::CodebaseInfo;
 use hakana_code_info::file_info::ParserError;
+use hakana_code_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_code_info::issue::{Issue, IssueKind};
+use hakana_code_info::method_identifier::MethodIdentifier;
 use hakana_code_info::symbol_references::SymbolReferences;
 use hakana_code_info::FileSource;
 use hakana_str::{Interner, StrId};
 use indicatif::{ProgressBar, ProgressStyle};
 use oxidized::aast;
+use oxidized::pos::Pos;
 use oxidized::scoured_comments::ScouredComments;
 use rustc_hash::{FxHashMap, FxHashSet};
 
@@ -31,7 +34,7 @@ pub fn analyze_files(
     logger: Arc<Logger>,
     file_analysis_time: &mut Duration,
 ) -> io::Result<()> {
-    let mut group_size = threads as usize;
+    let mut group_size = config.analysis_threads.unwrap_or(threads).max(1) as usize;
 
     let mut path_groups = FxHashMap::default();
 
@@ -76,6 +79,16 @@ pub fn analyze_files(
 
     let arc_file_analysis_time = Arc::new(Mutex::new(Duration::default()));
 
+    // each thread's results are merged into the shared result, so they need to use
+    // the same graph kind or the merge will panic
+    let graph_kind = analysis_result.lock().unwrap().program_dataflow_graph.kind;
+    let traversal_counts = analysis_result
+        .lock()
+        .unwrap()
+        .program_dataflow_graph
+        .traversal_counts
+        .clone();
+
     for (_, path_group) in path_groups {
         let scan_data = scan_data.clone();
 
@@ -92,6 +105,8 @@ pub fn analyze_files(
 
         let arc_file_analysis_time = arc_file_analysis_time.clone();
 
+        let traversal_counts = traversal_counts.clone();
+
         let handle = std::thread::spawn(move || {
             let codebase = &scan_data.codebase;
             let interner = &scan_data.interner;
@@ -100,7 +115,8 @@ pub fn analyze_files(
             let mut file_analysis_time = Duration::default();
 
             let mut new_analysis_result =
-                AnalysisResult::new(analysis_config.graph_kind, SymbolReferences::new());
+                AnalysisResult::new(graph_kind, SymbolReferences::new());
+            new_analysis_result.program_dataflow_graph.traversal_counts = traversal_counts;
 
             for str_path in &pgc {
                 let file_path = FilePath(interner.get(str_path).unwrap());
@@ -149,6 +165,193 @@ pub fn analyze_files(
     Ok(())
 }
 
+/// Analyzes a single function or method, along with the functions and methods it
+/// references directly, against an already-populated codebase. Every other definition
+/// in their files is skipped, so only issues from those functions (and, for methods,
+/// the enclosing classes' own checks) are reported.
+///
+/// Returns `None` if the function, or any of its dependencies, can't be loaded.
+pub fn analyze_function(
+    id: &FunctionLikeIdentifier,
+    scan_data: &SuccessfulScanData,
+    config: &Arc<Config>,
+) -> Option<AnalysisResult> {
+    let symbol = match id {
+        FunctionLikeIdentifier::Function(name) => (*name, StrId::EMPTY),
+        FunctionLikeIdentifier::Method(classlike_name, method_name) => {
+            (*classlike_name, *method_name)
+        }
+        FunctionLikeIdentifier::Closure(..) => return None,
+    };
+
+    let def_location = get_def_location(id, &scan_data.codebase)?;
+
+    // analyzing the function tells us what it references
+    let mut function_result = AnalysisResult::new(config.graph_kind, SymbolReferences::new());
+
+    analyze_defs_in_file(
+        def_location.file_path,
+        &[def_location],
+        scan_data,
+        config,
+        &mut function_result,
+    )?;
+
+    let mut def_locations_by_file: FxHashMap<FilePath, Vec<HPos>> = FxHashMap::default();
+
+    if let Some(referenced_symbols) = function_result
+        .symbol_references
+        .symbol_references_to_symbols
+        .get(&symbol)
+    {
+        for (symbol_name, member_name) in referenced_symbols {
+            let dependency_id = if *member_name == StrId::EMPTY {
+                FunctionLikeIdentifier::Function(*symbol_name)
+            } else {
+                FunctionLikeIdentifier::Method(*symbol_name, *member_name)
+            };
+
+            // classes, constants and the like aren't analyzed on their own
+            if let Some(dependency_location) = get_def_location(&dependency_id, &scan_data.codebase)
+            {
+                // recursive calls don't need the function analyzed a second time
+                if dependency_location == def_location {
+                    continue;
+                }
+
+                let file_locations = def_locations_by_file
+                    .entry(dependency_location.file_path)
+                    .or_default();
+
+                if !file_locations.contains(&dependency_location) {
+                    file_locations.push(dependency_location);
+                }
+            }
+        }
+    }
+
+    let mut dependencies_result = AnalysisResult::new(config.graph_kind, SymbolReferences::new());
+
+    for (file_path, def_locations) in def_locations_by_file {
+        analyze_defs_in_file(
+            file_path,
+            &def_locations,
+            scan_data,
+            config,
+            &mut dependencies_result,
+        )?;
+    }
+
+    // a method of the function's own class repeats the class's own checks
+    for (file_path, issues) in dependencies_result.emitted_issues.iter_mut() {
+        if let Some(function_issues) = function_result.emitted_issues.get(file_path) {
+            issues.retain(|issue| !function_issues.contains(issue));
+        }
+    }
+
+    function_result.extend(dependencies_result);
+
+    if config.char_columns {
+        set_char_columns(&mut function_result, &scan_data.interner);
+    }
+
+    Some(function_result)
+}
+
+/// Recounts every issue's columns in characters rather than bytes. Columns are only
+/// converted once all issues, cached ones included, have been collected, so cached
+/// results keep byte columns.
+pub(crate) fn set_char_columns(analysis_result: &mut AnalysisResult, interner: &Interner) {
+    for (file_path, issues) in analysis_result.emitted_issues.iter_mut() {
+        let Ok(file_contents) = fs::read_to_string(interner.lookup(&file_path.0)) else {
+            continue;
+        };
+
+        // issues for files that couldn't be parsed have no line to count along
+        for issue in issues.iter_mut().filter(|issue| issue.pos.start_line > 0) {
+            issue.pos.set_char_columns(&file_contents);
+        }
+    }
+}
+
+fn get_def_location(id: &FunctionLikeIdentifier, codebase: &CodebaseInfo) -> Option<HPos> {
+    let functionlike_info = match id {
+        FunctionLikeIdentifier::Function(name) => {
+            codebase.functionlike_infos.get(&(*name, StrId::EMPTY))?
+        }
+        FunctionLikeIdentifier::Method(classlike_name, method_name) => {
+            codebase.get_declaring_method(&MethodIdentifier(*classlike_name, *method_name))?
+        }
+        FunctionLikeIdentifier::Closure(..) => return None,
+    };
+
+    if functionlike_info.user_defined {
+        Some(functionlike_info.def_location)
+    } else {
+        None
+    }
+}
+
+/// Analyzes only the definitions of the given file that contain one of the locations
+fn analyze_defs_in_file(
+    file_path: FilePath,
+    def_locations: &[HPos],
+    scan_data: &SuccessfulScanData,
+    config: &Arc<Config>,
+    analysis_result: &mut AnalysisResult,
+) -> Option<()> {
+    let interner = &scan_data.interner;
+    let resolved_names = scan_data.resolved_names.get(&file_path)?;
+    let str_path = interner.lookup(&file_path.0).to_string();
+
+    let (mut program, comments, file_contents) = get_aast_for_path(file_path, &str_path).ok()?;
+
+    retain_defs_containing(&mut program.0, def_locations);
+
+    analyze_loaded_ast(
+        &str_path,
+        file_path,
+        &(program, comments),
+        &file_contents,
+        resolved_names,
+        &scan_data.codebase,
+        interner,
+        config,
+        analysis_result,
+    );
+
+    Some(())
+}
+
+fn retain_defs_containing(defs: &mut Vec<aast::Def<(), ()>>, def_locations: &[HPos]) {
+    let contains_def = |pos: &Pos| {
+        def_locations.iter().any(|def_location| {
+            pos.start_offset() as u32 <= def_location.start_offset
+                && pos.end_offset() as u32 >= def_location.end_offset
+        })
+    };
+
+    defs.retain_mut(|def| match def {
+        aast::Def::Fun(boxed) => contains_def(&boxed.fun.span),
+        aast::Def::Class(boxed) => {
+            if !contains_def(&boxed.span) {
+                return false;
+            }
+
+            boxed.methods.retain(|method| contains_def(&method.span));
+            true
+        }
+        aast::Def::Namespace(boxed) => {
+            retain_defs_containing(&mut boxed.1, def_locations);
+            true
+        }
+        aast::Def::NamespaceUse(_)
+        | aast::Def::SetNamespaceEnv(_)
+        | aast::Def::FileAttributes(_) => true,
+        _ => false,
+    });
+}
+
 fn analyze_file(
     file_path: FilePath,
     str_path: &String,
@@ -199,8 +402,8 @@ fn analyze_file(
         }
     }
 
-    let aast = match get_aast_for_path(file_path, str_path) {
-        Ok(aast) => (aast.0, aast.1),
+    let (aast, file_contents) = match get_aast_for_path(file_path, str_path) {
+        Ok(aast) => ((aast.0, aast.1), aast.2),
         Err(err) => {
             analysis_result.has_invalid_hack_files = true;
             analysis_result.emitted_issues.insert(
@@ -250,6 +453,7 @@ fn analyze_file(
         str_path,
         file_path,
         &aast,
+        &file_contents,
         resolved_names,
         codebase,
         interner,
@@ -264,6 +468,7 @@ fn analyze_loaded_ast(
     str_path: &String,
     file_path: FilePath,
     aast: &(aast::Program<(), ()>, ScouredComments),
+    file_contents: &str,
     resolved_names: &FxHashMap<u32, StrId>,
     codebase: &CodebaseInfo,
     interner: &Interner,
@@ -276,11 +481,9 @@ fn analyze_loaded_ast(
         file_path,
         hh_fixmes: &aast.1.fixmes,
         comments: &aast.1.comments,
-        file_contents: if !config.migration_symbols.is_empty() {
-            match fs::read_to_string(str_path) {
-                Ok(str_file) => str_file,
-                Err(_) => panic!("Could not read {}", str_path),
-            }
+        // only migrations and fixes need the source text
+        file_contents: if !config.migration_symbols.is_empty() || !config.issues_to_fix.is_empty() {
+            file_contents.to_string()
         } else {
             "".to_string()
         },
@@ -299,3 +502,213 @@ fn analyze_loaded_ast(
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_and_analyze;
+
+    #[test]
+    fn analyze_function_only_analyzes_the_function_and_its_dependencies() {
+        let root_dir =
+            std::env::temp_dir().join(format!("hakana-analyze-function-{}", std::process::id()));
+        fs::create_dir_all(&root_dir).unwrap();
+
+        fs::write(
+            root_dir.join("target.hack"),
+            "function target(): string {\n    return helper();\n}\n\n\
+            function sibling(): int {\n    return \"a\";\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root_dir.join("helper.hack"),
+            "function helper(): int {\n    return \"b\";\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root_dir.join("unrelated.hack"),
+            "function unrelated(): int {\n    return \"c\";\n}\n",
+        )
+        .unwrap();
+
+        let root_dir_str = root_dir.to_str().unwrap().to_string();
+        let config = Arc::new(Config::new(root_dir_str.clone(), FxHashSet::default()));
+
+        let (_, scan_data) = scan_and_analyze(
+            Vec::new(),
+            None,
+            None,
+            config.clone(),
+            None,
+            1,
+            Arc::new(Logger::DevNull),
+            "",
+            Interner::default(),
+            None,
+            None,
+            None,
+            || {},
+        )
+        .unwrap();
+
+        let target = FunctionLikeIdentifier::Function(scan_data.interner.get("target").unwrap());
+        let analysis_result = analyze_function(&target, &scan_data, &config).unwrap();
+
+        // a dependency that can no longer be loaded makes the result incomplete
+        fs::remove_file(root_dir.join("helper.hack")).unwrap();
+        assert!(analyze_function(&target, &scan_data, &config).is_none());
+
+        fs::remove_dir_all(&root_dir).unwrap();
+
+        let issues = analysis_result
+            .get_all_issues(&scan_data.interner, &root_dir_str, true)
+            .into_iter()
+            .map(|(file_path, issues)| {
+                (
+                    file_path,
+                    issues
+                        .iter()
+                        .map(|issue| issue.pos.start_line)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // neither sibling() nor unrelated() is analyzed
+        assert_eq!(
+            issues,
+            vec![
+                ("helper.hack".to_string(), vec![2]),
+                ("target.hack".to_string(), vec![2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn analyze_function_reports_char_columns() {
+        let root_dir =
+            std::env::temp_dir().join(format!("hakana-char-columns-{}", std::process::id()));
+        fs::create_dir_all(&root_dir).unwrap();
+
+        fs::write(
+            root_dir.join("target.hack"),
+            "function target(): int {\n    $a = \"\u{e9}\"; return $a;\n}\n",
+        )
+        .unwrap();
+
+        let mut config = Config::new(root_dir.to_str().unwrap().to_string(), FxHashSet::default());
+        config.char_columns = true;
+        let config = Arc::new(config);
+
+        let (_, scan_data) = scan_and_analyze(
+            Vec::new(),
+            None,
+            None,
+            config.clone(),
+            None,
+            1,
+            Arc::new(Logger::DevNull),
+            "",
+            Interner::default(),
+            None,
+            None,
+            None,
+            || {},
+        )
+        .unwrap();
+
+        let target = FunctionLikeIdentifier::Function(scan_data.interner.get("target").unwrap());
+        let analysis_result = analyze_function(&target, &scan_data, &config).unwrap();
+
+        fs::remove_dir_all(&root_dir).unwrap();
+
+        let columns = analysis_result
+            .emitted_issues
+            .values()
+            .flatten()
+            .map(|issue| (issue.pos.start_line, issue.pos.start_column))
+            .collect::<Vec<_>>();
+
+        // the returned $a starts at byte 23, but the é before it is a single character
+        assert_eq!(columns, vec![(2, 22)]);
+    }
+
+    fn get_issues_with_analysis_threads(root_dir: &str, analysis_threads: u8) -> Vec<String> {
+        let mut config = Config::new(root_dir.to_string(), FxHashSet::default());
+        config.analysis_threads = Some(analysis_threads);
+
+        let (analysis_result, scan_data) = scan_and_analyze(
+            Vec::new(),
+            None,
+            None,
+            Arc::new(config),
+            None,
+            1,
+            Arc::new(Logger::DevNull),
+            "",
+            Interner::default(),
+            None,
+            None,
+            None,
+            || {},
+        )
+        .unwrap();
+
+        analysis_result
+            .get_all_issues(&scan_data.interner, root_dir, true)
+            .into_iter()
+            .flat_map(|(file_path, issues)| {
+                issues
+                    .into_iter()
+                    .map(|issue| {
+                        format!(
+                            "{}:{} {} {}",
+                            file_path,
+                            issue.pos.start_line,
+                            issue.kind.to_string(),
+                            issue.description
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn analysis_thread_count_does_not_change_issues() {
+        let root_dir =
+            std::env::temp_dir().join(format!("hakana-analysis-threads-{}", std::process::id()));
+        fs::create_dir_all(&root_dir).unwrap();
+
+        fs::write(
+            root_dir.join("file0.hack"),
+            "function foo0(): int {\n    return 0;\n}\n",
+        )
+        .unwrap();
+
+        // enough files that four threads each get their own group
+        for i in 1..20 {
+            fs::write(
+                root_dir.join(format!("file{}.hack", i)),
+                format!(
+                    "function foo{}(): string {{\n    return foo{}();\n}}\n",
+                    i,
+                    (i + 1) % 20
+                ),
+            )
+            .unwrap();
+        }
+
+        let root_dir_str = root_dir.to_str().unwrap().to_string();
+
+        let single_threaded_issues = get_issues_with_analysis_threads(&root_dir_str, 1);
+        let multi_threaded_issues = get_issues_with_analysis_threads(&root_dir_str, 4);
+
+        fs::remove_dir_all(&root_dir).unwrap();
+
+        // only foo19() returns the int from foo0()
+        assert_eq!(single_threaded_issues.len(), 1);
+        assert!(single_threaded_issues[0].starts_with("file19.hack:2 "));
+        assert_eq!(single_threaded_issues, multi_threaded_issues);
+    }
+}
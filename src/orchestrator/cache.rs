@@ -7,6 +7,7 @@ use hakana_str::Interner;
 use hakana_str::StrId;
 use rustc_hash::FxHashMap;
 use std::fs;
+use std::io;
 use std::path::Path;
 
 use crate::file::VirtualFileSystem;
@@ -71,10 +72,16 @@ pub(crate) fn load_cached_existing_references(
 ) -> Option<SymbolReferences> {
     if Path::new(existing_references_path).exists() && use_codebase_cache {
         logger.log_sync("Deserializing existing references cache");
-        let serialized = fs::read(existing_references_path)
-            .unwrap_or_else(|_| panic!("Could not read file {}", &existing_references_path));
-        if let Ok(d) = bincode::deserialize::<SymbolReferences>(&serialized) {
-            return Some(d);
+        let serialized = match read_analysis_cache_file(existing_references_path) {
+            Ok(serialized) => serialized,
+            Err(reason) => {
+                log_invalid_cache(existing_references_path, &reason, logger);
+                return None;
+            }
+        };
+        match bincode::deserialize::<SymbolReferences>(&serialized) {
+            Ok(d) => return Some(d),
+            Err(error) => log_invalid_cache(existing_references_path, &error.to_string(), logger),
         }
     }
 
@@ -88,16 +95,72 @@ pub(crate) fn load_cached_existing_issues(
 ) -> Option<FxHashMap<FilePath, Vec<Issue>>> {
     if Path::new(existing_issues_path).exists() && use_codebase_cache {
         logger.log_sync("Deserializing existing issues cache");
-        let serialized = fs::read(existing_issues_path)
-            .unwrap_or_else(|_| panic!("Could not read file {}", &existing_issues_path));
-        if let Ok(d) = bincode::deserialize::<FxHashMap<FilePath, Vec<Issue>>>(&serialized) {
-            return Some(d);
+        let serialized = match read_analysis_cache_file(existing_issues_path) {
+            Ok(serialized) => serialized,
+            Err(reason) => {
+                log_invalid_cache(existing_issues_path, &reason, logger);
+                return None;
+            }
+        };
+        match bincode::deserialize::<FxHashMap<FilePath, Vec<Issue>>>(&serialized) {
+            Ok(d) => return Some(d),
+            Err(error) => log_invalid_cache(existing_issues_path, &error.to_string(), logger),
         }
     }
 
     None
 }
 
+/// Prefixed to the issues and references caches, followed by the payload length, so
+/// that a file left behind by an older Hakana or a partial write is rejected on load.
+const ANALYSIS_CACHE_HEADER: &[u8; 12] = b"hakana-ac-v1";
+
+/// Writes an analysis cache file via a temporary sibling file that's renamed into
+/// place, so an interrupted run never leaves a half-written cache behind.
+pub(crate) fn write_analysis_cache_file(path: &str, serialized: &[u8]) -> io::Result<()> {
+    let temp_path = format!("{}.tmp", path);
+
+    let mut contents = Vec::with_capacity(ANALYSIS_CACHE_HEADER.len() + 8 + serialized.len());
+    contents.extend_from_slice(ANALYSIS_CACHE_HEADER);
+    contents.extend_from_slice(&(serialized.len() as u64).to_le_bytes());
+    contents.extend_from_slice(serialized);
+
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Returns the payload of an analysis cache file, or why the file can't be used
+fn read_analysis_cache_file(path: &str) -> Result<Vec<u8>, String> {
+    let mut contents = fs::read(path).map_err(|error| error.to_string())?;
+
+    let header_len = ANALYSIS_CACHE_HEADER.len();
+
+    if contents.len() < header_len + 8 || &contents[..header_len] != ANALYSIS_CACHE_HEADER {
+        return Err("unrecognised header".to_string());
+    }
+
+    let payload_len = u64::from_le_bytes(contents[header_len..header_len + 8].try_into().unwrap());
+
+    if (contents.len() - header_len - 8) as u64 != payload_len {
+        return Err(format!(
+            "expected {} bytes, found {}",
+            payload_len,
+            contents.len() - header_len - 8
+        ));
+    }
+
+    contents.drain(..header_len + 8);
+
+    Ok(contents)
+}
+
+fn log_invalid_cache(path: &str, reason: &str, logger: &Logger) {
+    logger.log_sync(&format!(
+        "Warning: ignoring invalid cache file {} ({}), falling back to a full analysis",
+        path, reason
+    ));
+}
+
 pub(crate) fn get_file_manifest(cache_dir: &String) -> Option<VirtualFileSystem> {
     let aast_manifest_path = format!("{}/manifest", cache_dir);
 
@@ -111,3 +174,41 @@ pub(crate) fn get_file_manifest(cache_dir: &String) -> Option<VirtualFileSystem>
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_cache_falls_back_to_a_full_analysis() {
+        let cache_path = std::env::temp_dir()
+            .join(format!("hakana-truncated-cache-{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+
+        let existing_issues: FxHashMap<FilePath, Vec<Issue>> = FxHashMap::default();
+        let serialized = bincode::serialize(&existing_issues).unwrap();
+        write_analysis_cache_file(&cache_path, &serialized).unwrap();
+
+        assert_eq!(
+            read_analysis_cache_file(&cache_path),
+            Ok(serialized.clone())
+        );
+        assert!(load_cached_existing_issues(&cache_path, true, &Logger::DevNull).is_some());
+
+        let contents = fs::read(&cache_path).unwrap();
+        fs::write(&cache_path, &contents[..contents.len() - 1]).unwrap();
+
+        assert_eq!(
+            read_analysis_cache_file(&cache_path),
+            Err(format!(
+                "expected {} bytes, found {}",
+                serialized.len(),
+                serialized.len() - 1
+            ))
+        );
+        assert!(load_cached_existing_issues(&cache_path, true, &Logger::DevNull).is_none());
+
+        fs::remove_file(&cache_path).unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+use crate::analyzer::analyze_files;
+use crate::SuccessfulScanData;
+use hakana_analyzer::config::Config;
+use hakana_code_info::analysis_result::AnalysisResult;
+use hakana_code_info::code_location::FilePath;
+use hakana_code_info::issue::{Issue, IssueKind};
+use hakana_code_info::symbol_references::SymbolReferences;
+use hakana_logger::Logger;
+use rustc_hash::FxHashSet;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Re-analyzes up to `sample_size` files whose issues were carried over from a previous
+/// run, and logs every cached issue that a fresh analysis no longer produces (and every
+/// fresh issue missing from the cache). Any divergence means the diff invalidated too
+/// little. Issues are matched by kind, file and line, so a cached issue whose columns
+/// moved without its line changing isn't reported. Returns the number of divergences.
+pub(crate) fn verify_cached_issues(
+    mut scan_data: SuccessfulScanData,
+    config: &Arc<Config>,
+    analysis_result: &AnalysisResult,
+    reanalyzed_files: &FxHashSet<String>,
+    ignored_paths: &Option<FxHashSet<String>>,
+    sample_size: usize,
+    threads: u8,
+    logger: &Arc<Logger>,
+) -> io::Result<(SuccessfulScanData, usize)> {
+    let root_dir = &config.root_dir;
+
+    let mut sampled_files = scan_data
+        .file_system
+        .file_hashes_and_times
+        .keys()
+        .map(|file_path| scan_data.interner.lookup(&file_path.0).to_string())
+        .filter(|path| path.starts_with(root_dir.as_str()) && !reanalyzed_files.contains(path))
+        .collect::<Vec<_>>();
+
+    sampled_files.sort();
+    sampled_files.truncate(sample_size);
+
+    if sampled_files.is_empty() {
+        return Ok((scan_data, 0));
+    }
+
+    logger.log_sync(&format!(
+        "Verifying cached issues in {} files",
+        sampled_files.len()
+    ));
+
+    // without this the analyzer would skip every symbol we want to check
+    let safe_symbols = std::mem::take(&mut scan_data.codebase.safe_symbols);
+    let safe_symbol_members = std::mem::take(&mut scan_data.codebase.safe_symbol_members);
+
+    let arc_scan_data = Arc::new(scan_data);
+
+    let fresh_analysis_result = Arc::new(Mutex::new(AnalysisResult::new(
+        config.graph_kind,
+        SymbolReferences::new(),
+    )));
+
+    analyze_files(
+        sampled_files.clone(),
+        arc_scan_data.clone(),
+        config.clone(),
+        &fresh_analysis_result,
+        None,
+        ignored_paths,
+        threads,
+        Arc::new(Logger::DevNull),
+        &mut Duration::default(),
+    )?;
+
+    let mut scan_data = Arc::try_unwrap(arc_scan_data)
+        .map_err(|_| io::Error::other("scan data is still shared after verifying cached issues"))?;
+    scan_data.codebase.safe_symbols = safe_symbols;
+    scan_data.codebase.safe_symbol_members = safe_symbol_members;
+
+    let fresh_analysis_result = fresh_analysis_result.lock().unwrap();
+
+    let mut divergence_count = 0;
+
+    for path in &sampled_files {
+        let Some(file_path) = scan_data.interner.get(path).map(FilePath) else {
+            continue;
+        };
+
+        let cached_issues = analysis_result
+            .emitted_issues
+            .get(&file_path)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let fresh_issues = fresh_analysis_result
+            .emitted_issues
+            .get(&file_path)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for (issues, other_issues, description) in [
+            (cached_issues, fresh_issues, "Stale cached issue"),
+            (fresh_issues, cached_issues, "Issue missing from cache"),
+        ] {
+            let other_issue_keys = other_issues
+                .iter()
+                .map(get_issue_key)
+                .collect::<FxHashSet<_>>();
+
+            for issue in issues {
+                if !other_issue_keys.contains(&get_issue_key(issue)) {
+                    divergence_count += 1;
+                    log_divergence(logger, description, issue, path);
+                }
+            }
+        }
+    }
+
+    logger.log_sync(&format!(
+        "Found {} cached issue divergences",
+        divergence_count
+    ));
+
+    Ok((scan_data, divergence_count))
+}
+
+fn get_issue_key(issue: &Issue) -> (&IssueKind, FilePath, u32) {
+    (&issue.kind, issue.pos.file_path, issue.pos.start_line)
+}
+
+fn log_divergence(logger: &Logger, description: &str, issue: &Issue, path: &str) {
+    logger.log_sync(&format!(
+        "{} in {}:{}: {} - {}",
+        description,
+        path,
+        issue.pos.start_line,
+        issue.kind.to_string(),
+        issue.description
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_and_analyze;
+    use hakana_code_info::code_location::HPos;
+    use hakana_str::Interner;
+    use std::fs;
+
+    #[test]
+    fn verify_cached_issues_reports_stale_cached_issues() {
+        let root_dir =
+            std::env::temp_dir().join(format!("hakana-verify-cache-{}", std::process::id()));
+        fs::create_dir_all(&root_dir).unwrap();
+
+        fs::write(
+            root_dir.join("a.hack"),
+            "function a(): int {\n    return \"a\";\n}\n",
+        )
+        .unwrap();
+
+        let config = Arc::new(Config::new(
+            root_dir.to_str().unwrap().to_string(),
+            FxHashSet::default(),
+        ));
+
+        let (mut analysis_result, scan_data) = scan_and_analyze(
+            Vec::new(),
+            None,
+            None,
+            config.clone(),
+            None,
+            1,
+            Arc::new(Logger::DevNull),
+            "",
+            Interner::default(),
+            None,
+            None,
+            None,
+            || {},
+        )
+        .unwrap();
+
+        let cached_issues = analysis_result.emitted_issues.values_mut().next().unwrap();
+        let fresh_issue = cached_issues[0].clone();
+
+        // the same issue a column along still matches, but one on another line is stale
+        cached_issues[0].pos = HPos {
+            start_column: fresh_issue.pos.start_column + 1,
+            end_column: fresh_issue.pos.end_column + 1,
+            ..fresh_issue.pos
+        };
+        cached_issues.push(Issue {
+            pos: HPos {
+                start_line: fresh_issue.pos.start_line + 1,
+                end_line: fresh_issue.pos.end_line + 1,
+                ..fresh_issue.pos
+            },
+            ..fresh_issue
+        });
+
+        let (_, divergence_count) = verify_cached_issues(
+            scan_data,
+            &config,
+            &analysis_result,
+            &FxHashSet::default(),
+            &None,
+            10,
+            1,
+            &Arc::new(Logger::DevNull),
+        )
+        .unwrap();
+
+        fs::remove_dir_all(&root_dir).unwrap();
+
+        assert_eq!(divergence_count, 1);
+    }
+}
@@ -1,10 +1,13 @@
 use hakana_code_info::analysis_result::AnalysisResult;
 use hakana_code_info::code_location::FilePath;
 use hakana_code_info::codebase_info::CodebaseInfo;
-use hakana_code_info::diff::CodebaseDiff;
+use hakana_code_info::data_flow::graph::{DataFlowGraph, GraphKind};
+use hakana_code_info::diff::{CodebaseDiff, IssueDiffUpdates};
+use hakana_code_info::functionlike_identifier::FunctionLikeIdentifier;
 use hakana_code_info::issue::Issue;
 use hakana_code_info::symbol_references::SymbolReferences;
 use hakana_logger::Logger;
+use hakana_logger::Verbosity;
 use hakana_str::Interner;
 use hakana_str::StrId;
 use rustc_hash::FxHashMap;
@@ -20,6 +23,26 @@ pub(crate) struct CachedAnalysis {
     pub existing_issues: FxHashMap<FilePath, Vec<Issue>>,
     pub symbol_references: SymbolReferences,
     pub definition_locations: FxHashMap<FilePath, FxHashMap<(u32, u32), (StrId, StrId)>>,
+    /// Why each symbol or member was excluded from the safe sets, only populated
+    /// when debug logging is enabled
+    pub invalidation_reasons: Option<FxHashMap<(StrId, StrId), InvalidationReason>>,
+    pub issue_diff_updates: IssueDiffUpdates,
+    /// Only the per-file parts of the diff, for reporting on changed files
+    pub codebase_diff: CodebaseDiff,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InvalidationReason {
+    /// The symbol (or member) was directly invalidated by the diff, or references
+    /// an invalidated symbol in its signature
+    InvalidSymbol,
+    /// One or more of the symbol's members were invalidated
+    PartiallyInvalidSymbol,
+    /// The file containing the symbol could not be diffed, so everything in it
+    /// is re-analyzed
+    InvalidScannedFile,
+    /// The file containing the symbol didn't exist in the previous run
+    NewFile,
 }
 
 pub(crate) fn mark_safe_symbols_from_diff(
@@ -28,14 +51,19 @@ pub(crate) fn mark_safe_symbols_from_diff(
     codebase: &CodebaseInfo,
     interner: &mut Interner,
     invalid_scanned_files: FxHashSet<FilePath>,
+    added_files: FxHashSet<FilePath>,
     files_to_analyze: &mut Vec<String>,
     issues_path: &Option<String>,
     references_path: &Option<String>,
     previous_analysis_result: Option<AnalysisResult>,
     max_changes_allowed: usize,
 ) -> CachedAnalysis {
+    let mut previous_program_graph = None;
+
     let (existing_references, mut existing_issues, mut existing_definition_locations) =
         if let Some(previous_analysis_result) = previous_analysis_result {
+            previous_program_graph = Some(previous_analysis_result.program_dataflow_graph);
+
             (
                 previous_analysis_result.symbol_references,
                 previous_analysis_result.emitted_issues,
@@ -63,21 +91,79 @@ pub(crate) fn mark_safe_symbols_from_diff(
             return CachedAnalysis::default();
         };
 
-    let (invalid_symbols_and_members, partially_invalid_symbols) = if let Some(invalid_symbols) =
-        existing_references.get_invalid_symbols(&codebase_diff, max_changes_allowed)
-    {
-        invalid_symbols
-    } else {
-        // this happens when there are too many invalidated symbols
-        return CachedAnalysis::default();
-    };
+    let (mut invalid_symbols_and_members, mut partially_invalid_symbols) =
+        if let Some(invalid_symbols) =
+            existing_references.get_invalid_symbols(&codebase_diff, max_changes_allowed)
+        {
+            invalid_symbols
+        } else {
+            // this happens when there are too many invalidated symbols
+            return CachedAnalysis::default();
+        };
+
+    if let Some(previous_program_graph) = &previous_program_graph {
+        if let GraphKind::WholeProgram(_) = previous_program_graph.kind {
+            for symbol in get_symbols_with_stale_specializations(
+                &codebase_diff,
+                codebase,
+                previous_program_graph,
+            ) {
+                if !symbol.1.is_empty() {
+                    partially_invalid_symbols.insert(symbol.0);
+                }
+
+                invalid_symbols_and_members.insert(symbol);
+            }
+        }
+    }
 
     let mut cached_analysis = CachedAnalysis {
         symbol_references: existing_references,
         ..CachedAnalysis::default()
     };
 
+    if matches!(
+        logger.get_verbosity(),
+        Verbosity::Debugging | Verbosity::DebuggingByLine
+    ) {
+        let invalidation_reasons = get_invalidation_reasons(
+            &codebase_diff,
+            codebase,
+            &invalid_symbols_and_members,
+            &partially_invalid_symbols,
+            &invalid_scanned_files,
+            &added_files,
+        );
+
+        for (symbol, reason) in &invalidation_reasons {
+            logger.log_debug_sync(&format!(
+                "Re-analyzing {}{}: {:?}",
+                interner.lookup(&symbol.0),
+                if symbol.1.is_empty() {
+                    "".to_string()
+                } else {
+                    format!("::{}", interner.lookup(&symbol.1))
+                },
+                reason
+            ));
+        }
+
+        cached_analysis.invalidation_reasons = Some(invalidation_reasons);
+    }
+
+    // symbols in new files have no previous analysis to fall back on, even when a
+    // symbol with the same name used to be defined elsewhere
+    let added_file_symbols = added_files
+        .iter()
+        .filter_map(|file_path| codebase.files.get(file_path))
+        .flat_map(|file_info| file_info.ast_nodes.iter().map(|node| node.name))
+        .collect::<FxHashSet<_>>();
+
     for keep_symbol in &codebase_diff.keep {
+        if added_file_symbols.contains(&keep_symbol.0) {
+            continue;
+        }
+
         if !invalid_symbols_and_members.contains(keep_symbol) {
             if keep_symbol.1.is_empty() {
                 if !partially_invalid_symbols.contains(&keep_symbol.0) {
@@ -110,12 +196,13 @@ pub(crate) fn mark_safe_symbols_from_diff(
     invalid_files.extend(
         invalid_scanned_files
             .iter()
+            .chain(added_files.iter())
             .map(|file_id| interner.lookup(&file_id.0)),
     );
 
     files_to_analyze.retain(|full_path| invalid_files.contains(&full_path.as_str()));
 
-    update_issues_from_diff(
+    cached_analysis.issue_diff_updates = update_issues_from_diff(
         &mut existing_issues,
         &codebase_diff,
         &invalid_symbols_and_members,
@@ -125,20 +212,120 @@ pub(crate) fn mark_safe_symbols_from_diff(
     update_definition_locations_from_diff(&mut existing_definition_locations, &codebase_diff);
     cached_analysis.definition_locations = existing_definition_locations;
 
+    cached_analysis.codebase_diff = CodebaseDiff {
+        diff_map: codebase_diff.diff_map,
+        deletion_ranges_map: codebase_diff.deletion_ranges_map,
+        ..CodebaseDiff::default()
+    };
+
     cached_analysis
 }
 
+/// Taint summaries are only stored in the whole-program graph as specialized nodes at
+/// each call site, so when a function's body changes (but its signature doesn't) the
+/// symbols containing those call sites have to be re-analyzed to rebuild them
+fn get_symbols_with_stale_specializations(
+    codebase_diff: &CodebaseDiff,
+    codebase: &CodebaseInfo,
+    previous_program_graph: &DataFlowGraph,
+) -> FxHashSet<(StrId, StrId)> {
+    let mut call_sites = FxHashSet::default();
+
+    for (symbol, member) in &codebase_diff.keep_signature {
+        let functionlike_id = if member.is_empty() {
+            FunctionLikeIdentifier::Function(*symbol)
+        } else {
+            FunctionLikeIdentifier::Method(*symbol, *member)
+        };
+
+        call_sites.extend(previous_program_graph.get_specialized_call_sites(&functionlike_id));
+    }
+
+    let mut stale_symbols = FxHashSet::default();
+
+    for (file_path, offset) in call_sites {
+        let Some(file_info) = codebase.files.get(&file_path) else {
+            continue;
+        };
+
+        for node in &file_info.ast_nodes {
+            if offset < node.start_offset || offset > node.end_offset {
+                continue;
+            }
+
+            if let Some(child) = node
+                .children
+                .iter()
+                .find(|child| offset >= child.start_offset && offset <= child.end_offset)
+            {
+                stale_symbols.insert((node.name, child.name));
+            } else {
+                stale_symbols.insert((node.name, StrId::EMPTY));
+            }
+        }
+    }
+
+    stale_symbols
+}
+
+fn get_invalidation_reasons(
+    codebase_diff: &CodebaseDiff,
+    codebase: &CodebaseInfo,
+    invalid_symbols_and_members: &FxHashSet<(StrId, StrId)>,
+    partially_invalid_symbols: &FxHashSet<StrId>,
+    invalid_scanned_files: &FxHashSet<FilePath>,
+    added_files: &FxHashSet<FilePath>,
+) -> FxHashMap<(StrId, StrId), InvalidationReason> {
+    let mut invalidation_reasons = FxHashMap::default();
+
+    for keep_symbol in &codebase_diff.keep {
+        if invalid_symbols_and_members.contains(keep_symbol) {
+            invalidation_reasons.insert(*keep_symbol, InvalidationReason::InvalidSymbol);
+        } else if keep_symbol.1.is_empty() && partially_invalid_symbols.contains(&keep_symbol.0) {
+            invalidation_reasons.insert(*keep_symbol, InvalidationReason::PartiallyInvalidSymbol);
+        }
+    }
+
+    for file_path in invalid_scanned_files {
+        if let Some(file_info) = codebase.files.get(file_path) {
+            for node in &file_info.ast_nodes {
+                invalidation_reasons
+                    .entry((node.name, StrId::EMPTY))
+                    .or_insert(InvalidationReason::InvalidScannedFile);
+            }
+        }
+    }
+
+    for file_path in added_files {
+        if let Some(file_info) = codebase.files.get(file_path) {
+            for node in &file_info.ast_nodes {
+                invalidation_reasons.insert((node.name, StrId::EMPTY), InvalidationReason::NewFile);
+            }
+        }
+    }
+
+    invalidation_reasons
+}
+
 fn update_issues_from_diff(
     existing_issues: &mut FxHashMap<FilePath, Vec<Issue>>,
     codebase_diff: &CodebaseDiff,
     invalid_symbols_and_members: &FxHashSet<(StrId, StrId)>,
-) {
+) -> IssueDiffUpdates {
+    let mut updates = IssueDiffUpdates::default();
+
     for (existing_file, file_issues) in existing_issues.iter_mut() {
+        let issue_count = file_issues.len();
+
         file_issues.retain(|issue| {
             !invalid_symbols_and_members.contains(&issue.symbol)
                 && issue.symbol.0 != existing_file.0
         });
 
+        if file_issues.len() != issue_count {
+            updates.dropped_files.insert(*existing_file);
+        }
+
         if file_issues.is_empty() {
             continue;
         }
@@ -156,6 +343,8 @@ fn update_issues_from_diff(
             .unwrap_or(vec![]);
 
         if !deletion_ranges.is_empty() {
+            let issue_count = file_issues.len();
+
             file_issues.retain(|issue| {
                 for (from, to) in &deletion_ranges {
                     if &issue.pos.start_offset >= from && &issue.pos.start_offset <= to {
@@ -165,10 +354,14 @@ fn update_issues_from_diff(
 
                 true
             });
+
+            if file_issues.len() != issue_count {
+                updates.dropped_files.insert(*existing_file);
+            }
         }
 
         if !diff_map.is_empty() {
-            for issue in file_issues {
+            for issue in file_issues.iter_mut() {
                 for (from, to, file_offset, line_offset) in &diff_map {
                     if &issue.pos.start_offset >= from && &issue.pos.start_offset <= to {
                         issue.pos.start_offset =
@@ -178,12 +371,24 @@ fn update_issues_from_diff(
                         issue.pos.start_line =
                             ((issue.pos.start_line as isize) + line_offset) as u32;
                         issue.pos.end_line = ((issue.pos.end_line as isize) + line_offset) as u32;
+                        updates.shifted_files.insert(*existing_file);
                         break;
                     }
                 }
             }
+
+            // shifting can move a cached issue onto the spot of an identical one, which
+            // would then be reported twice. Issues that only share a fingerprint but sit
+            // on different lines are distinct, so both are kept.
+            let mut seen_issues = FxHashSet::default();
+
+            file_issues.retain(|issue| {
+                seen_issues.insert((issue.get_fingerprint(), issue.pos.start_offset))
+            });
         }
     }
+
+    updates
 }
 
 fn update_definition_locations_from_diff(
@@ -241,3 +446,171 @@ fn update_definition_locations_from_diff(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hakana_code_info::ast_signature::DefSignatureNode;
+    use hakana_code_info::code_location::HPos;
+    use hakana_code_info::file_info::FileInfo;
+    use hakana_code_info::issue::IssueKind;
+
+    fn file_with_function(name: StrId) -> FileInfo {
+        FileInfo {
+            ast_nodes: vec![DefSignatureNode {
+                name,
+                is_function: true,
+                is_constant: false,
+                start_offset: 0,
+                end_offset: 0,
+                start_line: 1,
+                end_line: 1,
+                start_colum: 1,
+                end_column: 1,
+                children: vec![],
+                signature_hash: 0,
+                body_hash: None,
+            }],
+            ..FileInfo::default()
+        }
+    }
+
+    fn issue_in(file_path: FilePath, symbol: StrId, start_offset: u32) -> Issue {
+        Issue::new(
+            IssueKind::InvalidArgument,
+            String::new(),
+            HPos {
+                file_path,
+                start_offset,
+                end_offset: start_offset + 1,
+                start_line: 1,
+                end_line: 1,
+                start_column: 1,
+                end_column: 2,
+            },
+            &Some(FunctionLikeIdentifier::Function(symbol)),
+        )
+    }
+
+    #[test]
+    fn issue_diff_updates_list_shifted_and_dropped_files() {
+        let mut interner = Interner::default();
+        let mut intern = |name: &str| interner.intern(name.to_string());
+
+        let foo = intern("foo");
+        let invalid = intern("invalid");
+        let shifted_file = FilePath(intern("shifted.hack"));
+        let other_shifted_file = FilePath(intern("other_shifted.hack"));
+        let unchanged_file = FilePath(intern("unchanged.hack"));
+        let deleted_file = FilePath(intern("deleted_range.hack"));
+        let invalid_file = FilePath(intern("invalid.hack"));
+
+        let mut existing_issues = FxHashMap::from_iter([
+            (shifted_file, vec![issue_in(shifted_file, foo, 10)]),
+            (
+                other_shifted_file,
+                vec![
+                    issue_in(other_shifted_file, foo, 5),
+                    issue_in(other_shifted_file, foo, 50),
+                ],
+            ),
+            (unchanged_file, vec![issue_in(unchanged_file, foo, 10)]),
+            (deleted_file, vec![issue_in(deleted_file, foo, 10)]),
+            (invalid_file, vec![issue_in(invalid_file, invalid, 10)]),
+        ]);
+
+        let codebase_diff = CodebaseDiff {
+            diff_map: FxHashMap::from_iter([
+                (shifted_file, vec![(0, 20, 4, 1)]),
+                (other_shifted_file, vec![(40, 60, -2, 0)]),
+            ]),
+            deletion_ranges_map: FxHashMap::from_iter([(deleted_file, vec![(5, 15)])]),
+            ..CodebaseDiff::default()
+        };
+
+        let updates = update_issues_from_diff(
+            &mut existing_issues,
+            &codebase_diff,
+            &FxHashSet::from_iter([(invalid, StrId::EMPTY)]),
+        );
+
+        assert_eq!(
+            updates.shifted_files,
+            codebase_diff
+                .diff_map
+                .iter()
+                .filter(|(_, diff)| !diff.is_empty())
+                .map(|(file_path, _)| *file_path)
+                .collect::<FxHashSet<_>>()
+        );
+        assert_eq!(
+            updates.dropped_files,
+            FxHashSet::from_iter([deleted_file, invalid_file])
+        );
+        assert_eq!(existing_issues[&shifted_file][0].pos.start_offset, 14);
+        assert_eq!(existing_issues[&shifted_file][0].pos.start_line, 2);
+        assert_eq!(
+            existing_issues[&other_shifted_file]
+                .iter()
+                .map(|issue| issue.pos.start_offset)
+                .collect::<Vec<_>>(),
+            vec![5, 48]
+        );
+        assert_eq!(existing_issues[&unchanged_file][0].pos.start_offset, 10);
+    }
+
+    #[test]
+    fn invalidation_reasons_are_attributed_to_their_cause() {
+        let mut interner = Interner::default();
+        let mut intern = |name: &str| interner.intern(name.to_string());
+
+        let unchanged = intern("Unchanged");
+        let invalid = intern("Invalid");
+        let partially_invalid = intern("PartiallyInvalid");
+        let invalid_member = intern("invalidMember");
+        let unparseable = intern("unparseable");
+        let added = intern("added");
+        let unparseable_file = FilePath(intern("unparseable.hack"));
+        let added_file = FilePath(intern("added.hack"));
+
+        let codebase_diff = CodebaseDiff {
+            keep: FxHashSet::from_iter([
+                (unchanged, StrId::EMPTY),
+                (invalid, StrId::EMPTY),
+                (partially_invalid, StrId::EMPTY),
+            ]),
+            ..CodebaseDiff::default()
+        };
+
+        let mut codebase = CodebaseInfo::new();
+        codebase
+            .files
+            .insert(unparseable_file, file_with_function(unparseable));
+        codebase.files.insert(added_file, file_with_function(added));
+
+        let invalidation_reasons = get_invalidation_reasons(
+            &codebase_diff,
+            &codebase,
+            &FxHashSet::from_iter([(invalid, StrId::EMPTY), (partially_invalid, invalid_member)]),
+            &FxHashSet::from_iter([partially_invalid]),
+            &FxHashSet::from_iter([unparseable_file]),
+            &FxHashSet::from_iter([added_file]),
+        );
+
+        assert_eq!(
+            invalidation_reasons,
+            FxHashMap::from_iter([
+                ((invalid, StrId::EMPTY), InvalidationReason::InvalidSymbol),
+                (
+                    (partially_invalid, StrId::EMPTY),
+                    InvalidationReason::PartiallyInvalidSymbol
+                ),
+                (
+                    (unparseable, StrId::EMPTY),
+                    InvalidationReason::InvalidScannedFile
+                ),
+                ((added, StrId::EMPTY), InvalidationReason::NewFile),
+            ])
+        );
+    }
+}
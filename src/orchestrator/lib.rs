@@ -1,6 +1,9 @@
 pub(crate) mod populator;
 
-use analyzer::analyze_files;
+pub use analyzer::analyze_function;
+use analyzer::{analyze_files, set_char_columns};
+use cache::write_analysis_cache_file;
+use cache_verifier::verify_cached_issues;
 use diff::{mark_safe_symbols_from_diff, CachedAnalysis};
 use file::{FileStatus, VirtualFileSystem};
 use hakana_aast_helper::get_aast_for_path_and_contents;
@@ -9,11 +12,11 @@ use hakana_analyzer::dataflow::program_analyzer::{find_connections, find_tainted
 use hakana_code_info::analysis_result::AnalysisResult;
 use hakana_code_info::code_location::{FilePath, HPos};
 use hakana_code_info::codebase_info::CodebaseInfo;
-use hakana_code_info::data_flow::graph::{GraphKind, WholeProgramKind};
+use hakana_code_info::data_flow::graph::{GraphKind, TraversalCounts, WholeProgramKind};
 use hakana_code_info::file_info::ParserError;
 use hakana_code_info::issue::{Issue, IssueKind};
 use hakana_code_info::symbol_references::SymbolReferences;
-use hakana_logger::Logger;
+use hakana_logger::{Logger, Verbosity};
 use hakana_str::{Interner, StrId};
 use indicatif::ProgressBar;
 use oxidized::aast;
@@ -31,10 +34,12 @@ use tower_lsp::lsp_types::MessageType;
 #[cfg(not(target_arch = "wasm32"))]
 use tower_lsp::Client;
 use unused_symbols::find_unused_definitions;
+pub use wasm::analyze_source;
 
 mod analyzer;
 mod ast_differ;
 mod cache;
+mod cache_verifier;
 mod diff;
 pub mod file;
 pub mod scanner;
@@ -107,6 +112,7 @@ pub async fn scan_and_analyze_async(
         file_system,
         mut files_to_analyze,
         invalid_files,
+        added_files,
     } = scan_files(
         &all_scanned_dirs,
         None,
@@ -126,6 +132,7 @@ pub async fn scan_and_analyze_async(
             &codebase,
             &mut interner,
             invalid_files,
+            added_files,
             &mut files_to_analyze,
             &None,
             &None,
@@ -155,6 +162,12 @@ pub async fn scan_and_analyze_async(
         hook.after_populate(&codebase, &interner, &config);
     }
 
+    let analyzed_files = files_to_analyze
+        .iter()
+        .filter_map(|file_path| interner.get(file_path))
+        .map(FilePath)
+        .collect::<FxHashSet<_>>();
+
     let (analysis_result, arc_scan_data) = get_analysis_ready(
         &config,
         codebase,
@@ -189,6 +202,10 @@ pub async fn scan_and_analyze_async(
 
     let mut analysis_result = (*analysis_result.lock().unwrap()).clone();
 
+    analysis_result.issue_diff_updates = cached_analysis.issue_diff_updates;
+    analysis_result.codebase_diff = cached_analysis.codebase_diff;
+    analysis_result.analyzed_files = analyzed_files;
+
     let mut scan_data = Arc::try_unwrap(arc_scan_data).unwrap();
 
     add_invalid_files(&scan_data, &mut analysis_result);
@@ -237,6 +254,7 @@ pub fn scan_and_analyze<F: FnOnce()>(
         file_system,
         mut files_to_analyze,
         invalid_files,
+        added_files,
     } = scan_files(
         &all_scanned_dirs,
         cache_dir,
@@ -277,6 +295,7 @@ pub fn scan_and_analyze<F: FnOnce()>(
             &codebase,
             &mut interner,
             invalid_files,
+            added_files,
             &mut files_to_analyze,
             &get_issues_path(cache_dir),
             &get_references_path(cache_dir),
@@ -287,6 +306,19 @@ pub fn scan_and_analyze<F: FnOnce()>(
         CachedAnalysis::default()
     };
 
+    if let Some(invalidation_reasons) = &cached_analysis.invalidation_reasons {
+        logger.log_debug_sync(&format!(
+            "{} symbols invalidated by diff",
+            invalidation_reasons.len()
+        ));
+    }
+
+    logger.log_debug_sync(&format!(
+        "Cached issues shifted in {} files, dropped in {} files",
+        cached_analysis.issue_diff_updates.shifted_files.len(),
+        cached_analysis.issue_diff_updates.dropped_files.len()
+    ));
+
     logger.log_sync("Calculating symbol inheritance");
 
     let populating_now = Instant::now();
@@ -313,6 +345,12 @@ pub fn scan_and_analyze<F: FnOnce()>(
         ));
     }
 
+    let analyzed_files = files_to_analyze
+        .iter()
+        .filter_map(|file_path| interner.get(file_path))
+        .map(FilePath)
+        .collect::<FxHashSet<_>>();
+
     let (analysis_result, arc_scan_data) = get_analysis_ready(
         &config,
         codebase,
@@ -328,8 +366,27 @@ pub fn scan_and_analyze<F: FnOnce()>(
 
     let mut pure_file_analysis_time = Duration::default();
 
+    let log_traversal_counts = matches!(
+        logger.get_verbosity(),
+        Verbosity::Debugging | Verbosity::DebuggingByLine
+    );
+
+    if log_traversal_counts {
+        analysis_result
+            .lock()
+            .unwrap()
+            .program_dataflow_graph
+            .traversal_counts = Some(TraversalCounts::default());
+    }
+
     chaos_monkey();
 
+    let reanalyzed_files = if config.verify_cached_issues.is_some() {
+        files_to_analyze.iter().cloned().collect::<FxHashSet<_>>()
+    } else {
+        FxHashSet::default()
+    };
+
     analyze_files(
         files_to_analyze,
         arc_scan_data.clone(),
@@ -349,14 +406,42 @@ pub fn scan_and_analyze<F: FnOnce()>(
         ));
     }
 
+    if let Some(traversal_counts) = analysis_result
+        .lock()
+        .unwrap()
+        .program_dataflow_graph
+        .traversal_counts
+        .take()
+    {
+        for (path_kind, count) in traversal_counts.lock().unwrap().iter() {
+            logger.log_debug_sync(&format!("Traversed {} {} paths", count, path_kind));
+        }
+    }
+
     let mut analysis_result = (*analysis_result.lock().unwrap()).clone();
 
     analysis_result.time_in_analysis = pure_file_analysis_time;
+    analysis_result.issue_diff_updates = cached_analysis.issue_diff_updates;
+    analysis_result.codebase_diff = cached_analysis.codebase_diff;
+    analysis_result.analyzed_files = analyzed_files;
 
     cache_analysis_data(cache_dir, &analysis_result)?;
 
     let mut scan_data = Arc::try_unwrap(arc_scan_data).unwrap();
 
+    if let (true, Some(sample_size)) = (config.ast_diff, config.verify_cached_issues) {
+        (scan_data, _) = verify_cached_issues(
+            scan_data,
+            &config,
+            &analysis_result,
+            &reanalyzed_files,
+            &ignored_paths,
+            sample_size,
+            threads,
+            &logger,
+        )?;
+    }
+
     add_invalid_files(&scan_data, &mut analysis_result);
 
     if config.find_unused_definitions {
@@ -371,6 +456,35 @@ pub fn scan_and_analyze<F: FnOnce()>(
     }
 
     if let GraphKind::WholeProgram(whole_program_kind) = config.graph_kind {
+        let is_debugging = matches!(
+            logger.get_verbosity(),
+            Verbosity::Debugging | Verbosity::DebuggingByLine
+        );
+
+        // pruning shouldn't change any results, which is worth checking when debugging
+        let unpruned_graph = if is_debugging && whole_program_kind == WholeProgramKind::Taint {
+            Some(analysis_result.program_dataflow_graph.clone())
+        } else {
+            None
+        };
+
+        let pruned_count = analysis_result.program_dataflow_graph.prune_orphans();
+
+        logger.log_debug_sync(&format!(
+            "Pruned {} orphaned data-flow vertices",
+            pruned_count
+        ));
+
+        if let Some(unpruned_graph) = unpruned_graph {
+            let taint_diff = analysis_result
+                .program_dataflow_graph
+                .diff_taint_flows(&unpruned_graph);
+
+            if !taint_diff.is_empty() {
+                logger.log_debug_sync(&format!("Pruning changed taint flows: {:?}", taint_diff));
+            }
+        }
+
         let issues = match whole_program_kind {
             WholeProgramKind::Taint => find_tainted_data(
                 &analysis_result.program_dataflow_graph,
@@ -395,6 +509,10 @@ pub fn scan_and_analyze<F: FnOnce()>(
         }
     }
 
+    if config.char_columns {
+        set_char_columns(&mut analysis_result, &scan_data.interner);
+    }
+
     Ok((analysis_result, scan_data))
 }
 
@@ -431,15 +549,13 @@ fn cache_analysis_data(
     analysis_result: &AnalysisResult,
 ) -> Result<(), io::Error> {
     if let Some(references_path) = get_references_path(cache_dir) {
-        let mut symbols_file = fs::File::create(references_path).unwrap();
         let serialized_symbol_references =
             bincode::serialize(&analysis_result.symbol_references).unwrap();
-        symbols_file.write_all(&serialized_symbol_references)?;
+        write_analysis_cache_file(&references_path, &serialized_symbol_references)?;
     }
     if let Some(issues_path) = get_issues_path(cache_dir) {
-        let mut issues_file = fs::File::create(issues_path).unwrap();
         let serialized_issues = bincode::serialize(&analysis_result.emitted_issues).unwrap();
-        issues_file.write_all(&serialized_issues)?;
+        write_analysis_cache_file(&issues_path, &serialized_issues)?;
     };
     Ok(())
 }
@@ -23,6 +23,8 @@ pub fn populate_codebase(
     safe_symbol_members: FxHashSet<(StrId, StrId)>,
     config: &Config,
 ) {
+    codebase.bump_generation();
+
     let new_classlike_names = codebase
         .classlike_infos
         .iter()
@@ -46,6 +46,8 @@ pub struct ScanFilesResult {
     pub codebase_diff: CodebaseDiff,
     pub files_to_analyze: Vec<String>,
     pub invalid_files: FxHashSet<FilePath>,
+    /// Files that didn't exist in the previous run
+    pub added_files: FxHashSet<FilePath>,
 }
 
 pub fn scan_files(
@@ -205,11 +207,16 @@ pub fn scan_files(
     invalidate_changed_codebase_elements(&mut codebase, &changed_files);
 
     let mut files_to_scan = vec![];
+    let mut added_files = FxHashSet::default();
 
     for (target_file, status) in &file_statuses {
         if matches!(status, FileStatus::Added(..) | FileStatus::Modified(..)) {
             files_to_scan.push(target_file);
         }
+
+        if let FileStatus::Added(..) = status {
+            added_files.insert(*target_file);
+        }
     }
 
     let mut existing_changed_files = FxHashMap::default();
@@ -442,6 +449,7 @@ pub fn scan_files(
         files_to_analyze,
         file_system,
         invalid_files: invalid_files.into_iter().collect(),
+        added_files,
     })
 }
 
@@ -609,22 +609,29 @@ pub(crate) fn find_unused_definitions(
                 }
             }
 
-            if !config.allow_issue_kind_in_file(&IssueKind::UnusedTypeDefinition, file_path) {
+            if !config.allow_issue_kind_in_file(&IssueKind::UnusedTypeAlias, file_path) {
                 continue;
             }
 
+            if type_definition_info
+                .attributes
+                .iter()
+                .any(|a| a.name == StrId::HAKANA_PUBLIC_API)
+            {
+                continue;
+            }
+
+            // self-references are never recorded, so an alias that only appears in its own
+            // definition is reported here too
             if !referenced_symbols_and_members.contains(&(*type_name, StrId::EMPTY)) {
                 let issue = Issue::new(
-                    IssueKind::UnusedTypeDefinition,
-                    format!("Unused type definition {}", interner.lookup(type_name)),
+                    IssueKind::UnusedTypeAlias,
+                    format!("Unused type alias {}", interner.lookup(type_name)),
                     *pos,
                     &Some(FunctionLikeIdentifier::Function(*type_name)),
                 );
 
-                if config
-                    .issues_to_fix
-                    .contains(&IssueKind::UnusedTypeDefinition)
-                {
+                if config.issues_to_fix.contains(&IssueKind::UnusedTypeAlias) {
                     analysis_result
                         .replacements
                         .entry(pos.file_path)
@@ -651,6 +658,55 @@ pub(crate) fn find_unused_definitions(
             }
         }
     }
+
+    // a constant looked up by a name that's only known at runtime could be any of them
+    let has_dynamic_constant_lookup =
+        referenced_symbols_and_members.contains(&(StrId::CONSTANT, StrId::EMPTY));
+
+    'outer3: for (constant_name, constant_info) in &codebase.constant_infos {
+        if constant_info.user_defined && !has_dynamic_constant_lookup {
+            let pos = &constant_info.pos;
+            let file_path = interner.lookup(&pos.file_path.0);
+
+            if let Some(ignored_paths) = ignored_paths {
+                for ignored_path in ignored_paths {
+                    if file_path.matches(ignored_path.as_str()).count() > 0 {
+                        continue 'outer3;
+                    }
+                }
+            }
+
+            if !config.allow_issue_kind_in_file(&IssueKind::UnusedConstant, file_path)
+                || constant_info
+                    .suppressed_issues
+                    .iter()
+                    .any(|(i, _)| i == &IssueKind::UnusedConstant)
+            {
+                continue;
+            }
+
+            if !referenced_symbols_and_members.contains(&(*constant_name, StrId::EMPTY)) {
+                let issue = Issue::new(
+                    IssueKind::UnusedConstant,
+                    format!("Unused constant {}", interner.lookup(constant_name)),
+                    *pos,
+                    &Some(FunctionLikeIdentifier::Function(*constant_name)),
+                );
+
+                if config.can_add_issue(&issue) {
+                    *analysis_result
+                        .issue_counts
+                        .entry(issue.kind.clone())
+                        .or_insert(0) += 1;
+                    analysis_result
+                        .emitted_definition_issues
+                        .entry(pos.file_path)
+                        .or_default()
+                        .push(issue);
+                }
+            }
+        }
+    }
 }
 
 fn add_service_calls_attributes(
@@ -93,6 +93,68 @@ pub fn scan_and_analyze_single_file(
     Ok((analysis_result, interner))
 }
 
+/// Scans and analyzes in-memory sources against the bundled HHI and HSL definitions,
+/// using the default config and without reading anything from disk. Files can refer to
+/// symbols declared in any of the other files.
+pub fn analyze_source(files: Vec<(String, String)>) -> AnalysisResult {
+    let (mut codebase, interner, _) = get_single_file_codebase(vec![]);
+
+    let analysis_config = Config::new("".to_string(), FxHashSet::default());
+
+    let mut threaded_interner = ThreadedInterner::new(Arc::new(Mutex::new(interner)));
+
+    let mut all_resolved_names = vec![];
+
+    for (path, file_contents) in &files {
+        // files with syntax errors still need an id so their errors can be reported
+        threaded_interner.intern(path.clone());
+
+        all_resolved_names.push(
+            scan_single_file(
+                &mut codebase,
+                &mut threaded_interner,
+                path.clone(),
+                file_contents.clone(),
+            )
+            .unwrap_or_default(),
+        );
+    }
+
+    let interner = Arc::try_unwrap(threaded_interner.parent)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+
+    let mut symbol_references = SymbolReferences::new();
+
+    populate_codebase(
+        &mut codebase,
+        &interner,
+        &mut symbol_references,
+        FxHashSet::default(),
+        FxHashSet::default(),
+        &analysis_config,
+    );
+
+    let mut analysis_result =
+        AnalysisResult::new(analysis_config.graph_kind, SymbolReferences::new());
+
+    for ((path, file_contents), resolved_names) in files.into_iter().zip(all_resolved_names) {
+        if let Ok(file_analysis_result) = analyze_single_file(
+            path,
+            file_contents,
+            &codebase,
+            &interner,
+            &resolved_names,
+            &analysis_config,
+        ) {
+            analysis_result.extend(file_analysis_result);
+        }
+    }
+
+    analysis_result
+}
+
 pub fn get_single_file_codebase(
     additional_files: Vec<&str>,
 ) -> (CodebaseInfo, Interner, VirtualFileSystem) {
@@ -298,3 +360,45 @@ pub fn analyze_single_file(
 
     Ok(analysis_result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_issue_kinds(analysis_result: &AnalysisResult) -> Vec<IssueKind> {
+        analysis_result
+            .emitted_issues
+            .values()
+            .flatten()
+            .map(|issue| issue.kind.clone())
+            .collect()
+    }
+
+    #[test]
+    fn analyze_source_resolves_symbols_across_files() {
+        let name_file = (
+            "name.hack".to_string(),
+            "function get_name(): string {\n    return 'hakana';\n}".to_string(),
+        );
+        let main_file = (
+            "main.hack".to_string(),
+            concat!(
+                "function takes_int(int $i): void {}\n",
+                "\n",
+                "function main(): void {\n",
+                "    takes_int(get_name());\n",
+                "}",
+            )
+            .to_string(),
+        );
+
+        // on its own, the file can't see get_name
+        assert!(get_issue_kinds(&analyze_source(vec![main_file.clone()]))
+            .contains(&IssueKind::NonExistentFunction));
+
+        assert_eq!(
+            get_issue_kinds(&analyze_source(vec![name_file, main_file])),
+            vec![IssueKind::InvalidArgument]
+        );
+    }
+}
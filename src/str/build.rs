@@ -258,6 +258,7 @@ fn main() -> Result<()> {
         "Hakana\\Immutable",
         "Hakana\\MustUse",
         "Hakana\\NotTestOnly",
+        "Hakana\\PublicApi",
         "Hakana\\RequestHandler",
         "Hakana\\SecurityAnalysis\\IgnorePath",
         "Hakana\\SecurityAnalysis\\IgnorePathIfTrue",
@@ -278,6 +279,7 @@ fn main() -> Result<()> {
         "Throwable",
         "XHPChild",
         "__DIR__",
+        "__Deprecated",
         "__DynamicallyCallable",
         "__EntryPoint",
         "__FILE__",
@@ -286,6 +288,7 @@ fn main() -> Result<()> {
         "__PHP_Incomplete_Class",
         "__Sealed",
         "__construct",
+        "__invoke",
         "abs",
         "addcslashes",
         "addslashes",
@@ -316,6 +319,7 @@ fn main() -> Result<()> {
         "chunk_split",
         "class_exists",
         "coerce",
+        "constant",
         "convert_uudecode",
         "convert_uuencode",
         "count",
@@ -334,6 +338,7 @@ fn main() -> Result<()> {
         "debug_backtrace",
         "decbin",
         "dechex",
+        "defined",
         "deg2rad",
         "dirname",
         "echo",